@@ -0,0 +1,183 @@
+use crate::models::{Decision, IpcRequest};
+use chrono::{DateTime, Local, NaiveDate, Utc};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::sync::Notify;
+
+/// Sessions idle for longer than this are no longer listed as active.
+const ACTIVE_SESSION_WINDOW: chrono::Duration = chrono::Duration::hours(2);
+
+/// Last known state of one Claude Code session.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionInfo {
+    pub session_id: String,
+    pub cwd: String,
+    pub last_activity: DateTime<Utc>,
+}
+
+/// Request outcomes counted since local midnight.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DailyStats {
+    pub approved: u32,
+    pub denied: u32,
+    pub timed_out: u32,
+}
+
+#[derive(Default)]
+struct ActivityInner {
+    sessions: HashMap<String, SessionInfo>,
+    stats_date: Option<NaiveDate>,
+    stats: DailyStats,
+}
+
+/// Tracks session activity and daily outcome counts, and signals every
+/// change to listeners such as the pinned dashboard.
+#[derive(Default)]
+pub struct Activity {
+    inner: Mutex<ActivityInner>,
+    changed: Notify,
+}
+
+pub type ActivityState = Arc<Activity>;
+
+impl Activity {
+    /// Records a new permission request from `request`'s session.
+    pub fn touch_session(&self, request: &IpcRequest) {
+        self.touch_session_at(request, Utc::now());
+    }
+
+    fn touch_session_at(&self, request: &IpcRequest, now: DateTime<Utc>) {
+        self.inner
+            .lock()
+            .expect("activity lock poisoned")
+            .sessions
+            .insert(
+                request.session_id.clone(),
+                SessionInfo {
+                    session_id: request.session_id.clone(),
+                    cwd: request.cwd.clone(),
+                    last_activity: now,
+                },
+            );
+        self.changed.notify_one();
+    }
+
+    /// Counts a resolved request in today's statistics.
+    pub fn record_outcome(&self, decision: &Decision) {
+        self.record_outcome_on(decision, Local::now().date_naive());
+    }
+
+    fn record_outcome_on(&self, decision: &Decision, today: NaiveDate) {
+        {
+            let mut inner = self.inner.lock().expect("activity lock poisoned");
+            if inner.stats_date != Some(today) {
+                inner.stats_date = Some(today);
+                inner.stats = DailyStats::default();
+            }
+            match decision {
+                Decision::Allow | Decision::AlwaysAllow => inner.stats.approved += 1,
+                Decision::Deny | Decision::Reply => inner.stats.denied += 1,
+                Decision::Timeout => inner.stats.timed_out += 1,
+            }
+        }
+        self.changed.notify_one();
+    }
+
+    /// Waits for the next state change.
+    pub async fn changed(&self) {
+        self.changed.notified().await;
+    }
+
+    /// Sessions active within the last two hours, most recent first.
+    pub fn active_sessions(&self, now: DateTime<Utc>) -> Vec<SessionInfo> {
+        let mut inner = self.inner.lock().expect("activity lock poisoned");
+        inner
+            .sessions
+            .retain(|_, s| now - s.last_activity <= ACTIVE_SESSION_WINDOW);
+        let mut sessions: Vec<SessionInfo> = inner.sessions.values().cloned().collect();
+        drop(inner);
+        sessions.sort_by_key(|s| std::cmp::Reverse(s.last_activity));
+        sessions
+    }
+
+    /// Today's outcome counts (zero after local midnight until the next outcome).
+    pub fn today(&self) -> DailyStats {
+        self.stats_on(Local::now().date_naive())
+    }
+
+    fn stats_on(&self, today: NaiveDate) -> DailyStats {
+        let inner = self.inner.lock().expect("activity lock poisoned");
+        if inner.stats_date == Some(today) {
+            inner.stats.clone()
+        } else {
+            DailyStats::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    fn make_request(session_id: &str) -> IpcRequest {
+        IpcRequest {
+            request_id: Uuid::new_v4(),
+            tool_name: "Bash".to_string(),
+            tool_input: serde_json::json!({"command": "ls"}),
+            cwd: format!("/home/user/{session_id}"),
+            session_id: session_id.to_string(),
+            permission_suggestions: vec![],
+            assistant_context: None,
+            first_prompt: None,
+        }
+    }
+
+    #[test]
+    fn active_sessions_sorted_and_pruned() {
+        let activity = Activity::default();
+        let now = Utc::now();
+        activity.touch_session_at(&make_request("old"), now - chrono::Duration::hours(3));
+        activity.touch_session_at(&make_request("a"), now - chrono::Duration::minutes(10));
+        activity.touch_session_at(&make_request("b"), now - chrono::Duration::minutes(1));
+
+        let sessions = activity.active_sessions(now);
+        let ids: Vec<&str> = sessions.iter().map(|s| s.session_id.as_str()).collect();
+        assert_eq!(ids, ["b", "a"]);
+    }
+
+    #[test]
+    fn outcomes_counted_per_day() {
+        let activity = Activity::default();
+        let monday = NaiveDate::from_ymd_opt(2026, 1, 5).unwrap();
+        let tuesday = monday.succ_opt().unwrap();
+
+        activity.record_outcome_on(&Decision::Allow, monday);
+        activity.record_outcome_on(&Decision::AlwaysAllow, monday);
+        activity.record_outcome_on(&Decision::Deny, monday);
+        activity.record_outcome_on(&Decision::Timeout, monday);
+        assert_eq!(
+            activity.stats_on(monday),
+            DailyStats {
+                approved: 2,
+                denied: 1,
+                timed_out: 1
+            }
+        );
+
+        // A new day starts from zero
+        assert_eq!(activity.stats_on(tuesday), DailyStats::default());
+        activity.record_outcome_on(&Decision::Reply, tuesday);
+        assert_eq!(activity.stats_on(tuesday).denied, 1);
+    }
+
+    #[tokio::test]
+    async fn changes_wake_listeners() {
+        let activity = Arc::new(Activity::default());
+        activity.touch_session(&make_request("s"));
+        // notify_one stores a permit, so an already-signalled change is not lost
+        tokio::time::timeout(std::time::Duration::from_secs(1), activity.changed())
+            .await
+            .unwrap();
+    }
+}
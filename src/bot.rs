@@ -1,12 +1,32 @@
+use crate::activity::ActivityState;
+use crate::burst::Bursts;
+use crate::channel::ApprovalChannel;
 use crate::config::Config;
-use crate::ipc::server::{self, PendingMap};
-use crate::models::{IpcRequest, SentMessage};
-use crate::telegram::handler::{self, ReplyState};
+use crate::control::channel::{ControlChannel, ControlState};
+use crate::discord::channel::{DiscordChannel, DiscordState};
+use crate::email::channel::{EmailChannel, EmailState};
+use crate::ipc::server::{self, PendingMap, ServerContext};
+use crate::matrix::channel::{MatrixChannel, MatrixState};
+use crate::ntfy::channel::{NtfyChannel, NtfyState};
+use crate::quiet::{Quiet, QuietState};
+use crate::rota::{OnCall, OnCallState};
+use crate::signal::channel::{SignalChannel, SignalState};
+use crate::slack::channel::{SlackChannel, SlackState};
+use crate::telegram::channel::{TelegramChannel, TelegramState};
+use crate::telegram::command::Command;
+use crate::telegram::dashboard;
+use crate::telegram::handler;
+use crate::telegram::topics::{TopicState, Topics};
+use crate::web::channel::{WebChannel, WebState};
+use crate::webhook::channel::{WebhookChannel, WebhookState};
 use dashmap::DashMap;
 use std::sync::Arc;
-use teloxide::dispatching::UpdateFilterExt;
+use teloxide::dispatching::{UpdateFilterExt, UpdateHandler};
 use teloxide::dptree;
 use teloxide::prelude::*;
+use teloxide::types::{Me, MessageReactionUpdated};
+use teloxide::utils::command::BotCommands;
+use tokio::time::{Duration, Instant};
 use tokio_util::sync::CancellationToken;
 
 pub async fn run_bot(config: Config) -> anyhow::Result<()> {
@@ -18,51 +38,56 @@ pub async fn run_bot(config: Config) -> anyhow::Result<()> {
     let bot = Bot::new(&config.telegram_bot_token);
     let config = Arc::new(config);
     let pending_map: PendingMap = Arc::new(DashMap::new());
-    let reply_state: ReplyState = Arc::new(DashMap::new());
+    let on_call: OnCallState = Arc::new(OnCall::new(config.rota.clone()));
+    let topics: TopicState = Arc::new(Topics::load(
+        config.forum_topics,
+        crate::config::state_file_path("topics.json")?,
+    ));
+    let activity: ActivityState = Arc::default();
+    let quiet: QuietState = Arc::new(Quiet::new(config.quiet_hours.clone()));
+    let telegram: TelegramState =
+        Arc::new(TelegramChannel::new(bot.clone(), quiet.clone(), topics));
     let cancel_token = CancellationToken::new();
 
     spawn_signal_handler(cancel_token.clone());
 
-    let handler = {
-        let callback_handler = Update::filter_callback_query().endpoint({
-            let config = config.clone();
-            let pending = pending_map.clone();
-            let reply = reply_state.clone();
-            move |bot: Bot, query: CallbackQuery| {
-                let config = config.clone();
-                let pending = pending.clone();
-                let reply = reply.clone();
-                async move { handler::handle_callback(bot, query, config, pending, reply).await }
-            }
-        });
-
-        let message_handler = Update::filter_message().endpoint({
-            let config = config.clone();
-            let pending = pending_map.clone();
-            let reply = reply_state;
-            move |bot: Bot, msg: Message| {
-                let config = config.clone();
-                let pending = pending.clone();
-                let reply = reply.clone();
-                async move { handler::handle_message(bot, msg, config, pending, reply).await }
-            }
-        });
+    let mut channels: Vec<Arc<dyn ApprovalChannel>> = vec![telegram.clone()];
+    channels.extend(start_other_channels(&config, &pending_map, &cancel_token));
 
-        dptree::entry()
-            .branch(callback_handler)
-            .branch(message_handler)
+    let ctx = ServerContext {
+        config: config.clone(),
+        pending_map: pending_map.clone(),
+        channels: Arc::new(channels),
+        on_call,
+        activity: activity.clone(),
+        quiet,
+        mutes: Arc::default(),
+        pauses: Arc::default(),
+        bursts: Arc::new(Bursts::new(Duration::from_millis(
+            config.coalesce_window_ms,
+        ))),
+        started_at: Instant::now(),
     };
 
+    if let Err(e) = bot.set_my_commands(Command::bot_commands()).await {
+        tracing::warn!("Failed to register the command menu: {e}");
+    }
+
+    let handler = update_handler(&ctx, &telegram);
+
     let cancel_for_dispatcher = cancel_token.clone();
 
     // Run socket server and Telegram dispatcher concurrently
-    let socket_server = server::run_server(
-        &socket_path,
-        cancel_token.clone(),
+    let socket_server = server::run_server(&socket_path, cancel_token.clone(), ctx);
+
+    tokio::spawn(dashboard::run_dashboard(
         bot.clone(),
+        telegram,
         config.clone(),
         pending_map.clone(),
-    );
+        activity,
+        cancel_token.clone(),
+    ));
 
     let dispatcher = async {
         Box::pin(
@@ -97,6 +122,138 @@ pub async fn run_bot(config: Config) -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Approval channels configured besides Telegram, with their endpoints
+/// started.
+fn start_other_channels(
+    config: &Config,
+    pending_map: &PendingMap,
+    cancel_token: &CancellationToken,
+) -> Vec<Arc<dyn ApprovalChannel>> {
+    let mut channels: Vec<Arc<dyn ApprovalChannel>> = Vec::new();
+    if config.tui {
+        let control: ControlState = Arc::new(ControlChannel::new(pending_map.clone()));
+        spawn_endpoint("Control socket", {
+            let control = control.clone();
+            let path = config.effective_control_socket_path();
+            let cancel_token = cancel_token.clone();
+            async move { control.serve(&path, cancel_token).await }
+        });
+        channels.push(control);
+    }
+    if let Some(slack_config) = &config.slack {
+        let slack: SlackState = Arc::new(SlackChannel::new(slack_config.clone()));
+        spawn_endpoint(
+            "Slack interactivity endpoint",
+            slack.clone().serve(cancel_token.clone()),
+        );
+        channels.push(slack);
+    }
+    if let Some(discord_config) = &config.discord {
+        let discord: DiscordState = Arc::new(DiscordChannel::new(discord_config.clone()));
+        spawn_endpoint(
+            "Discord interactions endpoint",
+            discord.clone().serve(cancel_token.clone()),
+        );
+        channels.push(discord);
+    }
+    if let Some(matrix_config) = &config.matrix {
+        let matrix: MatrixState = Arc::new(MatrixChannel::new(
+            matrix_config.clone(),
+            config.reactions.clone(),
+        ));
+        tokio::spawn(matrix.clone().run(cancel_token.clone()));
+        channels.push(matrix);
+    }
+    if let Some(ntfy_config) = &config.ntfy {
+        let ntfy: NtfyState = Arc::new(NtfyChannel::new(ntfy_config.clone()));
+        spawn_endpoint(
+            "ntfy action endpoint",
+            ntfy.clone().serve(cancel_token.clone()),
+        );
+        channels.push(ntfy);
+    }
+    if let Some(web_config) = &config.web {
+        let web: WebState = Arc::new(WebChannel::new(web_config.clone(), pending_map.clone()));
+        spawn_endpoint("Web UI", web.clone().serve(cancel_token.clone()));
+        channels.push(web);
+    }
+    if let Some(email_config) = &config.email {
+        let email: EmailState = Arc::new(EmailChannel::new(email_config.clone()));
+        spawn_endpoint(
+            "Email link endpoint",
+            email.clone().serve(cancel_token.clone()),
+        );
+        channels.push(email);
+    }
+    if let Some(webhook_config) = &config.webhook {
+        let webhook: WebhookState = Arc::new(WebhookChannel::new(webhook_config.clone()));
+        spawn_endpoint(
+            "Webhook decision endpoint",
+            webhook.clone().serve(cancel_token.clone()),
+        );
+        channels.push(webhook);
+    }
+    if let Some(signal_config) = &config.signal {
+        let signal: SignalState = Arc::new(SignalChannel::new(signal_config.clone()));
+        tokio::spawn(signal.clone().run(cancel_token.clone()));
+        channels.push(signal);
+    }
+    channels
+}
+
+/// Runs a channel's endpoint in the background, logging why if it stops.
+fn spawn_endpoint(
+    name: &'static str,
+    endpoint: impl Future<Output = anyhow::Result<()>> + Send + 'static,
+) {
+    tokio::spawn(async move {
+        if let Err(e) = endpoint.await {
+            tracing::error!("{name} failed: {e}");
+        }
+    });
+}
+
+/// Routes button taps, messages and reactions to their handlers.
+fn update_handler(
+    ctx: &ServerContext,
+    telegram: &TelegramState,
+) -> UpdateHandler<teloxide::RequestError> {
+    let callback_handler = Update::filter_callback_query().endpoint({
+        let ctx = ctx.clone();
+        let telegram = telegram.clone();
+        move |bot: Bot, query: CallbackQuery| {
+            let ctx = ctx.clone();
+            let telegram = telegram.clone();
+            async move { handler::handle_callback(bot, query, ctx, telegram).await }
+        }
+    });
+
+    let message_handler = Update::filter_message().endpoint({
+        let ctx = ctx.clone();
+        let telegram = telegram.clone();
+        move |bot: Bot, msg: Message, me: Me| {
+            let ctx = ctx.clone();
+            let telegram = telegram.clone();
+            async move { handler::handle_message(bot, msg, me, ctx, telegram).await }
+        }
+    });
+
+    let reaction_handler = Update::filter_message_reaction_updated().endpoint({
+        let ctx = ctx.clone();
+        let telegram = telegram.clone();
+        move |update: MessageReactionUpdated| {
+            let ctx = ctx.clone();
+            let telegram = telegram.clone();
+            async move { handler::handle_reaction(update, ctx, telegram).await }
+        }
+    });
+
+    dptree::entry()
+        .branch(callback_handler)
+        .branch(message_handler)
+        .branch(reaction_handler)
+}
+
 fn spawn_signal_handler(cancel_token: CancellationToken) {
     tokio::spawn(async move {
         let ctrl_c = tokio::signal::ctrl_c();
@@ -135,74 +292,11 @@ fn drain_pending_requests(pending_map: &PendingMap) {
     }
 }
 
-pub async fn send_permission_to_telegram(
-    bot: &Bot,
-    config: &Config,
-    request: &IpcRequest,
-) -> anyhow::Result<Vec<SentMessage>> {
-    let text = crate::telegram::formatter::format_permission_message(request);
-    let keyboard = crate::telegram::keyboard::make_keyboard(
-        request.request_id,
-        !request.permission_suggestions.is_empty(),
-    );
-
-    let mut sent_messages = Vec::new();
-
-    for &chat_id in &config.allowed_chat_ids {
-        let chat = ChatId(chat_id);
-        match bot
-            .send_message(chat, &text)
-            .parse_mode(teloxide::types::ParseMode::Html)
-            .reply_markup(keyboard.clone())
-            .await
-        {
-            Ok(msg) => {
-                sent_messages.push(SentMessage {
-                    chat_id: chat,
-                    message_id: msg.id,
-                });
-            }
-            Err(e) => {
-                tracing::warn!(chat_id = chat_id, "Failed to send message: {e}");
-            }
-        }
-    }
-
-    if sent_messages.is_empty() {
-        anyhow::bail!("Failed to send permission message to any chat");
-    }
-
-    Ok(sent_messages)
-}
-
-pub async fn edit_messages_status(
-    bot: &Bot,
-    sent_messages: &[SentMessage],
-    original_text: &str,
-    status: &str,
-) {
-    let new_text = format!("{original_text}\n\n{status}");
-    for msg in sent_messages {
-        if let Err(e) = bot
-            .edit_message_text(msg.chat_id, msg.message_id, &new_text)
-            .parse_mode(teloxide::types::ParseMode::Html)
-            .await
-        {
-            tracing::warn!(
-                chat_id = msg.chat_id.0,
-                message_id = msg.message_id.0,
-                "Failed to edit message: {e}"
-            );
-        }
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::models::{Decision, PendingRequest};
+    use crate::models::{Decision, IpcRequest, PendingRequest};
     use tokio::sync::oneshot;
-    use tokio::time::Instant;
     use uuid::Uuid;
 
     #[test]
@@ -223,12 +317,24 @@ mod tests {
             map.insert(
                 id,
                 PendingRequest {
-                    request_id: id,
                     sender: tx,
-                    sent_messages: vec![],
-                    original_text: String::new(),
-                    permission_suggestions: vec![],
+                    request: IpcRequest {
+                        request_id: id,
+                        tool_name: "Bash".to_string(),
+                        tool_input: serde_json::json!({"command": "ls"}),
+                        cwd: "/tmp".to_string(),
+                        session_id: "s".to_string(),
+                        permission_suggestions: vec![],
+                        assistant_context: None,
+                        first_prompt: None,
+                    },
+                    summary: "Bash".to_string(),
+                    posted: false,
+                    chat_ids: vec![],
                     created_at: Instant::now(),
+                    deadline_tx: tokio::sync::watch::channel(Instant::now()).0,
+                    required_approvals: 1,
+                    approvals: vec![],
                 },
             );
             receivers.push((id, rx));
@@ -0,0 +1,200 @@
+use crate::models::IpcRequest;
+use crate::quiet::Delivery;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::time::{Duration, Instant};
+
+/// A burst is flushed at the latest this many windows after it started,
+/// even if requests keep arriving.
+const MAX_WINDOWS_PER_BURST: u32 = 5;
+
+/// How a new request should be delivered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Arrival {
+    /// Nothing else from the session just arrived: send it right away.
+    Immediate,
+    /// Buffered with the rest of a burst. The first buffered request of a
+    /// burst is responsible for flushing it.
+    Buffered { flush: bool },
+}
+
+/// A request waiting to go out with the rest of its burst.
+#[derive(Debug, Clone)]
+pub struct BufferedRequest {
+    pub request: IpcRequest,
+    pub deliveries: Vec<Delivery>,
+}
+
+#[derive(Debug)]
+struct Burst {
+    started: Instant,
+    last_arrival: Instant,
+    buffer: Vec<BufferedRequest>,
+}
+
+/// Per-session burst detection. A request arriving within `window` of the
+/// previous one from the same session is buffered; the buffer is sent as a
+/// single message once the session has been quiet for `window`. The first
+/// request of a burst has already gone out on its own by then.
+///
+/// A combined message is one list, so its items get no countdown, quorum
+/// progress, reminder or reactions of their own; they are only edited as
+/// they resolve.
+pub struct Bursts {
+    window: Duration,
+    sessions: Mutex<HashMap<String, Burst>>,
+}
+
+pub type BurstState = Arc<Bursts>;
+
+impl Bursts {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            sessions: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn arrive(&self, request: &IpcRequest, deliveries: &[Delivery], now: Instant) -> Arrival {
+        if self.window.is_zero() {
+            return Arrival::Immediate;
+        }
+        let mut sessions = self.sessions.lock().expect("burst lock poisoned");
+        sessions.retain(|_, burst| {
+            !burst.buffer.is_empty() || now.duration_since(burst.last_arrival) < self.window
+        });
+
+        let arrival = match sessions.get_mut(&request.session_id) {
+            None => {
+                sessions.insert(
+                    request.session_id.clone(),
+                    Burst {
+                        started: now,
+                        last_arrival: now,
+                        buffer: Vec::new(),
+                    },
+                );
+                Arrival::Immediate
+            }
+            Some(burst) => {
+                if burst.buffer.is_empty() {
+                    burst.started = now;
+                }
+                burst.last_arrival = now;
+                burst.buffer.push(BufferedRequest {
+                    request: request.clone(),
+                    deliveries: deliveries.to_vec(),
+                });
+                Arrival::Buffered {
+                    flush: burst.buffer.len() == 1,
+                }
+            }
+        };
+        drop(sessions);
+        arrival
+    }
+
+    /// When the session's burst should be flushed: after `window` without
+    /// new requests, but no later than a few windows after it started.
+    pub fn flush_at(&self, session_id: &str) -> Option<Instant> {
+        self.sessions
+            .lock()
+            .expect("burst lock poisoned")
+            .get(session_id)
+            .map(|burst| {
+                let quiet = burst.last_arrival + self.window;
+                let cap = burst.started + self.window * MAX_WINDOWS_PER_BURST;
+                quiet.min(cap)
+            })
+    }
+
+    /// Takes the session's buffered requests, oldest first.
+    pub fn take(&self, session_id: &str) -> Vec<BufferedRequest> {
+        self.sessions
+            .lock()
+            .expect("burst lock poisoned")
+            .get_mut(session_id)
+            .map(|burst| std::mem::take(&mut burst.buffer))
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    const WINDOW: Duration = Duration::from_secs(2);
+
+    fn request(session_id: &str) -> IpcRequest {
+        IpcRequest {
+            request_id: Uuid::new_v4(),
+            tool_name: "Bash".to_string(),
+            tool_input: serde_json::json!({"command": "ls"}),
+            cwd: "/tmp".to_string(),
+            session_id: session_id.to_string(),
+            permission_suggestions: vec![],
+            assistant_context: None,
+            first_prompt: None,
+        }
+    }
+
+    #[test]
+    fn lone_requests_go_out_immediately() {
+        let bursts = Bursts::new(WINDOW);
+        let now = Instant::now();
+        assert_eq!(bursts.arrive(&request("a"), &[], now), Arrival::Immediate);
+        assert_eq!(bursts.arrive(&request("b"), &[], now), Arrival::Immediate);
+        let later = now + WINDOW;
+        assert_eq!(bursts.arrive(&request("a"), &[], later), Arrival::Immediate);
+    }
+
+    #[test]
+    fn followers_are_buffered_until_quiet() {
+        let bursts = Bursts::new(WINDOW);
+        let now = Instant::now();
+        bursts.arrive(&request("a"), &[], now);
+
+        let second = now + Duration::from_millis(500);
+        let third = now + Duration::from_secs(1);
+        assert_eq!(
+            bursts.arrive(&request("a"), &[], second),
+            Arrival::Buffered { flush: true }
+        );
+        assert_eq!(
+            bursts.arrive(&request("a"), &[], third),
+            Arrival::Buffered { flush: false }
+        );
+        assert_eq!(bursts.flush_at("a"), Some(third + WINDOW));
+
+        assert_eq!(bursts.take("a").len(), 2);
+        assert!(bursts.take("a").is_empty());
+        // Still within the window of the last arrival: starts a new burst
+        assert_eq!(
+            bursts.arrive(&request("a"), &[], third + Duration::from_secs(1)),
+            Arrival::Buffered { flush: true }
+        );
+    }
+
+    #[test]
+    fn flush_is_capped_for_endless_bursts() {
+        let bursts = Bursts::new(WINDOW);
+        let now = Instant::now();
+        bursts.arrive(&request("a"), &[], now);
+        let mut at = now;
+        for _ in 0..20 {
+            at += Duration::from_secs(1);
+            bursts.arrive(&request("a"), &[], at);
+        }
+        let started = now + Duration::from_secs(1);
+        assert_eq!(bursts.flush_at("a"), Some(started + WINDOW * 5));
+    }
+
+    #[test]
+    fn zero_window_disables_coalescing() {
+        let bursts = Bursts::new(Duration::ZERO);
+        let now = Instant::now();
+        bursts.arrive(&request("a"), &[], now);
+        assert_eq!(bursts.arrive(&request("a"), &[], now), Arrival::Immediate);
+    }
+}
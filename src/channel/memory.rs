@@ -0,0 +1,136 @@
+use super::{
+    ApprovalChannel, DecisionOutcome, DecisionQueue, DecisionStream, Page, RequestView,
+    StatusUpdate, Verdict,
+};
+use crate::models::Approver;
+use futures::future::BoxFuture;
+use std::sync::Mutex;
+use tokio::sync::mpsc;
+use uuid::Uuid;
+
+/// A channel that records what it is asked to show and answers with
+/// whatever the test decides.
+pub struct MemoryChannel {
+    sent_tx: mpsc::UnboundedSender<RequestView>,
+    sent_rx: tokio::sync::Mutex<mpsc::UnboundedReceiver<RequestView>>,
+    updates: Mutex<Vec<(Uuid, StatusUpdate)>>,
+    decisions: DecisionQueue,
+    anonymous: bool,
+}
+
+impl Default for MemoryChannel {
+    fn default() -> Self {
+        let (sent_tx, sent_rx) = mpsc::unbounded_channel();
+        Self {
+            sent_tx,
+            sent_rx: tokio::sync::Mutex::new(sent_rx),
+            updates: Mutex::new(Vec::new()),
+            decisions: DecisionQueue::default(),
+            anonymous: false,
+        }
+    }
+}
+
+impl MemoryChannel {
+    /// A channel that, like the web UI, can't tell its approvers apart.
+    pub fn anonymous() -> Self {
+        Self {
+            anonymous: true,
+            ..Self::default()
+        }
+    }
+
+    /// The next request posted.
+    pub async fn next_sent(&self) -> RequestView {
+        self.sent_rx
+            .lock()
+            .await
+            .recv()
+            .await
+            .expect("memory channel closed")
+    }
+
+    /// Status updates shown so far, oldest first.
+    pub fn updates(&self) -> Vec<(Uuid, StatusUpdate)> {
+        self.updates.lock().expect("memory lock poisoned").clone()
+    }
+
+    pub async fn decide(&self, request_id: Uuid, verdict: Verdict) -> DecisionOutcome {
+        self.decide_as(request_id, verdict, "tester").await
+    }
+
+    /// Decides as the approver called `name`, e.g. to fill a quorum.
+    pub async fn decide_as(
+        &self,
+        request_id: Uuid,
+        verdict: Verdict,
+        name: &str,
+    ) -> DecisionOutcome {
+        let approver = Approver {
+            id: format!("memory:{name}"),
+            name: name.to_string(),
+        };
+        self.decisions.decide(request_id, verdict, approver).await
+    }
+}
+
+impl ApprovalChannel for MemoryChannel {
+    fn name(&self) -> &'static str {
+        "Memory"
+    }
+
+    fn send_request<'a>(
+        &'a self,
+        view: &'a RequestView,
+        _page: Page<'a>,
+    ) -> BoxFuture<'a, anyhow::Result<()>> {
+        let _ = self.sent_tx.send(view.clone());
+        Box::pin(async { Ok(()) })
+    }
+
+    fn update_status<'a>(
+        &'a self,
+        view: &'a RequestView,
+        update: &'a StatusUpdate,
+    ) -> BoxFuture<'a, ()> {
+        self.updates
+            .lock()
+            .expect("memory lock poisoned")
+            .push((view.request_id(), update.clone()));
+        Box::pin(async {})
+    }
+
+    fn decisions(&self) -> DecisionStream {
+        self.decisions.stream()
+    }
+
+    fn identifies_approvers(&self) -> bool {
+        !self.anonymous
+    }
+}
+
+/// An open Bash request for `command`, as `process_request` stores it.
+pub fn pending_request(command: &str) -> crate::models::PendingRequest {
+    use std::time::Duration;
+    use tokio::time::Instant;
+    crate::models::PendingRequest {
+        sender: tokio::sync::oneshot::channel().0,
+        request: crate::models::IpcRequest {
+            request_id: Uuid::new_v4(),
+            tool_name: "Bash".to_string(),
+            tool_input: serde_json::json!({ "command": command }),
+            cwd: "/tmp/proj".to_string(),
+            session_id: "s".to_string(),
+            permission_suggestions: vec![],
+            assistant_context: None,
+            first_prompt: None,
+        },
+        summary: format!("Bash: {command}"),
+        posted: true,
+        chat_ids: vec![],
+        created_at: Instant::now(),
+        deadline_tx: tokio::sync::watch::channel(Instant::now() + Duration::from_secs(150)).0,
+        required_approvals: 1,
+        approvals: vec![],
+    }
+}
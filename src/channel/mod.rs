@@ -0,0 +1,509 @@
+#[cfg(test)]
+pub mod memory;
+pub mod open;
+
+use crate::ipc::server::ServerContext;
+use crate::models::{ApprovalProgress, Approver, IpcRequest, IpcResponse, PendingRequest};
+use crate::quiet::Delivery;
+use futures::future::BoxFuture;
+use futures::stream::{BoxStream, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use tokio::sync::{mpsc, oneshot};
+use tokio::time::{Duration, Instant};
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
+
+/// How long one channel may take to show a status update before the others
+/// stop waiting for it.
+const STATUS_UPDATE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A person's answer to a request.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Verdict {
+    Allow,
+    Deny,
+    AlwaysAllow,
+    /// Free-text guidance sent back instead of a yes/no.
+    Reply(String),
+}
+
+/// How a decision was applied, so the channel can tell whoever made it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecisionOutcome {
+    Resolved,
+    /// Counted towards a quorum that isn't reached yet.
+    ApprovalRecorded,
+    /// This approver already approved the request.
+    DuplicateApproval,
+    /// The request was already resolved.
+    AlreadyHandled,
+    /// An approval for a quorum from a channel that can't tell who made it.
+    UnidentifiedApprover,
+}
+
+impl DecisionOutcome {
+    /// What to tell whoever made the decision, if anything.
+    pub const fn feedback(self) -> Option<&'static str> {
+        match self {
+            Self::Resolved => None,
+            Self::ApprovalRecorded => Some("Approval recorded"),
+            Self::DuplicateApproval => Some("You already approved this request"),
+            Self::AlreadyHandled => Some("This request has already been handled"),
+            Self::UnidentifiedApprover => Some("Quorum approvals need an identified approver"),
+        }
+    }
+}
+
+/// The `410 Gone` answer to a link or action for a request that was
+/// already resolved.
+pub fn already_handled() -> hyper::Response<crate::http::Body> {
+    let text = DecisionOutcome::AlreadyHandled
+        .feedback()
+        .unwrap_or_default();
+    crate::http::text_response(hyper::StatusCode::GONE, text)
+}
+
+/// A decision made in an approval channel.
+#[derive(Debug)]
+pub struct ChannelDecision {
+    pub request_id: Uuid,
+    pub verdict: Verdict,
+    pub approver: Approver,
+    /// Receives the outcome once the decision is applied.
+    pub ack: Option<oneshot::Sender<DecisionOutcome>>,
+}
+
+/// An open request as channels show it.
+#[derive(Debug, Clone)]
+pub struct RequestView {
+    pub request: IpcRequest,
+    /// One-line summary for batched lists.
+    pub summary: String,
+    pub deadline: Instant,
+    /// Distinct approvals needed before the request is allowed (1 = no quorum).
+    pub required_approvals: usize,
+    pub approvals: Vec<Approver>,
+}
+
+impl RequestView {
+    pub const fn request_id(&self) -> Uuid {
+        self.request.request_id
+    }
+
+    /// "Always Allow" would bypass future quorums, so it is only offered for
+    /// requests without one.
+    pub const fn offers_always_allow(&self) -> bool {
+        !self.request.permission_suggestions.is_empty() && self.required_approvals == 1
+    }
+}
+
+impl PendingRequest {
+    /// What approval channels show for the request right now.
+    pub fn view(&self) -> RequestView {
+        RequestView {
+            request: self.request.clone(),
+            summary: self.summary.clone(),
+            deadline: self.deadline(),
+            required_approvals: self.required_approvals,
+            approvals: self.approvals.clone(),
+        }
+    }
+}
+
+/// Who a request is posted to: the chats of escalation tier `tier`
+/// (0 = first). Channels without per-chat addressing ignore `deliveries`
+/// and are only asked to post for the first tier (see
+/// [`ApprovalChannel::pages_escalation_tiers`]).
+#[derive(Debug, Clone, Copy)]
+pub struct Page<'a> {
+    pub tier: usize,
+    pub deliveries: &'a [Delivery],
+}
+
+/// A change to show on a request's messages.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StatusUpdate {
+    /// The time left or the quorum progress changed.
+    Progress,
+    /// The deadline moved later, so earlier reminders are stale.
+    Extended,
+    /// The request is about to fall back to the terminal.
+    Reminder,
+    /// Still open, but no longer shown (e.g. muted into the `/pending` queue).
+    Withdrawn(String),
+    /// Answered or timed out, with the status line to show.
+    Resolved(String),
+}
+
+pub type DecisionStream = BoxStream<'static, ChannelDecision>;
+
+/// Somewhere permission requests are posted and answered: Telegram, Slack,
+/// or an in-memory channel in tests.
+pub trait ApprovalChannel: Send + Sync {
+    /// Shown in logs and in deny messages ("Denied by user via Telegram"),
+    /// spelled the way the service spells itself (so "ntfy" stays lowercase).
+    fn name(&self) -> &'static str;
+
+    /// Posts an open request.
+    fn send_request<'a>(
+        &'a self,
+        view: &'a RequestView,
+        page: Page<'a>,
+    ) -> BoxFuture<'a, anyhow::Result<()>>;
+
+    /// Posts several open requests from one session together. By default
+    /// each is posted on its own.
+    fn send_batch<'a>(
+        &'a self,
+        views: &'a [RequestView],
+        page: Page<'a>,
+    ) -> BoxFuture<'a, anyhow::Result<()>> {
+        Box::pin(async move {
+            for view in views {
+                self.send_request(view, page).await?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Shows `update` on the request's messages (best-effort).
+    fn update_status<'a>(
+        &'a self,
+        view: &'a RequestView,
+        update: &'a StatusUpdate,
+    ) -> BoxFuture<'a, ()>;
+
+    /// Decisions made in this channel. Taken once, when the server starts.
+    fn decisions(&self) -> DecisionStream;
+
+    /// Whether later escalation tiers reach anyone new here. Channels that
+    /// post to one fixed audience are only asked to post the first tier.
+    fn pages_escalation_tiers(&self) -> bool {
+        false
+    }
+
+    /// Whether decisions made here name the person who made them. Approvals
+    /// from channels where anyone with a link, token or socket decides
+    /// alike can't be told apart, so they don't count towards a quorum.
+    fn identifies_approvers(&self) -> bool {
+        true
+    }
+}
+
+pub type Channels = Arc<Vec<Arc<dyn ApprovalChannel>>>;
+
+/// Decisions collected by a channel until the server takes them as a stream.
+pub struct DecisionQueue {
+    tx: mpsc::UnboundedSender<ChannelDecision>,
+    rx: Mutex<Option<mpsc::UnboundedReceiver<ChannelDecision>>>,
+}
+
+impl Default for DecisionQueue {
+    fn default() -> Self {
+        let (tx, rx) = mpsc::unbounded_channel();
+        Self {
+            tx,
+            rx: Mutex::new(Some(rx)),
+        }
+    }
+}
+
+impl DecisionQueue {
+    /// The queued decisions. Empty after the first call.
+    pub fn stream(&self) -> DecisionStream {
+        let rx = self.rx.lock().expect("decision lock poisoned").take();
+        match rx {
+            Some(rx) => futures::stream::unfold(rx, |mut rx| async move {
+                rx.recv().await.map(|decision| (decision, rx))
+            })
+            .boxed(),
+            None => futures::stream::empty().boxed(),
+        }
+    }
+
+    /// Submits a decision and waits until it is applied.
+    pub async fn decide(
+        &self,
+        request_id: Uuid,
+        verdict: Verdict,
+        approver: Approver,
+    ) -> DecisionOutcome {
+        let (ack, outcome) = oneshot::channel();
+        let decision = ChannelDecision {
+            request_id,
+            verdict,
+            approver,
+            ack: Some(ack),
+        };
+        if self.tx.send(decision).is_err() {
+            return DecisionOutcome::AlreadyHandled;
+        }
+        outcome.await.unwrap_or(DecisionOutcome::AlreadyHandled)
+    }
+}
+
+/// Applies the decisions of every channel as they arrive, until
+/// `cancel_token` fires. Each is applied in its own task, so showing one
+/// outcome in slow channels doesn't hold up the next decision.
+pub async fn run_decisions(ctx: ServerContext, cancel_token: CancellationToken) {
+    let mut decisions = futures::stream::select_all(ctx.channels.iter().map(|channel| {
+        let source = Source {
+            name: channel.name(),
+            identifies_approvers: channel.identifies_approvers(),
+        };
+        channel.decisions().map(move |decision| (source, decision))
+    }));
+    loop {
+        tokio::select! {
+            () = cancel_token.cancelled() => return,
+            next = decisions.next() => {
+                let Some((source, decision)) = next else {
+                    return;
+                };
+                let ctx = ctx.clone();
+                tokio::spawn(async move {
+                    let outcome = apply_decision(&ctx, source, &decision).await;
+                    if let Some(ack) = decision.ack {
+                        let _ = ack.send(outcome);
+                    }
+                });
+            }
+        }
+    }
+}
+
+/// The channel a decision was made in.
+#[derive(Debug, Clone, Copy)]
+struct Source {
+    name: &'static str,
+    identifies_approvers: bool,
+}
+
+/// Applies one decision from `source`. Approvals of requests that need a
+/// quorum are only counted until enough are in, and only from channels
+/// that identify the approver.
+async fn apply_decision(
+    ctx: &ServerContext,
+    source: Source,
+    decision: &ChannelDecision,
+) -> DecisionOutcome {
+    let request_id = decision.request_id;
+    let channel = source.name;
+    let mut verdict = &decision.verdict;
+    if matches!(verdict, Verdict::Allow | Verdict::AlwaysAllow) {
+        let progress = ctx
+            .pending_map
+            .get_mut(&request_id)
+            .and_then(|mut pending| {
+                (pending.required_approvals > 1).then(|| {
+                    source
+                        .identifies_approvers
+                        .then(|| pending.record_approval(decision.approver.clone()))
+                })
+            });
+        match progress {
+            Some(None) => return DecisionOutcome::UnidentifiedApprover,
+            Some(Some(ApprovalProgress::Duplicate)) => return DecisionOutcome::DuplicateApproval,
+            Some(Some(ApprovalProgress::Pending)) => {
+                update_open(ctx, request_id, &StatusUpdate::Progress).await;
+                return DecisionOutcome::ApprovalRecorded;
+            }
+            // "Always Allow" would bypass every future quorum for the tool,
+            // so a quorum only ever resolves as a one-off approval.
+            Some(Some(ApprovalProgress::Reached)) => verdict = &Verdict::Allow,
+            None => {}
+        }
+    }
+
+    let Some((response, status)) = ctx.pending_map.get(&request_id).map(|pending| {
+        let (response, status_text) = build_response(
+            verdict,
+            request_id,
+            &pending.request.permission_suggestions,
+            channel,
+        );
+        let status = if pending.approvals.is_empty() {
+            status_text.to_string()
+        } else {
+            let line =
+                crate::quorum::format_progress(&pending.approvals, pending.required_approvals);
+            format!("{status_text}\n{line}")
+        };
+        (response, status)
+    }) else {
+        return DecisionOutcome::AlreadyHandled;
+    };
+    if resolve(ctx, request_id, response, &status).await {
+        DecisionOutcome::Resolved
+    } else {
+        DecisionOutcome::AlreadyHandled
+    }
+}
+
+/// Build the IPC response and status text for a verdict.
+/// Extracted as a pure function for testability.
+fn build_response(
+    verdict: &Verdict,
+    request_id: Uuid,
+    permission_suggestions: &[serde_json::Value],
+    channel: &str,
+) -> (IpcResponse, &'static str) {
+    match verdict {
+        Verdict::Allow => (IpcResponse::allow(request_id), "\u{2705} Approved"),
+        Verdict::Deny => (
+            IpcResponse::deny(request_id, format!("Denied by user via {channel}")),
+            "\u{274c} Denied",
+        ),
+        Verdict::AlwaysAllow => {
+            let suggestion = permission_suggestions.first().cloned();
+            (
+                IpcResponse::always_allow(request_id, suggestion),
+                "\u{1f513} Always Allowed",
+            )
+        }
+        Verdict::Reply(text) => (
+            IpcResponse::reply(request_id, text.clone()),
+            "\u{270f}\u{fe0f} Amended",
+        ),
+    }
+}
+
+/// Resolves an open request with `response`, then shows `status` in every
+/// channel. Returns `false` if it was already resolved.
+pub async fn resolve(
+    ctx: &ServerContext,
+    request_id: Uuid,
+    response: IpcResponse,
+    status: &str,
+) -> bool {
+    let Some((_, pending)) = ctx.pending_map.remove(&request_id) else {
+        return false;
+    };
+    let view = pending.view();
+    ctx.activity.record_outcome(&response.decision);
+    let _ = pending.sender.send(response);
+    update_all(ctx, &view, &StatusUpdate::Resolved(status.to_string())).await;
+    true
+}
+
+/// Takes an open request off screen in every channel, leaving it open
+/// (e.g. for `/pending`).
+pub async fn withdraw(ctx: &ServerContext, request_id: Uuid, status: &str) {
+    let Some(view) = ctx.pending_map.get_mut(&request_id).map(|mut pending| {
+        pending.posted = false;
+        pending.view()
+    }) else {
+        return;
+    };
+    update_all(ctx, &view, &StatusUpdate::Withdrawn(status.to_string())).await;
+}
+
+/// Shows `update` for a request that is still open.
+pub async fn update_open(ctx: &ServerContext, request_id: Uuid, update: &StatusUpdate) {
+    let Some(view) = ctx.pending_map.get(&request_id).map(|p| p.view()) else {
+        return;
+    };
+    update_all(ctx, &view, update).await;
+}
+
+/// Shows `update` in every channel at once, giving up on any that take
+/// longer than [`STATUS_UPDATE_TIMEOUT`].
+async fn update_all(ctx: &ServerContext, view: &RequestView, update: &StatusUpdate) {
+    futures::future::join_all(ctx.channels.iter().map(|channel| async move {
+        let shown =
+            tokio::time::timeout(STATUS_UPDATE_TIMEOUT, channel.update_status(view, update)).await;
+        if shown.is_err() {
+            tracing::warn!(
+                request_id = %view.request_id(),
+                "{} took too long to show a status update",
+                channel.name()
+            );
+        }
+    }))
+    .await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Decision;
+
+    #[test]
+    fn allow_response() {
+        let id = Uuid::new_v4();
+        let (resp, status) = build_response(&Verdict::Allow, id, &[], "Telegram");
+        assert_eq!(resp.decision, Decision::Allow);
+        assert_eq!(resp.request_id, id);
+        assert!(resp.message.is_none());
+        assert!(resp.always_allow_suggestion.is_none());
+        assert_eq!(status, "\u{2705} Approved");
+    }
+
+    #[test]
+    fn deny_response_names_the_channel() {
+        let id = Uuid::new_v4();
+        let (resp, status) = build_response(&Verdict::Deny, id, &[], "Telegram");
+        assert_eq!(resp.decision, Decision::Deny);
+        assert_eq!(resp.message.as_deref(), Some("Denied by user via Telegram"));
+        assert_eq!(status, "\u{274c} Denied");
+    }
+
+    #[test]
+    fn always_response_with_suggestions() {
+        let id = Uuid::new_v4();
+        let suggestions = vec![serde_json::json!({"tool": "Bash"})];
+        let (resp, status) = build_response(&Verdict::AlwaysAllow, id, &suggestions, "Telegram");
+        assert_eq!(resp.decision, Decision::AlwaysAllow);
+        assert_eq!(
+            resp.always_allow_suggestion,
+            Some(serde_json::json!({"tool": "Bash"}))
+        );
+        assert_eq!(status, "\u{1f513} Always Allowed");
+    }
+
+    #[test]
+    fn always_response_without_suggestions() {
+        let id = Uuid::new_v4();
+        let (resp, _) = build_response(&Verdict::AlwaysAllow, id, &[], "Telegram");
+        assert_eq!(resp.decision, Decision::AlwaysAllow);
+        assert!(resp.always_allow_suggestion.is_none());
+    }
+
+    #[test]
+    fn reply_response_carries_text() {
+        let id = Uuid::new_v4();
+        let verdict = Verdict::Reply("use pytest".to_string());
+        let (resp, status) = build_response(&verdict, id, &[], "Telegram");
+        assert_eq!(resp.decision, Decision::Reply);
+        assert_eq!(resp.user_message.as_deref(), Some("use pytest"));
+        assert_eq!(status, "\u{270f}\u{fe0f} Amended");
+    }
+
+    #[tokio::test]
+    async fn queue_streams_decisions_once() {
+        let queue = DecisionQueue::default();
+        let mut stream = queue.stream();
+        let approver = Approver {
+            id: "test:1".to_string(),
+            name: "alice".to_string(),
+        };
+        let id = Uuid::new_v4();
+        let decide = queue.decide(id, Verdict::Deny, approver);
+        let answer = async {
+            let decision = stream.next().await.unwrap();
+            assert_eq!(decision.request_id, id);
+            assert_eq!(decision.verdict, Verdict::Deny);
+            decision
+                .ack
+                .unwrap()
+                .send(DecisionOutcome::Resolved)
+                .unwrap();
+        };
+        let (outcome, ()) = tokio::join!(decide, answer);
+        assert_eq!(outcome, DecisionOutcome::Resolved);
+
+        assert!(queue.stream().next().await.is_none());
+    }
+}
@@ -0,0 +1,65 @@
+use crate::channel::{RequestView, StatusUpdate};
+use crate::ipc::server::PendingMap;
+use dashmap::DashSet;
+use tokio::sync::broadcast;
+use uuid::Uuid;
+
+/// The requests a listing channel (web UI, TUI) shows, read straight from
+/// the pending map, and a signal for whenever the list changes.
+pub struct OpenRequests {
+    pending_map: PendingMap,
+    /// Requests handed to the channel and not yet closed.
+    shown: DashSet<Uuid>,
+    changes: broadcast::Sender<()>,
+}
+
+impl OpenRequests {
+    pub fn new(pending_map: PendingMap) -> Self {
+        Self {
+            pending_map,
+            shown: DashSet::new(),
+            changes: broadcast::channel(16).0,
+        }
+    }
+
+    /// Lists a request handed to the channel. Every open request is listed,
+    /// whatever the escalation tier.
+    pub fn show(&self, request_id: Uuid) {
+        if self.shown.insert(request_id) {
+            self.changed();
+        }
+    }
+
+    /// Keeps the list in step with a status update.
+    pub fn update(&self, view: &RequestView, update: &StatusUpdate) {
+        match update {
+            StatusUpdate::Progress | StatusUpdate::Extended => self.changed(),
+            StatusUpdate::Reminder => {}
+            StatusUpdate::Withdrawn(_) | StatusUpdate::Resolved(_) => {
+                self.shown.remove(&view.request_id());
+                self.changed();
+            }
+        }
+    }
+
+    /// Open requests, soonest deadline first.
+    pub fn views(&self) -> Vec<RequestView> {
+        let mut views: Vec<RequestView> = self
+            .shown
+            .iter()
+            .filter_map(|id| self.pending_map.get(&*id).map(|pending| pending.view()))
+            .collect();
+        views.sort_by_key(|view| view.deadline);
+        views
+    }
+
+    /// Fires after every change to the list.
+    pub fn subscribe(&self) -> broadcast::Receiver<()> {
+        self.changes.subscribe()
+    }
+
+    fn changed(&self) {
+        // Nobody listening is fine
+        let _ = self.changes.send(());
+    }
+}
@@ -1,6 +1,20 @@
+use crate::discord::DiscordConfig;
+use crate::email::EmailConfig;
 use crate::error::BotError;
+use crate::matrix::MatrixConfig;
+use crate::mute::MuteOutcome;
+use crate::ntfy::NtfyConfig;
+use crate::quiet::QuietHours;
+use crate::quorum::QuorumRule;
+use crate::rota::RotaConfig;
+use crate::routing::Route;
+use crate::signal::SignalConfig;
+use crate::slack::SlackConfig;
+use crate::telegram::reaction::{ReactionAction, default_reactions};
+use crate::web::WebConfig;
+use crate::webhook::WebhookConfig;
 use serde::Deserialize;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
 
 #[derive(Debug, Deserialize, Clone)]
@@ -9,7 +23,59 @@ pub struct Config {
     pub telegram_bot_token: String,
     pub allowed_chat_ids: HashSet<i64>,
     pub timeout_seconds: u64,
+    /// Seconds before the timeout to send a notifying reminder (0 = never).
+    pub reminder_before_seconds: u64,
     pub socket_path: Option<PathBuf>,
+    pub escalation: Vec<EscalationTier>,
+    pub rota: Option<RotaConfig>,
+    pub routes: Vec<Route>,
+    pub quorum: Vec<QuorumRule>,
+    pub forum_topics: bool,
+    pub dashboard: bool,
+    /// Listen on the control socket for `vibe-reachout tui`.
+    pub tui: bool,
+    pub quiet_hours: Vec<QuietHours>,
+    /// Default outcome for requests from sessions muted in Telegram.
+    pub mute_outcome: MuteOutcome,
+    /// Requests from a session arriving within this many milliseconds of
+    /// the previous one are combined into one message (0 = never).
+    pub coalesce_window_ms: u64,
+    /// Emoji reactions that approve or deny the request they're put on.
+    pub reactions: HashMap<String, ReactionAction>,
+    /// Also post requests to Slack.
+    pub slack: Option<SlackConfig>,
+    /// Also post requests to Discord.
+    pub discord: Option<DiscordConfig>,
+    /// Also post requests to a Matrix room.
+    pub matrix: Option<MatrixConfig>,
+    /// Also push requests to an ntfy topic.
+    pub ntfy: Option<NtfyConfig>,
+    /// Serve a browser UI for answering requests.
+    pub web: Option<WebConfig>,
+    /// Also email requests.
+    pub email: Option<EmailConfig>,
+    /// Post requests to a webhook and take decisions back over REST.
+    pub webhook: Option<WebhookConfig>,
+    /// Also send requests over Signal, through signal-cli.
+    pub signal: Option<SignalConfig>,
+}
+
+/// Upper bound for `timeout_seconds`, and for the total wait after "+5 min"
+/// extensions.
+pub const MAX_TIMEOUT_SECONDS: u64 = 3600;
+
+/// Upper bound for `coalesce_window_ms`.
+pub const MAX_COALESCE_WINDOW_MS: u64 = 10_000;
+
+/// One step of the escalation chain: who to notify, and how long to wait
+/// for them before paging the next tier.
+#[derive(Debug, Deserialize, Clone, PartialEq, Eq)]
+pub struct EscalationTier {
+    pub chat_ids: Vec<i64>,
+    /// Required (and non-zero) on every tier but the last, which has no
+    /// next tier to page.
+    #[serde(default)]
+    pub escalate_after_seconds: u64,
 }
 
 /// Intermediate type for deserialization (Vec → `HashSet` conversion).
@@ -19,7 +85,38 @@ struct RawConfig {
     allowed_chat_ids: Vec<i64>,
     #[serde(default = "default_timeout")]
     timeout_seconds: u64,
+    #[serde(default = "default_reminder_before")]
+    reminder_before_seconds: u64,
     socket_path: Option<PathBuf>,
+    #[serde(default)]
+    escalation: Vec<EscalationTier>,
+    rota: Option<RotaConfig>,
+    #[serde(default)]
+    routes: Vec<Route>,
+    #[serde(default)]
+    quorum: Vec<QuorumRule>,
+    #[serde(default = "default_forum_topics")]
+    forum_topics: bool,
+    #[serde(default)]
+    dashboard: bool,
+    #[serde(default)]
+    tui: bool,
+    #[serde(default)]
+    quiet_hours: Vec<QuietHours>,
+    #[serde(default)]
+    mute_outcome: MuteOutcome,
+    #[serde(default = "default_coalesce_window")]
+    coalesce_window_ms: u64,
+    #[serde(default = "default_reactions")]
+    reactions: HashMap<String, ReactionAction>,
+    slack: Option<SlackConfig>,
+    discord: Option<DiscordConfig>,
+    matrix: Option<MatrixConfig>,
+    ntfy: Option<NtfyConfig>,
+    web: Option<WebConfig>,
+    email: Option<EmailConfig>,
+    webhook: Option<WebhookConfig>,
+    signal: Option<SignalConfig>,
 }
 
 impl From<RawConfig> for Config {
@@ -28,7 +125,27 @@ impl From<RawConfig> for Config {
             telegram_bot_token: raw.telegram_bot_token,
             allowed_chat_ids: raw.allowed_chat_ids.into_iter().collect(),
             timeout_seconds: raw.timeout_seconds,
+            reminder_before_seconds: raw.reminder_before_seconds,
             socket_path: raw.socket_path,
+            escalation: raw.escalation,
+            rota: raw.rota,
+            routes: raw.routes,
+            quorum: raw.quorum,
+            forum_topics: raw.forum_topics,
+            dashboard: raw.dashboard,
+            tui: raw.tui,
+            quiet_hours: raw.quiet_hours,
+            mute_outcome: raw.mute_outcome,
+            coalesce_window_ms: raw.coalesce_window_ms,
+            reactions: raw.reactions,
+            slack: raw.slack,
+            discord: raw.discord,
+            matrix: raw.matrix,
+            ntfy: raw.ntfy,
+            web: raw.web,
+            email: raw.email,
+            webhook: raw.webhook,
+            signal: raw.signal,
         }
     }
 }
@@ -37,6 +154,18 @@ const fn default_timeout() -> u64 {
     300
 }
 
+const fn default_reminder_before() -> u64 {
+    60
+}
+
+const fn default_coalesce_window() -> u64 {
+    2000
+}
+
+const fn default_forum_topics() -> bool {
+    true
+}
+
 impl Config {
     pub fn load() -> anyhow::Result<Self> {
         let config_path = config_file_path()?;
@@ -65,8 +194,14 @@ impl Config {
         if self.allowed_chat_ids.is_empty() {
             anyhow::bail!("allowed_chat_ids must have at least one entry");
         }
-        if self.timeout_seconds == 0 || self.timeout_seconds > 3600 {
-            anyhow::bail!("timeout_seconds must be between 1 and 3600");
+        if self.timeout_seconds == 0 || self.timeout_seconds > MAX_TIMEOUT_SECONDS {
+            anyhow::bail!("timeout_seconds must be between 1 and {MAX_TIMEOUT_SECONDS}");
+        }
+        if self.coalesce_window_ms > MAX_COALESCE_WINDOW_MS {
+            anyhow::bail!("coalesce_window_ms must be at most {MAX_COALESCE_WINDOW_MS}");
+        }
+        if self.reactions.keys().any(String::is_empty) {
+            anyhow::bail!("reactions must not map an empty emoji");
         }
         if let Some(ref path) = self.socket_path
             && let Some(parent) = path.parent()
@@ -77,24 +212,113 @@ impl Config {
                 parent.display()
             );
         }
+        for (index, tier) in self.escalation.iter().enumerate() {
+            if tier.chat_ids.is_empty() {
+                anyhow::bail!("escalation tier {index} must have at least one chat_id");
+            }
+            if tier.escalate_after_seconds == 0 && index + 1 < self.escalation.len() {
+                anyhow::bail!(
+                    "escalation tier {index} must set escalate_after_seconds above 0, \
+                     or every later tier is paged at once"
+                );
+            }
+            if let Some(chat_id) = tier
+                .chat_ids
+                .iter()
+                .find(|id| !self.allowed_chat_ids.contains(id))
+            {
+                anyhow::bail!(
+                    "escalation tier {index} references chat_id {chat_id} not in allowed_chat_ids"
+                );
+            }
+        }
+        if let Some(ref rota) = self.rota {
+            rota.validate(&self.allowed_chat_ids)?;
+            if rota.escalate_after_seconds == 0 && !self.escalation.is_empty() {
+                anyhow::bail!(
+                    "rota.escalate_after_seconds must be above 0 with escalation tiers, \
+                     or they are paged along with whoever is on call"
+                );
+            }
+        }
+        for (index, route) in self.routes.iter().enumerate() {
+            route.validate(index, &self.allowed_chat_ids)?;
+        }
+        for (index, rule) in self.quorum.iter().enumerate() {
+            rule.validate(index)?;
+        }
+        for (index, hours) in self.quiet_hours.iter().enumerate() {
+            hours.validate(index, &self.allowed_chat_ids)?;
+        }
+        if let Some(ref slack) = self.slack {
+            slack.validate()?;
+        }
+        if let Some(ref discord) = self.discord {
+            discord.validate()?;
+        }
+        if let Some(ref matrix) = self.matrix {
+            matrix.validate()?;
+        }
+        if let Some(ref ntfy) = self.ntfy {
+            ntfy.validate()?;
+        }
+        if let Some(ref web) = self.web {
+            web.validate()?;
+        }
+        if let Some(ref email) = self.email {
+            email.validate()?;
+        }
+        if let Some(ref webhook) = self.webhook {
+            webhook.validate()?;
+        }
+        if let Some(ref signal) = self.signal {
+            signal.validate()?;
+        }
         Ok(())
     }
 
+    /// Returns the ordered notification tiers for a permission request.
+    /// Without an explicit escalation chain, every allowed chat is notified at once.
+    pub fn notification_tiers(&self) -> Vec<EscalationTier> {
+        if !self.escalation.is_empty() {
+            return self.escalation.clone();
+        }
+        let mut chat_ids: Vec<i64> = self.allowed_chat_ids.iter().copied().collect();
+        chat_ids.sort_unstable();
+        vec![EscalationTier {
+            chat_ids,
+            escalate_after_seconds: 0,
+        }]
+    }
+
     pub fn effective_socket_path(&self) -> PathBuf {
         if let Some(ref path) = self.socket_path {
             return path.clone();
         }
         default_socket_path()
     }
+
+    /// The TUI's socket, next to the hook's: `vibe-reachout.control.sock`.
+    pub fn effective_control_socket_path(&self) -> PathBuf {
+        let mut path = self.effective_socket_path();
+        path.set_extension("control.sock");
+        path
+    }
 }
 
 fn config_file_path() -> anyhow::Result<PathBuf> {
+    Ok(config_dir()?.join("config.toml"))
+}
+
+/// Path of a bot state file kept next to the config (e.g. `topics.json`).
+pub fn state_file_path(name: &str) -> anyhow::Result<PathBuf> {
+    Ok(config_dir()?.join(name))
+}
+
+fn config_dir() -> anyhow::Result<PathBuf> {
     let home = dirs::home_dir()
         .ok_or_else(|| BotError::ConfigInvalid("Cannot determine home directory".to_string()))?;
-    Ok(home
-        .join(".config")
-        .join("vibe-reachout")
-        .join("config.toml"))
+    Ok(home.join(".config").join("vibe-reachout"))
 }
 
 pub fn default_socket_path() -> PathBuf {
@@ -148,6 +372,42 @@ mod tests {
         );
         let config = Config::load_from_path(&path).unwrap();
         assert_eq!(config.timeout_seconds, 300);
+        assert!(config.forum_topics);
+        assert!(!config.dashboard);
+        assert!(!config.tui);
+        assert_eq!(config.reminder_before_seconds, 60);
+        assert_eq!(config.mute_outcome, MuteOutcome::Terminal);
+        assert_eq!(config.coalesce_window_ms, 2000);
+        assert_eq!(config.reactions, default_reactions());
+    }
+
+    #[test]
+    fn reactions_can_be_remapped_or_disabled() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = write_config(
+            tmp.path(),
+            r#"
+            telegram_bot_token = "tok"
+            allowed_chat_ids = [1]
+
+            [reactions]
+            "\U0001F44C" = "allow"
+            "\U0001F4A9" = "deny"
+            "#,
+        );
+        let config = Config::load_from_path(&path).unwrap();
+        assert_eq!(config.reactions.len(), 2);
+        assert_eq!(config.reactions["\u{1f4a9}"], ReactionAction::Deny);
+
+        let path = write_config(
+            tmp.path(),
+            r#"
+            telegram_bot_token = "tok"
+            allowed_chat_ids = [1]
+            reactions = {}
+            "#,
+        );
+        assert!(Config::load_from_path(&path).unwrap().reactions.is_empty());
     }
 
     #[test]
@@ -192,6 +452,21 @@ mod tests {
         assert!(Config::load_from_path(&path).is_err());
     }
 
+    #[test]
+    fn long_coalesce_window_rejected() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = write_config(
+            tmp.path(),
+            r#"
+            telegram_bot_token = "tok"
+            allowed_chat_ids = [1]
+            coalesce_window_ms = 10001
+            "#,
+        );
+        let err = Config::load_from_path(&path).unwrap_err();
+        assert!(err.to_string().contains("coalesce_window_ms"));
+    }
+
     #[test]
     fn timeout_3601_rejected() {
         let tmp = tempfile::tempdir().unwrap();
@@ -237,6 +512,10 @@ mod tests {
         );
         let config = Config::load_from_path(&path).unwrap();
         assert_eq!(config.effective_socket_path(), sock);
+        assert_eq!(
+            config.effective_control_socket_path(),
+            tmp.path().join("custom.control.sock")
+        );
     }
 
     #[test]
@@ -310,4 +589,641 @@ mod tests {
         let config = Config::load_from_path(&path).unwrap();
         assert_eq!(config.allowed_chat_ids.len(), 3);
     }
+
+    #[test]
+    fn escalation_tiers_parsed_in_order() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = write_config(
+            tmp.path(),
+            r#"
+            telegram_bot_token = "tok"
+            allowed_chat_ids = [1, 2]
+
+            [[escalation]]
+            chat_ids = [1]
+            escalate_after_seconds = 120
+
+            [[escalation]]
+            chat_ids = [2]
+            "#,
+        );
+        let config = Config::load_from_path(&path).unwrap();
+        let tiers = config.notification_tiers();
+        assert_eq!(tiers.len(), 2);
+        assert_eq!(tiers[0].chat_ids, vec![1]);
+        assert_eq!(tiers[0].escalate_after_seconds, 120);
+        assert_eq!(tiers[1].chat_ids, vec![2]);
+        assert_eq!(tiers[1].escalate_after_seconds, 0);
+    }
+
+    #[test]
+    fn no_escalation_notifies_all_chats_at_once() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = write_config(
+            tmp.path(),
+            r#"
+            telegram_bot_token = "tok"
+            allowed_chat_ids = [3, 1, 2]
+            "#,
+        );
+        let config = Config::load_from_path(&path).unwrap();
+        let tiers = config.notification_tiers();
+        assert_eq!(tiers.len(), 1);
+        assert_eq!(tiers[0].chat_ids, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn escalation_with_unknown_chat_rejected() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = write_config(
+            tmp.path(),
+            r#"
+            telegram_bot_token = "tok"
+            allowed_chat_ids = [1]
+
+            [[escalation]]
+            chat_ids = [99]
+            "#,
+        );
+        let err = Config::load_from_path(&path).unwrap_err();
+        assert!(err.to_string().contains("not in allowed_chat_ids"));
+    }
+
+    #[test]
+    fn escalation_with_empty_tier_rejected() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = write_config(
+            tmp.path(),
+            r#"
+            telegram_bot_token = "tok"
+            allowed_chat_ids = [1]
+
+            [[escalation]]
+            chat_ids = []
+            "#,
+        );
+        let err = Config::load_from_path(&path).unwrap_err();
+        assert!(err.to_string().contains("escalation tier 0"));
+    }
+
+    #[test]
+    fn escalation_delay_required_before_the_last_tier() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = write_config(
+            tmp.path(),
+            r#"
+            telegram_bot_token = "tok"
+            allowed_chat_ids = [1, 2, 3]
+
+            [[escalation]]
+            chat_ids = [1]
+            escalate_after_seconds = 60
+
+            [[escalation]]
+            chat_ids = [2]
+
+            [[escalation]]
+            chat_ids = [3]
+            "#,
+        );
+        let err = Config::load_from_path(&path).unwrap_err();
+        assert!(err.to_string().contains("escalation tier 1"));
+        assert!(err.to_string().contains("escalate_after_seconds"));
+    }
+
+    #[test]
+    fn rota_parsed_with_overrides() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = write_config(
+            tmp.path(),
+            r#"
+            telegram_bot_token = "tok"
+            allowed_chat_ids = [1, 2]
+
+            [rota]
+            timezone = "Europe/Madrid"
+            schedule = "weekly"
+            start_date = "2026-01-05"
+            handover_time = "10:30:00"
+            members = [
+                { name = "alice", chat_id = 1 },
+                { name = "bob", chat_id = 2 },
+            ]
+
+            [[rota.overrides]]
+            name = "bob"
+            start = "2026-03-01T00:00:00"
+            end = "2026-03-08T00:00:00"
+            "#,
+        );
+        let config = Config::load_from_path(&path).unwrap();
+        let rota = config.rota.unwrap();
+        assert_eq!(rota.timezone, chrono_tz::Europe::Madrid);
+        assert_eq!(rota.members.len(), 2);
+        assert_eq!(rota.overrides.len(), 1);
+        assert_eq!(rota.handover_time.to_string(), "10:30:00");
+    }
+
+    #[test]
+    fn rota_escalation_delay_required_with_escalation_tiers() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = write_config(
+            tmp.path(),
+            r#"
+            telegram_bot_token = "tok"
+            allowed_chat_ids = [1, 2]
+
+            [[escalation]]
+            chat_ids = [2]
+
+            [rota]
+            timezone = "UTC"
+            schedule = "daily"
+            start_date = "2026-01-05"
+            members = [{ name = "alice", chat_id = 1 }]
+            "#,
+        );
+        let err = Config::load_from_path(&path).unwrap_err();
+        assert!(err.to_string().contains("rota.escalate_after_seconds"));
+    }
+
+    #[test]
+    fn rota_member_not_in_allowed_chats_rejected() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = write_config(
+            tmp.path(),
+            r#"
+            telegram_bot_token = "tok"
+            allowed_chat_ids = [1]
+
+            [rota]
+            timezone = "UTC"
+            schedule = "daily"
+            start_date = "2026-01-05"
+            members = [{ name = "mallory", chat_id = 7 }]
+            "#,
+        );
+        let err = Config::load_from_path(&path).unwrap_err();
+        assert!(err.to_string().contains("rota member mallory"));
+    }
+
+    #[test]
+    fn rota_override_for_unknown_member_rejected() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = write_config(
+            tmp.path(),
+            r#"
+            telegram_bot_token = "tok"
+            allowed_chat_ids = [1]
+
+            [rota]
+            timezone = "UTC"
+            schedule = "daily"
+            start_date = "2026-01-05"
+            members = [{ name = "alice", chat_id = 1 }]
+
+            [[rota.overrides]]
+            name = "carol"
+            start = "2026-03-01T00:00:00"
+            end = "2026-03-02T00:00:00"
+            "#,
+        );
+        let err = Config::load_from_path(&path).unwrap_err();
+        assert!(err.to_string().contains("unknown member carol"));
+    }
+
+    #[test]
+    fn routes_parsed_in_order() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = write_config(
+            tmp.path(),
+            r#"
+            telegram_bot_token = "tok"
+            allowed_chat_ids = [1, -100200]
+
+            [[routes]]
+            git_remote = "*github.com?acme/*"
+            chat_ids = [-100200]
+
+            [[routes]]
+            cwd = "/home/me/hobby/*"
+            chat_ids = [1]
+            "#,
+        );
+        let config = Config::load_from_path(&path).unwrap();
+        assert_eq!(config.routes.len(), 2);
+        assert_eq!(config.routes[0].chat_ids, vec![-100_200]);
+        assert!(config.routes[0].cwd.is_none());
+        assert_eq!(config.routes[1].cwd.as_deref(), Some("/home/me/hobby/*"));
+    }
+
+    #[test]
+    fn route_without_pattern_rejected() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = write_config(
+            tmp.path(),
+            r#"
+            telegram_bot_token = "tok"
+            allowed_chat_ids = [1]
+
+            [[routes]]
+            chat_ids = [1]
+            "#,
+        );
+        let err = Config::load_from_path(&path).unwrap_err();
+        assert!(err.to_string().contains("must set cwd or git_remote"));
+    }
+
+    #[test]
+    fn route_with_invalid_glob_rejected() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = write_config(
+            tmp.path(),
+            r#"
+            telegram_bot_token = "tok"
+            allowed_chat_ids = [1]
+
+            [[routes]]
+            cwd = "/work/[unclosed"
+            chat_ids = [1]
+            "#,
+        );
+        let err = Config::load_from_path(&path).unwrap_err();
+        assert!(err.to_string().contains("invalid glob"));
+    }
+
+    #[test]
+    fn route_with_unknown_chat_rejected() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = write_config(
+            tmp.path(),
+            r#"
+            telegram_bot_token = "tok"
+            allowed_chat_ids = [1]
+
+            [[routes]]
+            cwd = "/work/*"
+            chat_ids = [2]
+            "#,
+        );
+        let err = Config::load_from_path(&path).unwrap_err();
+        assert!(err.to_string().contains("route 0 references chat_id 2"));
+    }
+
+    #[test]
+    fn quorum_rules_parsed() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = write_config(
+            tmp.path(),
+            r#"
+            telegram_bot_token = "tok"
+            allowed_chat_ids = [1]
+
+            [[quorum]]
+            tool = "Bash"
+            command = "*deploy*"
+            approvals = 2
+            "#,
+        );
+        let config = Config::load_from_path(&path).unwrap();
+        assert_eq!(config.quorum.len(), 1);
+        assert_eq!(config.quorum[0].approvals, 2);
+        assert_eq!(config.quorum[0].command.as_deref(), Some("*deploy*"));
+    }
+
+    #[test]
+    fn slack_parsed_and_validated() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = write_config(
+            tmp.path(),
+            r#"
+            telegram_bot_token = "tok"
+            allowed_chat_ids = [1]
+
+            [slack]
+            bot_token = "xoxb-1"
+            signing_secret = "s3cret"
+            channel_id = "C0123"
+            allowed_user_ids = ["U1", "U2"]
+            "#,
+        );
+        let slack = Config::load_from_path(&path).unwrap().slack.unwrap();
+        assert_eq!(slack.channel_id, "C0123");
+        assert!(slack.allowed_user_ids.contains("U2"));
+        assert_eq!(slack.listen.to_string(), "127.0.0.1:8790");
+
+        let path = write_config(
+            tmp.path(),
+            r#"
+            telegram_bot_token = "tok"
+            allowed_chat_ids = [1]
+
+            [slack]
+            bot_token = "xoxb-1"
+            signing_secret = "s3cret"
+            channel_id = "C0123"
+            allowed_user_ids = []
+            "#,
+        );
+        let err = Config::load_from_path(&path).unwrap_err();
+        assert!(err.to_string().contains("allowed_user_ids"));
+    }
+
+    #[test]
+    fn discord_parsed_and_validated() {
+        let tmp = tempfile::tempdir().unwrap();
+        let key = "ab".repeat(32);
+        let path = write_config(
+            tmp.path(),
+            &format!(
+                r#"
+            telegram_bot_token = "tok"
+            allowed_chat_ids = [1]
+
+            [discord]
+            bot_token = "bot"
+            public_key = "{key}"
+            dm_user_id = "1234"
+            allowed_user_ids = ["1234"]
+            "#
+            ),
+        );
+        let discord = Config::load_from_path(&path).unwrap().discord.unwrap();
+        assert_eq!(discord.dm_user_id.as_deref(), Some("1234"));
+        assert!(discord.channel_id.is_none());
+        assert_eq!(discord.listen.to_string(), "127.0.0.1:8791");
+
+        let path = write_config(
+            tmp.path(),
+            &format!(
+                r#"
+            telegram_bot_token = "tok"
+            allowed_chat_ids = [1]
+
+            [discord]
+            bot_token = "bot"
+            public_key = "{key}"
+            channel_id = "99"
+            dm_user_id = "1234"
+            allowed_user_ids = ["1234"]
+            "#
+            ),
+        );
+        let err = Config::load_from_path(&path).unwrap_err();
+        assert!(err.to_string().contains("exactly one"));
+    }
+
+    #[test]
+    fn matrix_parsed_and_validated() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = write_config(
+            tmp.path(),
+            r#"
+            telegram_bot_token = "tok"
+            allowed_chat_ids = [1]
+
+            [matrix]
+            homeserver_url = "https://matrix.example.org"
+            access_token = "syt_abc"
+            user_id = "@bot:example.org"
+            room_id = "!requests:example.org"
+            allowed_user_ids = ["@me:example.org"]
+            "#,
+        );
+        let matrix = Config::load_from_path(&path).unwrap().matrix.unwrap();
+        assert_eq!(matrix.room_id, "!requests:example.org");
+        assert!(matrix.allowed_user_ids.contains("@me:example.org"));
+
+        let path = write_config(
+            tmp.path(),
+            r#"
+            telegram_bot_token = "tok"
+            allowed_chat_ids = [1]
+
+            [matrix]
+            homeserver_url = "https://matrix.example.org"
+            access_token = "syt_abc"
+            user_id = "@bot:example.org"
+            room_id = "!requests:example.org"
+            allowed_user_ids = ["me"]
+            "#,
+        );
+        let err = Config::load_from_path(&path).unwrap_err();
+        assert!(err.to_string().contains("not an MXID"));
+    }
+
+    #[test]
+    fn ntfy_parsed_and_validated() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = write_config(
+            tmp.path(),
+            r#"
+            telegram_bot_token = "tok"
+            allowed_chat_ids = [1]
+
+            [ntfy]
+            topic = "reachout-7f3a9c"
+            callback_url = "http://100.64.0.1:8792"
+            "#,
+        );
+        let ntfy = Config::load_from_path(&path).unwrap().ntfy.unwrap();
+        assert_eq!(ntfy.server_url, "https://ntfy.sh");
+        assert!(ntfy.access_token.is_none());
+        assert_eq!(ntfy.listen.to_string(), "127.0.0.1:8792");
+
+        let path = write_config(
+            tmp.path(),
+            r#"
+            telegram_bot_token = "tok"
+            allowed_chat_ids = [1]
+
+            [ntfy]
+            topic = "my/topic"
+            callback_url = "http://100.64.0.1:8792"
+            "#,
+        );
+        let err = Config::load_from_path(&path).unwrap_err();
+        assert!(err.to_string().contains("topic"));
+    }
+
+    #[test]
+    fn web_token_validated() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = write_config(
+            tmp.path(),
+            r#"
+            telegram_bot_token = "tok"
+            allowed_chat_ids = [1]
+
+            [web]
+            listen = "100.64.0.1:8793"
+            token = "k3y-7f3a9c1e2b4d6f80"
+            "#,
+        );
+        let web = Config::load_from_path(&path).unwrap().web.unwrap();
+        assert_eq!(web.listen.to_string(), "100.64.0.1:8793");
+
+        let path = write_config(
+            tmp.path(),
+            r#"
+            telegram_bot_token = "tok"
+            allowed_chat_ids = [1]
+
+            [web]
+            token = "short"
+            "#,
+        );
+        let err = Config::load_from_path(&path).unwrap_err();
+        assert!(err.to_string().contains("at least 16"));
+    }
+
+    #[test]
+    fn email_section_parsed() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = write_config(
+            tmp.path(),
+            r#"
+            telegram_bot_token = "tok"
+            allowed_chat_ids = [1]
+
+            [email]
+            smtp_host = "smtp.example.com"
+            from = "vibe-reachout <bot@example.com>"
+            to = ["me@example.com"]
+            callback_url = "http://100.64.0.1:8794"
+            "#,
+        );
+        let email = Config::load_from_path(&path).unwrap().email.unwrap();
+        assert_eq!(email.smtp_port, 587);
+        assert_eq!(email.smtp_security, crate::email::SmtpSecurity::Starttls);
+        assert_eq!(email.listen.to_string(), "127.0.0.1:8794");
+        assert!(email.is_recipient("ME@example.com"));
+
+        let path = write_config(
+            tmp.path(),
+            r#"
+            telegram_bot_token = "tok"
+            allowed_chat_ids = [1]
+
+            [email]
+            smtp_host = "localhost"
+            smtp_port = 1025
+            smtp_security = "none"
+            smtp_username = "me"
+            from = "bot@example.com"
+            to = ["me@example.com"]
+            callback_url = "http://127.0.0.1:8794"
+            "#,
+        );
+        let err = Config::load_from_path(&path).unwrap_err();
+        assert!(err.to_string().contains("set together"));
+    }
+
+    #[test]
+    fn webhook_section_parsed() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = write_config(
+            tmp.path(),
+            r#"
+            telegram_bot_token = "tok"
+            allowed_chat_ids = [1]
+
+            [webhook]
+            url = "https://tools.internal/hooks/reachout"
+            secret = "whsec-7f3a9c1e2b4d6f80"
+            "#,
+        );
+        let webhook = Config::load_from_path(&path).unwrap().webhook.unwrap();
+        assert_eq!(webhook.listen.to_string(), "127.0.0.1:8795");
+
+        let path = write_config(
+            tmp.path(),
+            r#"
+            telegram_bot_token = "tok"
+            allowed_chat_ids = [1]
+
+            [webhook]
+            url = "https://tools.internal/hooks/reachout"
+            secret = "short"
+            "#,
+        );
+        let err = Config::load_from_path(&path).unwrap_err();
+        assert!(err.to_string().contains("secret"));
+    }
+
+    #[test]
+    fn signal_numbers_validated() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = write_config(
+            tmp.path(),
+            r#"
+            telegram_bot_token = "tok"
+            allowed_chat_ids = [1]
+
+            [signal]
+            socket_path = "/run/signal-cli/socket"
+            account = "+15550000000"
+            numbers = ["+15551111111"]
+            "#,
+        );
+        let signal = Config::load_from_path(&path).unwrap().signal.unwrap();
+        assert_eq!(signal.socket_path, PathBuf::from("/run/signal-cli/socket"));
+
+        let path = write_config(
+            tmp.path(),
+            r#"
+            telegram_bot_token = "tok"
+            allowed_chat_ids = [1]
+
+            [signal]
+            account = "+15550000000"
+            numbers = ["555-1111"]
+            "#,
+        );
+        let err = Config::load_from_path(&path).unwrap_err();
+        assert!(err.to_string().contains("not a phone number"));
+    }
+
+    #[test]
+    fn quiet_hours_parsed() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = write_config(
+            tmp.path(),
+            r#"
+            telegram_bot_token = "tok"
+            allowed_chat_ids = [1, 2]
+
+            [[quiet_hours]]
+            chat_ids = [1]
+            timezone = "Europe/Madrid"
+            start = "23:00:00"
+            end = "07:00:00"
+            mode = "deny"
+            message = "Asleep"
+            "#,
+        );
+        let config = Config::load_from_path(&path).unwrap();
+        assert_eq!(config.quiet_hours.len(), 1);
+        assert_eq!(config.quiet_hours[0].mode, crate::quiet::QuietMode::Deny);
+        assert_eq!(config.quiet_hours[0].timezone, chrono_tz::Europe::Madrid);
+    }
+
+    #[test]
+    fn quiet_hours_with_unknown_chat_rejected() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = write_config(
+            tmp.path(),
+            r#"
+            telegram_bot_token = "tok"
+            allowed_chat_ids = [1]
+
+            [[quiet_hours]]
+            chat_ids = [9]
+            start = "23:00:00"
+            end = "07:00:00"
+            "#,
+        );
+        let err = Config::load_from_path(&path).unwrap_err();
+        assert!(err.to_string().contains("quiet_hours entry 0"));
+    }
 }
@@ -0,0 +1,283 @@
+use crate::channel::open::OpenRequests;
+use crate::channel::{
+    ApprovalChannel, DecisionQueue, DecisionStream, Page, RequestView, StatusUpdate,
+};
+use crate::control::{ControlCommand, ControlEvent, OpenRequest};
+use crate::ipc::server::PendingMap;
+use crate::models::Approver;
+use futures::future::BoxFuture;
+use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+use std::path::Path;
+use std::sync::Arc;
+use tokio::io::{AsyncBufReadExt, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::net::{UnixListener, UnixStream};
+use tokio::sync::broadcast;
+use tokio::time::Instant;
+use tokio_util::sync::CancellationToken;
+
+/// The control socket as an approval channel: every connected client gets
+/// the open requests, and its decisions are applied like a button tap.
+pub struct ControlChannel {
+    open: OpenRequests,
+    decisions: DecisionQueue,
+}
+
+pub type ControlState = Arc<ControlChannel>;
+
+impl ControlChannel {
+    pub fn new(pending_map: PendingMap) -> Self {
+        Self {
+            open: OpenRequests::new(pending_map),
+            decisions: DecisionQueue::default(),
+        }
+    }
+
+    /// Serves clients on `path` until `cancel_token` fires. The socket is
+    /// only accessible to the bot's user, as it can approve anything.
+    pub async fn serve(
+        self: Arc<Self>,
+        path: &Path,
+        cancel_token: CancellationToken,
+    ) -> anyhow::Result<()> {
+        // The main socket was checked first, so a leftover file here is stale
+        if path.exists() {
+            std::fs::remove_file(path)?;
+        }
+        let listener = bind_private(path)?;
+        tracing::info!("Control socket listening on {}", path.display());
+        loop {
+            let stream = tokio::select! {
+                () = cancel_token.cancelled() => break,
+                accepted = listener.accept() => match accepted {
+                    Ok((stream, _)) => stream,
+                    Err(e) => {
+                        tracing::warn!("Failed to accept control connection: {e}");
+                        continue;
+                    }
+                },
+            };
+            let channel = self.clone();
+            let cancel_token = cancel_token.clone();
+            tokio::spawn(async move {
+                if let Err(e) = channel.handle_client(stream, cancel_token).await {
+                    tracing::debug!("Control client disconnected: {e}");
+                }
+            });
+        }
+        let _ = std::fs::remove_file(path);
+        Ok(())
+    }
+
+    async fn handle_client(
+        &self,
+        stream: UnixStream,
+        cancel_token: CancellationToken,
+    ) -> anyhow::Result<()> {
+        let (reader, mut writer) = stream.into_split();
+        let mut lines = BufReader::new(reader).lines();
+        let mut changes = self.open.subscribe();
+        self.send_requests(&mut writer).await?;
+        loop {
+            tokio::select! {
+                () = cancel_token.cancelled() => return Ok(()),
+                line = lines.next_line() => {
+                    let Some(line) = line? else {
+                        return Ok(());
+                    };
+                    if line.trim().is_empty() {
+                        continue;
+                    }
+                    match serde_json::from_str::<ControlCommand>(&line) {
+                        Ok(command) => self.run_command(command, &mut writer).await?,
+                        Err(e) => tracing::warn!("Bad control command: {e}"),
+                    }
+                }
+                change = changes.recv() => match change {
+                    Ok(()) | Err(broadcast::error::RecvError::Lagged(_)) => {
+                        self.send_requests(&mut writer).await?;
+                    }
+                    Err(broadcast::error::RecvError::Closed) => return Ok(()),
+                },
+            }
+        }
+    }
+
+    async fn run_command(
+        &self,
+        command: ControlCommand,
+        writer: &mut (impl AsyncWrite + Unpin),
+    ) -> anyhow::Result<()> {
+        let ControlCommand::Decide {
+            request_id,
+            verdict,
+        } = command;
+        // Whoever can open the socket is the bot's own user
+        let approver = Approver {
+            id: "tui".to_string(),
+            name: "TUI".to_string(),
+        };
+        let outcome = self.decisions.decide(request_id, verdict, approver).await;
+        let text = outcome.feedback().unwrap_or("Done").to_string();
+        send(writer, &ControlEvent::Feedback { request_id, text }).await
+    }
+
+    async fn send_requests(&self, writer: &mut (impl AsyncWrite + Unpin)) -> anyhow::Result<()> {
+        let now = Instant::now();
+        let requests = self
+            .open
+            .views()
+            .iter()
+            .map(|view| OpenRequest::from_view(view, now))
+            .collect();
+        send(writer, &ControlEvent::Requests { requests }).await
+    }
+}
+
+async fn send(writer: &mut (impl AsyncWrite + Unpin), event: &ControlEvent) -> anyhow::Result<()> {
+    let mut line = serde_json::to_string(event)?;
+    line.push('\n');
+    writer.write_all(line.as_bytes()).await?;
+    Ok(())
+}
+
+impl ApprovalChannel for ControlChannel {
+    fn name(&self) -> &'static str {
+        "TUI"
+    }
+
+    fn send_request<'a>(
+        &'a self,
+        view: &'a RequestView,
+        _page: Page<'a>,
+    ) -> BoxFuture<'a, anyhow::Result<()>> {
+        Box::pin(async move {
+            self.open.show(view.request_id());
+            Ok(())
+        })
+    }
+
+    fn update_status<'a>(
+        &'a self,
+        view: &'a RequestView,
+        update: &'a StatusUpdate,
+    ) -> BoxFuture<'a, ()> {
+        Box::pin(async move { self.open.update(view, update) })
+    }
+
+    fn decisions(&self) -> DecisionStream {
+        self.decisions.stream()
+    }
+
+    // Anyone who can open the socket decides alike
+    fn identifies_approvers(&self) -> bool {
+        false
+    }
+}
+
+/// Binds the socket at `path` without it ever being reachable by other
+/// users: it is created in a new owner-only directory next to `path`,
+/// restricted to the owner, then moved into place.
+fn bind_private(path: &Path) -> anyhow::Result<UnixListener> {
+    let parent = path.parent().unwrap_or_else(|| Path::new("."));
+    let private_dir = parent.join(format!(".vibe-reachout.{}", uuid::Uuid::new_v4().simple()));
+    std::fs::DirBuilder::new()
+        .mode(0o700)
+        .create(&private_dir)?;
+    let staged = private_dir.join("control.sock");
+    let bound = UnixListener::bind(&staged)
+        .map_err(anyhow::Error::from)
+        .and_then(|listener| {
+            std::fs::set_permissions(&staged, std::fs::Permissions::from_mode(0o600))?;
+            std::fs::rename(&staged, path)?;
+            Ok(listener)
+        });
+    let _ = std::fs::remove_file(&staged);
+    let _ = std::fs::remove_dir(&private_dir);
+    bound
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::channel::memory::pending_request;
+    use crate::channel::{DecisionOutcome, Verdict};
+    use dashmap::DashMap;
+    use futures::StreamExt;
+    use tokio::io::Lines;
+    use tokio::net::unix::OwnedReadHalf;
+
+    async fn next_event(lines: &mut Lines<BufReader<OwnedReadHalf>>) -> ControlEvent {
+        let line = lines.next_line().await.unwrap().unwrap();
+        serde_json::from_str(&line).unwrap()
+    }
+
+    #[tokio::test]
+    async fn clients_follow_requests_and_decide() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("control.sock");
+        let pending_map: PendingMap = Arc::new(DashMap::new());
+        let channel = Arc::new(ControlChannel::new(pending_map.clone()));
+        let mut decisions = channel.decisions();
+        let cancel_token = CancellationToken::new();
+        let serving = tokio::spawn({
+            let channel = channel.clone();
+            let path = path.clone();
+            let cancel_token = cancel_token.clone();
+            async move { channel.serve(&path, cancel_token).await }
+        });
+        while !path.exists() {
+            tokio::task::yield_now().await;
+        }
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        // Nothing is left behind from binding it
+        assert_eq!(std::fs::read_dir(tmp.path()).unwrap().count(), 1);
+
+        let (reader, mut writer) = UnixStream::connect(&path).await.unwrap().into_split();
+        let mut lines = BufReader::new(reader).lines();
+        let ControlEvent::Requests { requests } = next_event(&mut lines).await else {
+            panic!("expected the request list");
+        };
+        assert!(requests.is_empty());
+
+        let pending = pending_request("cargo test");
+        let id = pending.request.request_id;
+        let view = pending.view();
+        pending_map.insert(id, pending);
+        let page = Page {
+            tier: 0,
+            deliveries: &[],
+        };
+        channel.send_request(&view, page).await.unwrap();
+        let ControlEvent::Requests { requests } = next_event(&mut lines).await else {
+            panic!("expected the request list");
+        };
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].summary, "Bash: cargo test");
+        assert!(requests[0].seconds_left > 100);
+
+        let command = ControlCommand::Decide {
+            request_id: id,
+            verdict: Verdict::Reply("use nextest".to_string()),
+        };
+        let line = format!("{}\n", serde_json::to_string(&command).unwrap());
+        writer.write_all(line.as_bytes()).await.unwrap();
+        let decision = decisions.next().await.unwrap();
+        assert_eq!(decision.verdict, Verdict::Reply("use nextest".to_string()));
+        assert_eq!(decision.approver.id, "tui");
+        decision
+            .ack
+            .unwrap()
+            .send(DecisionOutcome::AlreadyHandled)
+            .unwrap();
+        let ControlEvent::Feedback { request_id, text } = next_event(&mut lines).await else {
+            panic!("expected feedback");
+        };
+        assert_eq!(request_id, id);
+        assert_eq!(text, "This request has already been handled");
+
+        cancel_token.cancel();
+        serving.await.unwrap().unwrap();
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,55 @@
+pub mod channel;
+
+use crate::channel::{RequestView, Verdict};
+use crate::models::IpcRequest;
+use serde::{Deserialize, Serialize};
+use tokio::time::Instant;
+use uuid::Uuid;
+
+/// Bot to TUI over the control socket, one JSON object per line like the
+/// hook's socket.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ControlEvent {
+    /// Every open request, sent on connecting and after each change.
+    Requests { requests: Vec<OpenRequest> },
+    /// How a decision sent by this client went.
+    Feedback { request_id: Uuid, text: String },
+}
+
+/// TUI to bot.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ControlCommand {
+    Decide { request_id: Uuid, verdict: Verdict },
+}
+
+/// An open request as the TUI shows it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenRequest {
+    pub request: IpcRequest,
+    pub summary: String,
+    /// Until the request falls back to the terminal, as of sending.
+    pub seconds_left: u64,
+    /// Distinct approvals needed (1 = no quorum).
+    pub required_approvals: usize,
+    /// Names of those who approved so far.
+    pub approvals: Vec<String>,
+}
+
+impl OpenRequest {
+    pub fn from_view(view: &RequestView, now: Instant) -> Self {
+        Self {
+            request: view.request.clone(),
+            summary: view.summary.clone(),
+            seconds_left: view.deadline.saturating_duration_since(now).as_secs(),
+            required_approvals: view.required_approvals,
+            approvals: view.approvals.iter().map(|a| a.name.clone()).collect(),
+        }
+    }
+
+    /// Mirrors [`RequestView::offers_always_allow`].
+    pub const fn offers_always_allow(&self) -> bool {
+        !self.request.permission_suggestions.is_empty() && self.required_approvals == 1
+    }
+}
@@ -0,0 +1,127 @@
+use serde_json::{Value, json};
+
+const DEFAULT_BASE_URL: &str = "https://discord.com/api/v10";
+
+/// Message flag: only shown to the user who interacted.
+pub const EPHEMERAL: u64 = 1 << 6;
+
+/// The few Discord REST endpoints the bot uses, authenticated with a bot
+/// token.
+#[derive(Clone)]
+pub struct DiscordApi {
+    client: reqwest::Client,
+    token: String,
+    base_url: String,
+}
+
+impl DiscordApi {
+    pub fn new(token: &str) -> Self {
+        Self::with_base_url(token, DEFAULT_BASE_URL)
+    }
+
+    pub fn with_base_url(token: &str, base_url: &str) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            token: token.to_string(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    /// Posts a message (`content`, `embeds`, `components`...). Returns its
+    /// ID.
+    pub async fn create_message(
+        &self,
+        channel_id: &str,
+        message: &Value,
+    ) -> anyhow::Result<String> {
+        let response = self
+            .call(
+                reqwest::Method::POST,
+                &format!("/channels/{channel_id}/messages"),
+                Some(message),
+            )
+            .await?;
+        id_of(&response, "message")
+    }
+
+    pub async fn edit_message(
+        &self,
+        channel_id: &str,
+        message_id: &str,
+        message: &Value,
+    ) -> anyhow::Result<()> {
+        self.call(
+            reqwest::Method::PATCH,
+            &format!("/channels/{channel_id}/messages/{message_id}"),
+            Some(message),
+        )
+        .await
+        .map(drop)
+    }
+
+    pub async fn delete_message(&self, channel_id: &str, message_id: &str) -> anyhow::Result<()> {
+        self.call(
+            reqwest::Method::DELETE,
+            &format!("/channels/{channel_id}/messages/{message_id}"),
+            None,
+        )
+        .await
+        .map(drop)
+    }
+
+    /// Opens (or finds) the bot's DM channel with a user. Returns its ID.
+    pub async fn create_dm(&self, user_id: &str) -> anyhow::Result<String> {
+        let body = json!({ "recipient_id": user_id });
+        let response = self
+            .call(reqwest::Method::POST, "/users/@me/channels", Some(&body))
+            .await?;
+        id_of(&response, "DM channel")
+    }
+
+    /// A message only the user behind an interaction can see, sent after
+    /// the interaction has been acknowledged.
+    pub async fn send_followup(
+        &self,
+        application_id: &str,
+        interaction_token: &str,
+        content: &str,
+    ) -> anyhow::Result<()> {
+        let body = json!({ "content": content, "flags": EPHEMERAL });
+        self.call(
+            reqwest::Method::POST,
+            &format!("/webhooks/{application_id}/{interaction_token}"),
+            Some(&body),
+        )
+        .await
+        .map(drop)
+    }
+
+    async fn call(
+        &self,
+        method: reqwest::Method,
+        path: &str,
+        body: Option<&Value>,
+    ) -> anyhow::Result<Value> {
+        let mut request = self
+            .client
+            .request(method, format!("{}{path}", self.base_url))
+            .header("Authorization", format!("Bot {}", self.token));
+        if let Some(body) = body {
+            request = request.json(body);
+        }
+        let response = request.send().await?.error_for_status()?;
+        // DELETE answers 204 without a body
+        let text = response.text().await?;
+        if text.is_empty() {
+            return Ok(Value::Null);
+        }
+        Ok(serde_json::from_str(&text)?)
+    }
+}
+
+fn id_of(response: &Value, what: &str) -> anyhow::Result<String> {
+    response["id"]
+        .as_str()
+        .map(str::to_string)
+        .ok_or_else(|| anyhow::anyhow!("Discord returned a {what} without an id"))
+}
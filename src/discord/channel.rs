@@ -0,0 +1,629 @@
+use crate::channel::{
+    ApprovalChannel, DecisionOutcome, DecisionQueue, DecisionStream, Page, RequestView,
+    StatusUpdate, Verdict,
+};
+use crate::discord::DiscordConfig;
+use crate::discord::api::{DiscordApi, EPHEMERAL};
+use crate::discord::embed;
+use crate::http::{Body, Handler, json_response, text_response};
+use crate::models::Approver;
+use crate::telegram::callback_data::{CallbackAction, CallbackData};
+use bytes::Bytes;
+use dashmap::DashMap;
+use futures::future::BoxFuture;
+use hyper::{Method, Request, Response, StatusCode};
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::sync::Arc;
+use tokio::sync::OnceCell;
+use tokio::time::Instant;
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
+
+/// Path Discord posts interactions to.
+pub const INTERACTIONS_PATH: &str = "/discord/interactions";
+
+/// Interaction types.
+const PING: u8 = 1;
+const MESSAGE_COMPONENT: u8 = 3;
+const MODAL_SUBMIT: u8 = 5;
+
+/// Interaction response types.
+const PONG: u8 = 1;
+const CHANNEL_MESSAGE: u8 = 4;
+const DEFERRED_UPDATE: u8 = 6;
+const MODAL: u8 = 9;
+
+/// A request's messages in the Discord channel.
+#[derive(Debug)]
+struct Posted {
+    message_id: String,
+    /// Pre-timeout reminders replying to the message.
+    reminders: Vec<String>,
+}
+
+/// Discord as an approval channel: embeds with buttons in one channel or
+/// DM, answered through the interactions endpoint.
+pub struct DiscordChannel {
+    api: DiscordApi,
+    config: DiscordConfig,
+    public_key: Vec<u8>,
+    /// The channel requests go to; a DM channel is opened on first use.
+    target: OnceCell<String>,
+    posted: DashMap<Uuid, Posted>,
+    decisions: DecisionQueue,
+}
+
+pub type DiscordState = Arc<DiscordChannel>;
+
+impl DiscordChannel {
+    pub fn new(config: DiscordConfig) -> Self {
+        Self::with_api(DiscordApi::new(&config.bot_token), config)
+    }
+
+    pub fn with_api(api: DiscordApi, config: DiscordConfig) -> Self {
+        // Checked by `DiscordConfig::validate`
+        let public_key =
+            crate::discord::signature::parse_public_key(&config.public_key).unwrap_or_default();
+        Self {
+            api,
+            config,
+            public_key,
+            target: OnceCell::new(),
+            posted: DashMap::new(),
+            decisions: DecisionQueue::default(),
+        }
+    }
+
+    /// Serves the interactions endpoint until `cancel_token` fires.
+    pub async fn serve(self: Arc<Self>, cancel_token: CancellationToken) -> anyhow::Result<()> {
+        let addr = self.config.listen;
+        let handler: Handler = Arc::new(move |request| {
+            let channel = self.clone();
+            Box::pin(async move { channel.handle_request(&request) })
+        });
+        crate::http::serve(addr, handler, cancel_token).await
+    }
+
+    /// Checks an interaction and answers it; decisions are applied in the
+    /// background, since Discord wants an answer within three seconds.
+    fn handle_request(self: Arc<Self>, request: &Request<Bytes>) -> Response<Body> {
+        if request.method() != Method::POST || request.uri().path() != INTERACTIONS_PATH {
+            return text_response(StatusCode::NOT_FOUND, "Not found");
+        }
+        let header = |name: &str| {
+            request
+                .headers()
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .unwrap_or_default()
+        };
+        let signed = crate::discord::signature::verify(
+            &self.public_key,
+            header("x-signature-timestamp"),
+            request.body(),
+            header("x-signature-ed25519"),
+            chrono::Utc::now().timestamp(),
+        );
+        if !signed {
+            // Discord probes the endpoint with bad signatures and expects 401
+            tracing::debug!("Discord interaction with a bad signature");
+            return text_response(StatusCode::UNAUTHORIZED, "Bad signature");
+        }
+        let Ok(interaction) = serde_json::from_slice::<Interaction>(request.body()) else {
+            return text_response(StatusCode::BAD_REQUEST, "Unrecognized payload");
+        };
+        match self.respond(interaction) {
+            Some(response) => json_response(&response),
+            None => text_response(StatusCode::BAD_REQUEST, "Unsupported interaction"),
+        }
+    }
+
+    /// The interaction response, or `None` for interactions the bot doesn't
+    /// offer.
+    fn respond(self: Arc<Self>, interaction: Interaction) -> Option<Value> {
+        if interaction.kind == PING {
+            return Some(json!({ "type": PONG }));
+        }
+        if interaction.kind != MESSAGE_COMPONENT && interaction.kind != MODAL_SUBMIT {
+            return None;
+        }
+        let user = interaction.user()?;
+        if !self.config.allowed_user_ids.contains(&user.id) {
+            tracing::warn!(user_id = %user.id, "Unauthorized Discord interaction");
+            return Some(ephemeral(
+                "You are not allowed to answer permission requests",
+            ));
+        }
+        let data = interaction.data.as_ref()?;
+        let callback = CallbackData::parse(&data.custom_id)?;
+        let verdict = match callback.action {
+            CallbackAction::Allow => Verdict::Allow,
+            CallbackAction::Deny => Verdict::Deny,
+            CallbackAction::Always => Verdict::AlwaysAllow,
+            CallbackAction::Reply if interaction.kind == MODAL_SUBMIT => {
+                Verdict::Reply(data.reply_text()?)
+            }
+            CallbackAction::Reply => return Some(self.reply_modal(callback.request_id)),
+            _ => return None,
+        };
+
+        let approver = Approver {
+            id: format!("discord:{}", user.id),
+            name: user.display_name().to_string(),
+        };
+        tokio::spawn(async move {
+            let outcome = self
+                .decisions
+                .decide(callback.request_id, verdict, approver)
+                .await;
+            if let Some(text) = outcome.feedback() {
+                self.tell(&interaction, text).await;
+            }
+        });
+        Some(json!({ "type": DEFERRED_UPDATE }))
+    }
+
+    /// Opens the reply modal, unless the request is already resolved.
+    fn reply_modal(&self, request_id: Uuid) -> Value {
+        if !self.posted.contains_key(&request_id) {
+            return ephemeral(
+                DecisionOutcome::AlreadyHandled
+                    .feedback()
+                    .unwrap_or_default(),
+            );
+        }
+        json!({ "type": MODAL, "data": embed::reply_modal(request_id) })
+    }
+
+    /// An ephemeral follow-up to an acknowledged interaction.
+    async fn tell(&self, interaction: &Interaction, text: &str) {
+        if let Err(e) = self
+            .api
+            .send_followup(&interaction.application_id, &interaction.token, text)
+            .await
+        {
+            tracing::warn!("Failed to send Discord feedback: {e}");
+        }
+    }
+
+    /// The channel requests are posted to, opening the DM on first use.
+    async fn target(&self) -> anyhow::Result<&str> {
+        self.target
+            .get_or_try_init(|| async {
+                match (&self.config.channel_id, &self.config.dm_user_id) {
+                    (Some(channel_id), _) => Ok(channel_id.clone()),
+                    (None, Some(user_id)) => self.api.create_dm(user_id).await,
+                    (None, None) => anyhow::bail!("discord has no channel_id or dm_user_id"),
+                }
+            })
+            .await
+            .map(String::as_str)
+    }
+
+    async fn refresh(&self, view: &RequestView) {
+        let Some(message_id) = self.message_id(view) else {
+            return;
+        };
+        let message = embed::open_message(view, Instant::now());
+        self.edit(&message_id, &message).await;
+    }
+
+    /// Replies to the message so the request isn't missed.
+    async fn remind(&self, view: &RequestView) {
+        let (Some(message_id), Some(target)) = (self.message_id(view), self.target.get()) else {
+            return;
+        };
+        let text = crate::telegram::formatter::reminder_text(
+            view.deadline.saturating_duration_since(Instant::now()),
+        );
+        let reminder = json!({
+            "content": text,
+            "message_reference": { "message_id": message_id, "fail_if_not_exists": false },
+        });
+        match self.api.create_message(target, &reminder).await {
+            Ok(reminder) => {
+                if let Some(mut posted) = self.posted.get_mut(&view.request_id()) {
+                    posted.reminders.push(reminder);
+                }
+            }
+            Err(e) => tracing::warn!("Failed to send Discord reminder: {e}"),
+        }
+    }
+
+    async fn delete_reminders(&self, reminders: &[String]) {
+        let Some(target) = self.target.get() else {
+            return;
+        };
+        for message_id in reminders {
+            if let Err(e) = self.api.delete_message(target, message_id).await {
+                tracing::debug!("Failed to delete Discord reminder: {e}");
+            }
+        }
+    }
+
+    /// Shows `status` in place of the buttons and forgets the message.
+    async fn close(&self, view: &RequestView, status: &str) {
+        let Some((_, posted)) = self.posted.remove(&view.request_id()) else {
+            return;
+        };
+        self.delete_reminders(&posted.reminders).await;
+        let message = embed::closed_message(&view.request, status);
+        self.edit(&posted.message_id, &message).await;
+    }
+
+    fn message_id(&self, view: &RequestView) -> Option<String> {
+        self.posted
+            .get(&view.request_id())
+            .map(|posted| posted.message_id.clone())
+    }
+
+    async fn edit(&self, message_id: &str, message: &Value) {
+        let Some(target) = self.target.get() else {
+            return;
+        };
+        if let Err(e) = self.api.edit_message(target, message_id, message).await {
+            tracing::warn!(message_id = %message_id, "Failed to edit Discord message: {e}");
+        }
+    }
+}
+
+impl ApprovalChannel for DiscordChannel {
+    fn name(&self) -> &'static str {
+        "Discord"
+    }
+
+    fn send_request<'a>(
+        &'a self,
+        view: &'a RequestView,
+        _page: Page<'a>,
+    ) -> BoxFuture<'a, anyhow::Result<()>> {
+        Box::pin(async move {
+            if self.posted.contains_key(&view.request_id()) {
+                return Ok(());
+            }
+            let target = self.target().await?;
+            let message = embed::open_message(view, Instant::now());
+            let message_id = self.api.create_message(target, &message).await?;
+            self.posted.insert(
+                view.request_id(),
+                Posted {
+                    message_id,
+                    reminders: Vec::new(),
+                },
+            );
+            Ok(())
+        })
+    }
+
+    fn update_status<'a>(
+        &'a self,
+        view: &'a RequestView,
+        update: &'a StatusUpdate,
+    ) -> BoxFuture<'a, ()> {
+        Box::pin(async move {
+            match update {
+                StatusUpdate::Progress => self.refresh(view).await,
+                StatusUpdate::Extended => {
+                    let reminders = self
+                        .posted
+                        .get_mut(&view.request_id())
+                        .map(|mut posted| std::mem::take(&mut posted.reminders))
+                        .unwrap_or_default();
+                    self.delete_reminders(&reminders).await;
+                    self.refresh(view).await;
+                }
+                StatusUpdate::Reminder => self.remind(view).await,
+                StatusUpdate::Withdrawn(status) | StatusUpdate::Resolved(status) => {
+                    self.close(view, status).await;
+                }
+            }
+        })
+    }
+
+    fn decisions(&self) -> DecisionStream {
+        self.decisions.stream()
+    }
+}
+
+/// An interaction response message only the interacting user sees.
+fn ephemeral(content: &str) -> Value {
+    json!({
+        "type": CHANNEL_MESSAGE,
+        "data": { "content": content, "flags": EPHEMERAL },
+    })
+}
+
+/// The part of an interaction the bot acts on.
+#[derive(Debug, Deserialize)]
+struct Interaction {
+    #[serde(rename = "type")]
+    kind: u8,
+    #[serde(default)]
+    application_id: String,
+    #[serde(default)]
+    token: String,
+    data: Option<InteractionData>,
+    /// Set in guild channels.
+    member: Option<Member>,
+    /// Set in DMs.
+    user: Option<DiscordUser>,
+}
+
+impl Interaction {
+    fn user(&self) -> Option<&DiscordUser> {
+        self.member
+            .as_ref()
+            .map(|member| &member.user)
+            .or(self.user.as_ref())
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Member {
+    user: DiscordUser,
+}
+
+#[derive(Debug, Deserialize)]
+struct DiscordUser {
+    id: String,
+    #[serde(default)]
+    username: String,
+    global_name: Option<String>,
+}
+
+impl DiscordUser {
+    fn display_name(&self) -> &str {
+        self.global_name.as_deref().unwrap_or(&self.username)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct InteractionData {
+    #[serde(default)]
+    custom_id: String,
+    /// Action rows of a submitted modal.
+    #[serde(default)]
+    components: Vec<ActionRow>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ActionRow {
+    #[serde(default)]
+    components: Vec<TextInput>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TextInput {
+    #[serde(default)]
+    custom_id: String,
+    value: Option<String>,
+}
+
+impl InteractionData {
+    /// The text of a submitted reply modal.
+    fn reply_text(&self) -> Option<String> {
+        let text = self
+            .components
+            .iter()
+            .flat_map(|row| &row.components)
+            .find(|input| input.custom_id == embed::REPLY_INPUT)?
+            .value
+            .as_deref()?
+            .trim();
+        (!text.is_empty()).then(|| text.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::discord::signature;
+    use crate::http::test_support::{json_body, recording_server};
+    use crate::models::IpcRequest;
+    use futures::StreamExt;
+    use http_body_util::BodyExt;
+    use ring::signature::Ed25519KeyPair;
+    use tokio::sync::mpsc;
+
+    /// Stands in for the Discord REST API, reporting each call as
+    /// `(method path, body)`.
+    async fn fake_discord() -> (DiscordApi, mpsc::UnboundedReceiver<(String, Value)>) {
+        let record = |request: &Request<Bytes>| {
+            let path = request.uri().path().trim_start_matches("/api");
+            Some((format!("{} {path}", request.method()), json_body(request)))
+        };
+        let respond = |_: &Request<Bytes>| std::future::ready(json_response(&json!({"id": "10"})));
+        let (url, calls) = recording_server(record, respond).await;
+        (
+            DiscordApi::with_base_url("bot-token", &format!("{url}/api")),
+            calls,
+        )
+    }
+
+    fn make_channel(api: DiscordApi, public_key: String) -> Arc<DiscordChannel> {
+        let config = DiscordConfig {
+            bot_token: "bot-token".to_string(),
+            public_key,
+            channel_id: None,
+            dm_user_id: Some("42".to_string()),
+            allowed_user_ids: ["42".to_string()].into(),
+            listen: "127.0.0.1:0".parse().unwrap(),
+        };
+        Arc::new(DiscordChannel::with_api(api, config))
+    }
+
+    fn make_view() -> RequestView {
+        RequestView {
+            request: IpcRequest {
+                request_id: Uuid::new_v4(),
+                tool_name: "Bash".to_string(),
+                tool_input: json!({"command": "ls"}),
+                cwd: "/tmp/proj".to_string(),
+                session_id: "s".to_string(),
+                permission_suggestions: vec![],
+                assistant_context: None,
+                first_prompt: None,
+            },
+            summary: "Bash: ls".to_string(),
+            deadline: Instant::now() + std::time::Duration::from_secs(150),
+            required_approvals: 1,
+            approvals: vec![],
+        }
+    }
+
+    /// An interaction as Discord would sign it.
+    fn interaction(payload: &Value, key_pair: &Ed25519KeyPair) -> Request<Bytes> {
+        let body = payload.to_string();
+        let timestamp = chrono::Utc::now().timestamp().to_string();
+        let signature = signature::sign(key_pair, &timestamp, body.as_bytes());
+        Request::post(INTERACTIONS_PATH)
+            .header("x-signature-timestamp", timestamp)
+            .header("x-signature-ed25519", signature)
+            .body(Bytes::from(body))
+            .unwrap()
+    }
+
+    fn click(user: &str, custom_id: &str) -> Value {
+        json!({
+            "type": MESSAGE_COMPONENT,
+            "application_id": "app",
+            "token": "tok",
+            "member": {"user": {"id": user, "username": "alice"}},
+            "data": {"custom_id": custom_id, "component_type": 2},
+        })
+    }
+
+    async fn body_of(response: Response<Body>) -> Value {
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    #[tokio::test]
+    async fn posts_to_dm_and_resolves_requests() {
+        let (api, mut calls) = fake_discord().await;
+        let (_, public_key) = signature::test_key_pair();
+        let channel = make_channel(api, public_key);
+        let view = make_view();
+
+        let page = Page {
+            tier: 0,
+            deliveries: &[],
+        };
+        channel.send_request(&view, page).await.unwrap();
+        let (call, body) = calls.recv().await.unwrap();
+        assert_eq!(call, "POST /users/@me/channels");
+        assert_eq!(body["recipient_id"], "42");
+        let (call, body) = calls.recv().await.unwrap();
+        assert_eq!(call, "POST /channels/10/messages");
+        assert_eq!(
+            body["components"][0]["components"][0]["custom_id"],
+            format!("{}:allow", view.request_id())
+        );
+
+        channel.update_status(&view, &StatusUpdate::Reminder).await;
+        let (call, body) = calls.recv().await.unwrap();
+        assert_eq!(call, "POST /channels/10/messages");
+        assert_eq!(body["message_reference"]["message_id"], "10");
+
+        let status = StatusUpdate::Resolved("\u{2705} Approved".to_string());
+        channel.update_status(&view, &status).await;
+        let (call, _) = calls.recv().await.unwrap();
+        assert_eq!(call, "DELETE /channels/10/messages/10");
+        let (call, body) = calls.recv().await.unwrap();
+        assert_eq!(call, "PATCH /channels/10/messages/10");
+        assert_eq!(body["components"], json!([]));
+        assert_eq!(body["embeds"][0]["footer"]["text"], "\u{2705} Approved");
+        assert!(calls.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn verified_clicks_become_decisions() {
+        let (api, mut calls) = fake_discord().await;
+        let (key_pair, public_key) = signature::test_key_pair();
+        let channel = make_channel(api, public_key);
+        let mut decisions = channel.decisions();
+        let id = Uuid::new_v4();
+
+        let ping = channel
+            .clone()
+            .handle_request(&interaction(&json!({"type": PING}), &key_pair));
+        assert_eq!(body_of(ping).await["type"], PONG);
+
+        let (other_key_pair, _) = signature::test_key_pair();
+        let forged = interaction(&click("42", &format!("{id}:deny")), &other_key_pair);
+        let response = channel.clone().handle_request(&forged);
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let response = channel
+            .clone()
+            .handle_request(&interaction(&click("42", &format!("{id}:deny")), &key_pair));
+        assert_eq!(body_of(response).await["type"], DEFERRED_UPDATE);
+        let decision = decisions.next().await.unwrap();
+        assert_eq!(decision.request_id, id);
+        assert_eq!(decision.verdict, Verdict::Deny);
+        assert_eq!(decision.approver.id, "discord:42");
+        assert_eq!(decision.approver.name, "alice");
+        decision
+            .ack
+            .unwrap()
+            .send(DecisionOutcome::AlreadyHandled)
+            .unwrap();
+        let (call, body) = calls.recv().await.unwrap();
+        assert_eq!(call, "POST /webhooks/app/tok");
+        assert_eq!(body["content"], "This request has already been handled");
+        assert_eq!(body["flags"], EPHEMERAL);
+
+        // Strangers are told off and nothing is decided
+        let response = channel
+            .clone()
+            .handle_request(&interaction(&click("7", &format!("{id}:allow")), &key_pair));
+        let body = body_of(response).await;
+        assert_eq!(body["type"], CHANNEL_MESSAGE);
+        assert_eq!(body["data"]["flags"], EPHEMERAL);
+    }
+
+    #[tokio::test]
+    async fn reply_opens_modal_and_submission_decides() {
+        let (api, _calls) = fake_discord().await;
+        let (key_pair, public_key) = signature::test_key_pair();
+        let channel = make_channel(api, public_key);
+        let mut decisions = channel.decisions();
+        let view = make_view();
+        let reply_id = format!("{}:reply", view.request_id());
+
+        // Not posted (or already resolved): no modal
+        let response = channel
+            .clone()
+            .handle_request(&interaction(&click("42", &reply_id), &key_pair));
+        assert_eq!(body_of(response).await["type"], CHANNEL_MESSAGE);
+
+        let page = Page {
+            tier: 0,
+            deliveries: &[],
+        };
+        channel.send_request(&view, page).await.unwrap();
+        let response = channel
+            .clone()
+            .handle_request(&interaction(&click("42", &reply_id), &key_pair));
+        let body = body_of(response).await;
+        assert_eq!(body["type"], MODAL);
+        assert_eq!(body["data"]["custom_id"], reply_id);
+
+        let submit = json!({
+            "type": MODAL_SUBMIT,
+            "user": {"id": "42", "username": "alice", "global_name": "Alice"},
+            "data": {
+                "custom_id": reply_id,
+                "components": [{"type": 1, "components": [{"type": 4, "custom_id": "text", "value": " use pytest "}]}],
+            },
+        });
+        let response = channel
+            .clone()
+            .handle_request(&interaction(&submit, &key_pair));
+        assert_eq!(body_of(response).await["type"], DEFERRED_UPDATE);
+        let decision = decisions.next().await.unwrap();
+        assert_eq!(decision.verdict, Verdict::Reply("use pytest".to_string()));
+        assert_eq!(decision.approver.name, "Alice");
+    }
+}
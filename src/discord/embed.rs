@@ -0,0 +1,269 @@
+use crate::channel::RequestView;
+use crate::models::IpcRequest;
+use crate::telegram::formatter::{
+    ToolDetails, format_time_left, project_name, short_session_id, tool_details, truncate,
+};
+use serde_json::{Value, json};
+use tokio::time::Instant;
+use uuid::Uuid;
+
+/// Discord caps an embed description at 4096 characters.
+const MAX_DESCRIPTION_CHARS: usize = 4000;
+
+/// Embed colors of open and closed requests.
+const OPEN_COLOR: u32 = 0xF1_C4_0F;
+const CLOSED_COLOR: u32 = 0x95_A5_A6;
+
+/// Custom ID of the reply modal's text input.
+pub const REPLY_INPUT: &str = "text";
+
+/// Button styles.
+const PRIMARY: u8 = 1;
+const SECONDARY: u8 = 2;
+const SUCCESS: u8 = 3;
+const DANGER: u8 = 4;
+
+/// Backslash-escapes the characters Discord markdown would act on.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(
+            c,
+            '\\' | '*' | '_' | '~' | '`' | '|' | '>' | '#' | '[' | ']'
+        ) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// A code block that `code` can't break out of.
+fn code_block(code: &str) -> String {
+    format!("```\n{}\n```", code.replace("```", "`\u{200b}``"))
+}
+
+/// Inline code, doubling the backticks if `text` contains one.
+fn code_span(text: &str) -> String {
+    if text.contains('`') {
+        format!("`` {text} ``")
+    } else {
+        format!("`{text}`")
+    }
+}
+
+/// The embed description in Discord markdown, with the same fields as the
+/// Telegram message.
+pub fn permission_text(request: &IpcRequest) -> String {
+    let details = match tool_details(&request.tool_name, &request.tool_input) {
+        ToolDetails::Code(code) => code_block(&code),
+        ToolDetails::Write { file_path, size } => {
+            format!("\u{1f4c4} {} ({size})", code_span(&file_path))
+        }
+        ToolDetails::Edit {
+            file_path,
+            old,
+            new,
+        } => format!(
+            "\u{1f4c4} {}\n{}",
+            code_span(&file_path),
+            code_block(&format!("- {old}\n+ {new}"))
+        ),
+    };
+    let context = request
+        .assistant_context
+        .as_deref()
+        .map(|ctx| format!("\u{1f4ac} {}\n\n", escape(ctx)))
+        .unwrap_or_default();
+    let text = format!(
+        "{context}**\u{1f527} {tool}**\n{details}\n\n\u{1f4c1} {cwd}\n\u{1f194} Session: {session}",
+        tool = escape(&request.tool_name),
+        cwd = escape(&request.cwd),
+        session = code_span(short_session_id(&request.session_id)),
+    );
+    truncate(&text, MAX_DESCRIPTION_CHARS)
+}
+
+/// Plain-text content shown in notifications.
+pub fn notification_text(view: &RequestView) -> String {
+    format!(
+        "Permission request: {} in {}",
+        view.summary,
+        project_name(&view.request.cwd)
+    )
+}
+
+/// A still-open request: the embed with its progress, and buttons.
+pub fn open_message(view: &RequestView, now: Instant) -> Value {
+    let mut status = Vec::new();
+    if view.required_approvals > 1 {
+        status.push(crate::quorum::format_progress(
+            &view.approvals,
+            view.required_approvals,
+        ));
+    }
+    status.push(format_time_left(
+        view.deadline.saturating_duration_since(now),
+    ));
+
+    let id = view.request_id();
+    let mut buttons = vec![
+        button(id, "allow", "\u{2705} Allow", SUCCESS),
+        button(id, "deny", "\u{274c} Deny", DANGER),
+        button(id, "reply", "\u{1f4ac} Reply", SECONDARY),
+    ];
+    if view.offers_always_allow() {
+        buttons.push(button(id, "always", "\u{1f513} Always Allow", PRIMARY));
+    }
+    json!({
+        "content": notification_text(view),
+        "embeds": [embed(&view.request, &status.join("\n"), OPEN_COLOR)],
+        "components": [{ "type": 1, "components": buttons }],
+    })
+}
+
+/// A request that is no longer open: the embed with `status` and no
+/// buttons.
+pub fn closed_message(request: &IpcRequest, status: &str) -> Value {
+    json!({
+        "embeds": [embed(request, status, CLOSED_COLOR)],
+        "components": [],
+    })
+}
+
+/// The modal opened by "Reply", as interaction response data.
+pub fn reply_modal(request_id: Uuid) -> Value {
+    json!({
+        "custom_id": format!("{request_id}:reply"),
+        "title": "Reply to Claude",
+        "components": [{
+            "type": 1,
+            "components": [{
+                "type": 4,
+                "custom_id": REPLY_INPUT,
+                "label": "Your reply",
+                "style": 2,
+                "min_length": 1,
+                "required": true,
+            }],
+        }],
+    })
+}
+
+fn embed(request: &IpcRequest, status: &str, color: u32) -> Value {
+    json!({
+        "title": format!("\u{1f4cb} {}", project_name(&request.cwd)),
+        "description": permission_text(request),
+        "color": color,
+        "footer": { "text": status },
+    })
+}
+
+/// A button whose custom ID is `{request_id}:{action}`, as in Telegram
+/// callback data.
+fn button(request_id: Uuid, action: &str, label: &str, style: u8) -> Value {
+    json!({
+        "type": 2,
+        "style": style,
+        "label": label,
+        "custom_id": format!("{request_id}:{action}"),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::telegram::callback_data::{CallbackAction, CallbackData};
+
+    fn make_view(
+        tool_input: Value,
+        suggestions: Vec<Value>,
+        required_approvals: usize,
+    ) -> RequestView {
+        RequestView {
+            request: IpcRequest {
+                request_id: Uuid::new_v4(),
+                tool_name: "Bash".to_string(),
+                tool_input,
+                cwd: "/home/me/my_proj".to_string(),
+                session_id: "abcdef1234".to_string(),
+                permission_suggestions: suggestions,
+                assistant_context: Some("Running *all* tests".to_string()),
+                first_prompt: None,
+            },
+            summary: "Bash: ls".to_string(),
+            deadline: Instant::now() + std::time::Duration::from_secs(150),
+            required_approvals,
+            approvals: vec![],
+        }
+    }
+
+    #[test]
+    fn permission_text_is_discord_markdown() {
+        let view = make_view(json!({"command": "echo ```hi```"}), vec![], 1);
+        assert_eq!(
+            permission_text(&view.request),
+            "\u{1f4ac} Running \\*all\\* tests\n\n**\u{1f527} Bash**\n```\necho `\u{200b}``hi`\u{200b}``\n```\n\n\u{1f4c1} /home/me/my\\_proj\n\u{1f194} Session: `abcdef12`"
+        );
+    }
+
+    #[test]
+    fn open_message_buttons_parse_as_callback_data() {
+        let now = Instant::now();
+        let view = make_view(json!({"command": "ls"}), vec![], 1);
+        let message = open_message(&view, now);
+        let actions: Vec<CallbackAction> = message["components"][0]["components"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|b| {
+                let data = CallbackData::parse(b["custom_id"].as_str().unwrap()).unwrap();
+                assert_eq!(data.request_id, view.request_id());
+                data.action
+            })
+            .collect();
+        assert_eq!(
+            actions,
+            [
+                CallbackAction::Allow,
+                CallbackAction::Deny,
+                CallbackAction::Reply
+            ]
+        );
+
+        let view = make_view(json!({"command": "ls"}), vec![json!({})], 1);
+        let message = open_message(&view, now);
+        assert_eq!(
+            message["components"][0]["components"]
+                .as_array()
+                .unwrap()
+                .len(),
+            4
+        );
+        // Always Allow would bypass the quorum
+        let view = make_view(json!({"command": "ls"}), vec![json!({})], 2);
+        let message = open_message(&view, now);
+        assert_eq!(
+            message["components"][0]["components"]
+                .as_array()
+                .unwrap()
+                .len(),
+            3
+        );
+        assert!(
+            message["embeds"][0]["footer"]["text"]
+                .as_str()
+                .unwrap()
+                .starts_with("\u{1f5f3} 0/2 approvals")
+        );
+    }
+
+    #[test]
+    fn closed_message_removes_buttons() {
+        let view = make_view(json!({"command": "ls"}), vec![], 1);
+        let message = closed_message(&view.request, "\u{2705} Approved");
+        assert_eq!(message["components"], json!([]));
+        assert_eq!(message["embeds"][0]["footer"]["text"], "\u{2705} Approved");
+        assert_eq!(message["embeds"][0]["color"], CLOSED_COLOR);
+    }
+}
@@ -0,0 +1,50 @@
+pub mod api;
+pub mod channel;
+pub mod embed;
+pub mod signature;
+
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::net::SocketAddr;
+
+/// Posting requests to a Discord channel or DM, answered from message
+/// buttons.
+#[derive(Debug, Deserialize, Clone)]
+pub struct DiscordConfig {
+    /// Bot token of the Discord application.
+    pub bot_token: String,
+    /// The application's public key (hex), which verifies that
+    /// interactions really come from Discord.
+    pub public_key: String,
+    /// ID of the channel requests are posted to.
+    pub channel_id: Option<String>,
+    /// Or the user whose DMs with the bot they are posted to.
+    pub dm_user_id: Option<String>,
+    /// Discord user IDs allowed to answer requests.
+    pub allowed_user_ids: HashSet<String>,
+    /// Address of the interactions endpoint (`POST /discord/interactions`).
+    #[serde(default = "default_listen")]
+    pub listen: SocketAddr,
+}
+
+fn default_listen() -> SocketAddr {
+    SocketAddr::from(([127, 0, 0, 1], 8791))
+}
+
+impl DiscordConfig {
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if self.bot_token.is_empty() {
+            anyhow::bail!("discord bot_token must not be empty");
+        }
+        if signature::parse_public_key(&self.public_key).is_none() {
+            anyhow::bail!("discord public_key must be 64 hex characters");
+        }
+        if self.channel_id.is_some() == self.dm_user_id.is_some() {
+            anyhow::bail!("discord needs exactly one of channel_id and dm_user_id");
+        }
+        if self.allowed_user_ids.is_empty() {
+            anyhow::bail!("discord allowed_user_ids must have at least one entry");
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,89 @@
+use ring::signature::{ED25519, UnparsedPublicKey};
+
+/// Requests signed longer ago than this are rejected as possible replays.
+const MAX_AGE_SECS: i64 = 5 * 60;
+
+/// Decodes an application public key as shown in the developer portal.
+pub fn parse_public_key(public_key: &str) -> Option<Vec<u8>> {
+    hex::decode(public_key).ok().filter(|key| key.len() == 32)
+}
+
+/// Checks Discord's `X-Signature-Ed25519` (hex Ed25519 signature of
+/// `{timestamp}{body}`) and that `X-Signature-Timestamp` is recent.
+pub fn verify(public_key: &[u8], timestamp: &str, body: &[u8], signature: &str, now: i64) -> bool {
+    let Ok(signed_at) = timestamp.parse::<i64>() else {
+        return false;
+    };
+    if (now - signed_at).abs() > MAX_AGE_SECS {
+        return false;
+    }
+    let Ok(signature) = hex::decode(signature) else {
+        return false;
+    };
+    UnparsedPublicKey::new(&ED25519, public_key)
+        .verify(&signed_payload(timestamp, body), &signature)
+        .is_ok()
+}
+
+fn signed_payload(timestamp: &str, body: &[u8]) -> Vec<u8> {
+    let mut payload = timestamp.as_bytes().to_vec();
+    payload.extend_from_slice(body);
+    payload
+}
+
+/// A key pair standing in for Discord's, with its public key in hex.
+#[cfg(test)]
+pub fn test_key_pair() -> (ring::signature::Ed25519KeyPair, String) {
+    use ring::signature::KeyPair;
+    let pkcs8 =
+        ring::signature::Ed25519KeyPair::generate_pkcs8(&ring::rand::SystemRandom::new()).unwrap();
+    let key_pair = ring::signature::Ed25519KeyPair::from_pkcs8(pkcs8.as_ref()).unwrap();
+    let public_key = hex::encode(key_pair.public_key().as_ref());
+    (key_pair, public_key)
+}
+
+#[cfg(test)]
+pub fn sign(key_pair: &ring::signature::Ed25519KeyPair, timestamp: &str, body: &[u8]) -> String {
+    hex::encode(key_pair.sign(&signed_payload(timestamp, body)).as_ref())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TIMESTAMP: &str = "1700000000";
+    const BODY: &[u8] = br#"{"type":1}"#;
+
+    #[test]
+    fn accepts_signed_requests() {
+        let (key_pair, public_key) = test_key_pair();
+        let key = parse_public_key(&public_key).unwrap();
+        let signature = sign(&key_pair, TIMESTAMP, BODY);
+        assert!(verify(&key, TIMESTAMP, BODY, &signature, 1_700_000_060));
+    }
+
+    #[test]
+    fn rejects_tampering_and_stale_requests() {
+        let (key_pair, public_key) = test_key_pair();
+        let key = parse_public_key(&public_key).unwrap();
+        let signature = sign(&key_pair, TIMESTAMP, BODY);
+        let now = 1_700_000_000;
+        assert!(!verify(&key, TIMESTAMP, br#"{"type":3}"#, &signature, now));
+        assert!(!verify(&key, "1700000001", BODY, &signature, now));
+        assert!(!verify(&key, TIMESTAMP, BODY, "zz", now));
+        assert!(!verify(&key, "soon", BODY, &signature, now));
+        let later = now + MAX_AGE_SECS + 1;
+        assert!(!verify(&key, TIMESTAMP, BODY, &signature, later));
+
+        let (_, other_key) = test_key_pair();
+        let other_key = parse_public_key(&other_key).unwrap();
+        assert!(!verify(&other_key, TIMESTAMP, BODY, &signature, now));
+    }
+
+    #[test]
+    fn public_key_must_be_32_bytes() {
+        assert!(parse_public_key(&"ab".repeat(32)).is_some());
+        assert!(parse_public_key(&"ab".repeat(31)).is_none());
+        assert!(parse_public_key("not hex").is_none());
+    }
+}
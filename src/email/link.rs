@@ -0,0 +1,86 @@
+use ring::hmac;
+use uuid::Uuid;
+
+/// Why a link was refused.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinkError {
+    /// Malformed, or not signed with this bot's key.
+    Invalid,
+    Expired,
+}
+
+/// Signs the tokens of emailed links: `{request_id}.{action}.{expires}.{tag}`,
+/// where `tag` is the hex HMAC-SHA256 of everything before it. The key lives
+/// only as long as the bot, as do the requests it signs for.
+pub struct LinkKey(hmac::Key);
+
+impl LinkKey {
+    pub fn generate() -> Self {
+        let rng = ring::rand::SystemRandom::new();
+        Self(
+            hmac::Key::generate(hmac::HMAC_SHA256, &rng)
+                .expect("system random number generator failed"),
+        )
+    }
+
+    /// A token for taking `action` on a request, valid until `expires` (Unix
+    /// seconds).
+    pub fn sign(&self, request_id: Uuid, action: &str, expires: i64) -> String {
+        let payload = format!("{request_id}.{action}.{expires}");
+        let tag = hmac::sign(&self.0, payload.as_bytes());
+        format!("{payload}.{}", hex::encode(tag.as_ref()))
+    }
+
+    /// The request and action a token was signed for, if it is genuine and
+    /// unexpired at `now`.
+    pub fn verify<'a>(&self, token: &'a str, now: i64) -> Result<(Uuid, &'a str), LinkError> {
+        let (payload, tag) = token.rsplit_once('.').ok_or(LinkError::Invalid)?;
+        let tag = hex::decode(tag).map_err(|_| LinkError::Invalid)?;
+        hmac::verify(&self.0, payload.as_bytes(), &tag).map_err(|_| LinkError::Invalid)?;
+
+        let mut parts = payload.splitn(3, '.');
+        let (Some(request_id), Some(action), Some(expires)) =
+            (parts.next(), parts.next(), parts.next())
+        else {
+            return Err(LinkError::Invalid);
+        };
+        let request_id = request_id.parse().map_err(|_| LinkError::Invalid)?;
+        let expires: i64 = expires.parse().map_err(|_| LinkError::Invalid)?;
+        if now > expires {
+            return Err(LinkError::Expired);
+        }
+        Ok((request_id, action))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signed_links_verify_until_they_expire() {
+        let key = LinkKey::generate();
+        let id = Uuid::new_v4();
+        let token = key.sign(id, "allow", 1_000);
+        assert!(token.starts_with(&format!("{id}.allow.1000.")));
+        assert_eq!(key.verify(&token, 1_000), Ok((id, "allow")));
+        assert_eq!(key.verify(&token, 1_001), Err(LinkError::Expired));
+    }
+
+    #[test]
+    fn rejects_tampered_and_foreign_links() {
+        let key = LinkKey::generate();
+        let id = Uuid::new_v4();
+        let token = key.sign(id, "deny", 1_000);
+        let tampered = token.replacen(".deny.", ".allow.", 1);
+        assert_eq!(key.verify(&tampered, 0), Err(LinkError::Invalid));
+        let extended = token.replacen(".1000.", ".9000.", 1);
+        assert_eq!(key.verify(&extended, 0), Err(LinkError::Invalid));
+        assert_eq!(
+            LinkKey::generate().verify(&token, 0),
+            Err(LinkError::Invalid)
+        );
+        assert_eq!(key.verify("garbage", 0), Err(LinkError::Invalid));
+        assert_eq!(key.verify("a.b.c.zz", 0), Err(LinkError::Invalid));
+    }
+}
@@ -0,0 +1,214 @@
+use crate::channel::RequestView;
+use crate::telegram::formatter::{format_time_left, plain_tool_input, project_name};
+use std::fmt::Write;
+use tokio::time::Instant;
+use uuid::Uuid;
+
+/// Right-hand side of the Message-IDs the bot sends.
+const MESSAGE_ID_DOMAIN: &str = "vibe-reachout";
+
+/// A link in the email: what it does and where it goes.
+pub struct Link {
+    pub label: &'static str,
+    pub url: String,
+}
+
+pub fn subject(view: &RequestView, reminder: bool) -> String {
+    let subject = format!(
+        "[vibe-reachout] {} in {}: {}",
+        view.request.tool_name,
+        project_name(&view.request.cwd),
+        view.summary
+    );
+    if reminder {
+        format!("\u{23f0} Still waiting: {subject}")
+    } else {
+        subject
+    }
+}
+
+/// The request in full, then the links, as plain text.
+pub fn body(view: &RequestView, links: &[Link], link_ttl_minutes: u64, now: Instant) -> String {
+    let request = &view.request;
+    let mut body = format!(
+        "Claude Code wants to use {} in {}.\n\n{}\nSession {}\n",
+        request.tool_name,
+        request.cwd,
+        format_time_left(view.deadline.saturating_duration_since(now)),
+        request.session_id
+    );
+    if view.required_approvals > 1 {
+        let names: Vec<&str> = view.approvals.iter().map(|a| a.name.as_str()).collect();
+        let _ = writeln!(
+            body,
+            "{}/{} approvals{}",
+            names.len(),
+            view.required_approvals,
+            if names.is_empty() {
+                String::new()
+            } else {
+                format!(": {}", names.join(", "))
+            }
+        );
+    }
+    if let Some(context) = &request.assistant_context {
+        body.push('\n');
+        for line in context.lines() {
+            let _ = writeln!(body, "| {line}");
+        }
+    }
+    body.push('\n');
+    for line in plain_tool_input(request) {
+        let _ = writeln!(body, "    {line}");
+    }
+    body.push('\n');
+    let width = links
+        .iter()
+        .map(|link| link.label.len() + 1)
+        .max()
+        .unwrap_or(0);
+    for link in links {
+        let _ = writeln!(body, "{:width$}  {}", format!("{}:", link.label), link.url);
+    }
+    let _ = write!(
+        body,
+        "\nEach link works once, for {link_ttl_minutes} min. \
+         Or reply to this email with instructions instead.\n"
+    );
+    body
+}
+
+/// Message-ID of the email for a request, which replies point back to.
+pub fn message_id(request_id: Uuid) -> String {
+    format!("<request-{request_id}@{MESSAGE_ID_DOMAIN}>")
+}
+
+/// Message-ID of a reminder: unique, but still naming the request.
+pub fn reminder_message_id(request_id: Uuid) -> String {
+    format!(
+        "<request-{request_id}.{}@{MESSAGE_ID_DOMAIN}>",
+        Uuid::new_v4().simple()
+    )
+}
+
+/// The request an email replies to, from its `In-Reply-To` or, failing
+/// that, its `References`.
+pub fn replied_request(message: &mail_parser::Message) -> Option<Uuid> {
+    [message.in_reply_to(), message.references()]
+        .into_iter()
+        .filter_map(mail_parser::HeaderValue::as_text_list)
+        .flatten()
+        .find_map(|id| request_id_of(id))
+}
+
+fn request_id_of(message_id: &str) -> Option<Uuid> {
+    let id = message_id.trim_matches(['<', '>']);
+    let local = id.strip_suffix(MESSAGE_ID_DOMAIN)?.strip_suffix('@')?;
+    let request_id = local.strip_prefix("request-")?;
+    request_id.split('.').next()?.parse().ok()
+}
+
+/// What was written above the quoted original, without the signature.
+pub fn reply_text(body: &str) -> String {
+    let mut lines = Vec::new();
+    for line in body.lines() {
+        let trimmed = line.trim();
+        let is_attribution = trimmed.starts_with("On ") && trimmed.ends_with("wrote:");
+        if trimmed.starts_with('>')
+            || is_attribution
+            || line == "-- "
+            || trimmed.starts_with("-----Original Message-----")
+        {
+            break;
+        }
+        lines.push(line);
+    }
+    lines.join("\n").trim().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::IpcRequest;
+    use serde_json::json;
+
+    fn make_view() -> RequestView {
+        RequestView {
+            request: IpcRequest {
+                request_id: Uuid::new_v4(),
+                tool_name: "Bash".to_string(),
+                tool_input: json!({"command": "cargo test\ncargo build"}),
+                cwd: "/home/me/proj".to_string(),
+                session_id: "abcdef1234".to_string(),
+                permission_suggestions: vec![],
+                assistant_context: Some("Checking the build".to_string()),
+                first_prompt: None,
+            },
+            summary: "Bash: cargo test".to_string(),
+            deadline: Instant::now() + std::time::Duration::from_secs(150),
+            required_approvals: 1,
+            approvals: vec![],
+        }
+    }
+
+    #[test]
+    fn body_has_request_and_links() {
+        let view = make_view();
+        let links = [
+            Link {
+                label: "Allow",
+                url: "http://host/email/a".to_string(),
+            },
+            Link {
+                label: "Always allow",
+                url: "http://host/email/b".to_string(),
+            },
+        ];
+        let body = body(&view, &links, 60, Instant::now());
+        assert!(body.starts_with("Claude Code wants to use Bash in /home/me/proj.\n"));
+        assert!(body.contains("| Checking the build\n"));
+        assert!(body.contains("    cargo test\n    cargo build\n"));
+        assert!(body.contains("\nAllow:         http://host/email/a\n"));
+        assert!(body.contains("\nAlways allow:  http://host/email/b\n"));
+        assert!(body.contains("for 60 min"));
+        assert_eq!(
+            subject(&view, true),
+            "\u{23f0} Still waiting: [vibe-reachout] Bash in proj: Bash: cargo test"
+        );
+    }
+
+    #[test]
+    fn replies_find_their_request() {
+        let id = Uuid::new_v4();
+        let raw = format!(
+            "From: me@example.com\r\nIn-Reply-To: {}\r\nSubject: Re: x\r\n\r\nok\r\n",
+            reminder_message_id(id)
+        );
+        let message = mail_parser::MessageParser::default()
+            .parse(raw.as_bytes())
+            .unwrap();
+        assert_eq!(replied_request(&message), Some(id));
+
+        let raw = format!(
+            "From: me@example.com\r\nReferences: <other@example.com> {}\r\n\r\nok\r\n",
+            message_id(id)
+        );
+        let message = mail_parser::MessageParser::default()
+            .parse(raw.as_bytes())
+            .unwrap();
+        assert_eq!(replied_request(&message), Some(id));
+
+        assert_eq!(request_id_of("<request-nope@vibe-reachout>"), None);
+        assert_eq!(request_id_of(&format!("<request-{id}@example.com>")), None);
+    }
+
+    #[test]
+    fn reply_text_drops_quote_and_signature() {
+        let body = "Use the staging DB instead.\nThanks\n\n\
+                    On Mon, 1 Jan 2026 at 10:00, vibe-reachout <bot@example.com> wrote:\n\
+                    > Claude Code wants to use Bash\n";
+        assert_eq!(reply_text(body), "Use the staging DB instead.\nThanks");
+        assert_eq!(reply_text("No.\n-- \nMe\n"), "No.");
+        assert_eq!(reply_text("> only a quote\n"), "");
+    }
+}
@@ -0,0 +1,99 @@
+pub mod channel;
+pub mod link;
+pub mod message;
+
+use lettre::message::Mailbox;
+use serde::Deserialize;
+use std::net::SocketAddr;
+
+/// Emailing requests over SMTP, answered by signed links back to the bot or
+/// by replying to the email.
+#[derive(Debug, Deserialize, Clone)]
+pub struct EmailConfig {
+    pub smtp_host: String,
+    #[serde(default = "default_smtp_port")]
+    pub smtp_port: u16,
+    #[serde(default)]
+    pub smtp_security: SmtpSecurity,
+    pub smtp_username: Option<String>,
+    pub smtp_password: Option<String>,
+    /// Sender, e.g. `"vibe-reachout <bot@example.com>"`.
+    pub from: String,
+    /// Recipients; replies are only accepted from these addresses.
+    pub to: Vec<String>,
+    /// Base URL at which the links reach `listen` (e.g. a tailnet address).
+    pub callback_url: String,
+    /// Address of the link endpoint (`/email/{token}`) and inbound hook.
+    #[serde(default = "default_listen")]
+    pub listen: SocketAddr,
+    /// How long a link works after it is sent, if the request is still open.
+    #[serde(default = "default_link_ttl_seconds")]
+    pub link_ttl_seconds: u64,
+    /// Bearer token for `POST /email/inbound`, where the mail server delivers
+    /// replies as raw messages. Replies are ignored without it.
+    pub inbound_token: Option<String>,
+}
+
+/// How the SMTP connection is secured.
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SmtpSecurity {
+    /// Plain connection upgraded with STARTTLS (port 587).
+    #[default]
+    Starttls,
+    /// TLS from the start (port 465).
+    Tls,
+    /// Unencrypted, for a local relay or test sink only.
+    None,
+}
+
+const fn default_smtp_port() -> u16 {
+    587
+}
+
+fn default_listen() -> SocketAddr {
+    SocketAddr::from(([127, 0, 0, 1], 8794))
+}
+
+const fn default_link_ttl_seconds() -> u64 {
+    3600
+}
+
+impl EmailConfig {
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if self.from.parse::<Mailbox>().is_err() {
+            anyhow::bail!("email from is not a valid address: {}", self.from);
+        }
+        if self.to.is_empty() {
+            anyhow::bail!("email to must have at least one address");
+        }
+        if let Some(to) = self.to.iter().find(|to| to.parse::<Mailbox>().is_err()) {
+            anyhow::bail!("email to is not a valid address: {to}");
+        }
+        if self.smtp_username.is_some() != self.smtp_password.is_some() {
+            anyhow::bail!("email smtp_username and smtp_password must be set together");
+        }
+        if !(self.callback_url.starts_with("https://") || self.callback_url.starts_with("http://"))
+        {
+            anyhow::bail!("email callback_url must be an http(s) URL");
+        }
+        if self.link_ttl_seconds == 0 {
+            anyhow::bail!("email link_ttl_seconds must be greater than 0");
+        }
+        if let Some(token) = &self.inbound_token
+            && token.len() < 16
+        {
+            anyhow::bail!("email inbound_token must be at least 16 characters");
+        }
+        channel::transport(self)?;
+        Ok(())
+    }
+
+    /// Whether `address` is one of the recipients, ignoring case.
+    pub fn is_recipient(&self, address: &str) -> bool {
+        self.to.iter().any(|to| {
+            to.parse::<Mailbox>()
+                .is_ok_and(|mailbox| mailbox.email.to_string().eq_ignore_ascii_case(address))
+        })
+    }
+}
@@ -5,6 +5,7 @@ use tokio::io::AsyncReadExt;
 use uuid::Uuid;
 
 const MAX_ASSISTANT_CONTEXT_CHARS: usize = 500;
+const MAX_FIRST_PROMPT_CHARS: usize = 100;
 
 /// Maps an `IpcResponse` to the corresponding `HookOutput`.
 /// Returns `None` for `Decision::Timeout` (caller handles process exit).
@@ -51,6 +52,7 @@ pub async fn run_hook(config: &Config) -> anyhow::Result<()> {
     let hook_input: HookInput = serde_json::from_str(&input)?;
 
     let assistant_context = extract_last_assistant_text(&hook_input.transcript_path);
+    let first_prompt = extract_first_user_prompt(&hook_input.transcript_path);
 
     let request_id = Uuid::new_v4();
 
@@ -62,12 +64,14 @@ pub async fn run_hook(config: &Config) -> anyhow::Result<()> {
         session_id: hook_input.session_id,
         permission_suggestions: hook_input.permission_suggestions,
         assistant_context,
+        first_prompt,
     };
 
     let socket_path = config.effective_socket_path();
 
     // Send to bot and wait for response (timeout handled by bot side)
-    // Hook-side timeout is config.timeout_seconds + 30s buffer
+    // Hook-side timeout is config.timeout_seconds + 30s buffer, pushed back
+    // by keepalives when the request is extended in Telegram
     let ipc_timeout = config.timeout_seconds + 30;
     let response =
         crate::ipc::client::send_request(&socket_path, &ipc_request, ipc_timeout).await?;
@@ -139,6 +143,50 @@ fn extract_last_assistant_text(transcript_path: &str) -> Option<String> {
     None
 }
 
+/// Reads the transcript JSONL file and extracts the first user prompt.
+///
+/// Stops at the first `type == "user"` entry carrying text, either as a plain
+/// string or as text blocks. Entries that start with `<` are skipped: Claude
+/// Code records slash commands and injected context as tagged pseudo-prompts.
+fn extract_first_user_prompt(transcript_path: &str) -> Option<String> {
+    use std::io::BufRead;
+
+    let file = std::fs::File::open(transcript_path).ok()?;
+    for line in std::io::BufReader::new(file).lines() {
+        let Ok(line) = line else {
+            break;
+        };
+        let Ok(entry) = serde_json::from_str::<serde_json::Value>(line.trim()) else {
+            continue;
+        };
+        if entry.get("type").and_then(|t| t.as_str()) != Some("user") {
+            continue;
+        }
+
+        let Some(content) = entry.get("message").and_then(|m| m.get("content")) else {
+            continue;
+        };
+        let text = match content {
+            serde_json::Value::String(text) => Some(text.as_str()),
+            serde_json::Value::Array(blocks) => blocks
+                .iter()
+                .filter(|b| b.get("type").and_then(|t| t.as_str()) == Some("text"))
+                .find_map(|b| b.get("text").and_then(|t| t.as_str())),
+            _ => None,
+        };
+
+        if let Some(text) = text.map(str::trim)
+            && !text.is_empty()
+            && !text.starts_with('<')
+        {
+            let boundary = text.floor_char_boundary(MAX_FIRST_PROMPT_CHARS);
+            return Some(text[..boundary].to_string());
+        }
+    }
+
+    None
+}
+
 /// Truncates with a short `"..."` suffix (vs `formatter::truncate` which uses
 /// `"... (truncated)"`) since this text is displayed inline in the Telegram message.
 fn truncate_assistant_context(s: &str) -> String {
@@ -320,6 +368,52 @@ mod tests {
         assert!(result.ends_with("..."));
     }
 
+    #[test]
+    fn extract_first_user_prompt_from_transcript() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("transcript.jsonl");
+        let lines = [
+            r#"{"type":"user","message":{"content":"<command-name>/clear</command-name>"}}"#,
+            r#"{"type":"user","message":{"content":"  Fix the flaky test  "}}"#,
+            r#"{"type":"assistant","message":{"content":[{"type":"text","text":"On it."}]}}"#,
+            r#"{"type":"user","message":{"content":"second prompt"}}"#,
+        ];
+        std::fs::write(&path, lines.join("\n")).unwrap();
+        let result = extract_first_user_prompt(path.to_str().unwrap());
+        assert_eq!(result.as_deref(), Some("Fix the flaky test"));
+    }
+
+    #[test]
+    fn extract_first_user_prompt_from_text_blocks() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("transcript.jsonl");
+        let lines = [
+            r#"{"type":"user","message":{"content":[{"type":"tool_result","content":"ok"}]}}"#,
+            r#"{"type":"user","message":{"content":[{"type":"text","text":"Add a README"}]}}"#,
+        ];
+        std::fs::write(&path, lines.join("\n")).unwrap();
+        let result = extract_first_user_prompt(path.to_str().unwrap());
+        assert_eq!(result.as_deref(), Some("Add a README"));
+    }
+
+    #[test]
+    fn extract_first_user_prompt_truncates_long_text() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("transcript.jsonl");
+        let line = format!(
+            r#"{{"type":"user","message":{{"content":"{}"}}}}"#,
+            "p".repeat(300)
+        );
+        std::fs::write(&path, line).unwrap();
+        let result = extract_first_user_prompt(path.to_str().unwrap()).unwrap();
+        assert_eq!(result.len(), MAX_FIRST_PROMPT_CHARS);
+    }
+
+    #[test]
+    fn extract_first_user_prompt_returns_none_for_missing_file() {
+        assert!(extract_first_user_prompt("/nonexistent/path/transcript.jsonl").is_none());
+    }
+
     #[test]
     fn truncate_assistant_context_short_text() {
         let result = truncate_assistant_context("short text");
@@ -0,0 +1,217 @@
+use bytes::Bytes;
+use futures::future::BoxFuture;
+use http_body_util::combinators::UnsyncBoxBody;
+use http_body_util::{BodyExt, Full, Limited, StreamBody};
+use hyper::body::Frame;
+use hyper::body::Incoming;
+use hyper::server::conn::http1;
+use hyper::service::service_fn;
+use hyper::{Request, Response, StatusCode};
+use hyper_util::rt::TokioIo;
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::net::TcpListener;
+use tokio_util::sync::CancellationToken;
+
+/// Largest request body accepted.
+const MAX_BODY_BYTES: usize = 1024 * 1024;
+
+/// A response body: all at once, or streamed.
+pub type Body = UnsyncBoxBody<Bytes, Infallible>;
+
+/// Answers one HTTP request whose body has been read in full.
+pub type Handler = Arc<dyn Fn(Request<Bytes>) -> BoxFuture<'static, Response<Body>> + Send + Sync>;
+
+/// Serves HTTP/1 on `addr` with `handler` until `cancel_token` fires.
+pub async fn serve(
+    addr: SocketAddr,
+    handler: Handler,
+    cancel_token: CancellationToken,
+) -> anyhow::Result<()> {
+    let listener = TcpListener::bind(addr).await?;
+    tracing::info!("HTTP endpoint listening on {}", listener.local_addr()?);
+    serve_listener(listener, handler, cancel_token).await;
+    Ok(())
+}
+
+/// Like [`serve`], on a listener that is already bound.
+pub async fn serve_listener(
+    listener: TcpListener,
+    handler: Handler,
+    cancel_token: CancellationToken,
+) {
+    loop {
+        let stream = tokio::select! {
+            () = cancel_token.cancelled() => return,
+            accepted = listener.accept() => match accepted {
+                Ok((stream, _)) => stream,
+                Err(e) => {
+                    tracing::warn!("Failed to accept HTTP connection: {e}");
+                    continue;
+                }
+            },
+        };
+        let handler = handler.clone();
+        tokio::spawn(async move {
+            let service = service_fn(move |request: Request<Incoming>| {
+                let handler = handler.clone();
+                async move { Ok::<_, Infallible>(handle(request, &handler).await) }
+            });
+            if let Err(e) = http1::Builder::new()
+                .serve_connection(TokioIo::new(stream), service)
+                .await
+            {
+                tracing::debug!("HTTP connection error: {e}");
+            }
+        });
+    }
+}
+
+async fn handle(request: Request<Incoming>, handler: &Handler) -> Response<Body> {
+    let (parts, body) = request.into_parts();
+    match Limited::new(body, MAX_BODY_BYTES).collect().await {
+        Ok(body) => handler(Request::from_parts(parts, body.to_bytes())).await,
+        Err(_) => text_response(StatusCode::PAYLOAD_TOO_LARGE, "Request body too large"),
+    }
+}
+
+/// A plain-text response.
+pub fn text_response(status: StatusCode, text: &str) -> Response<Body> {
+    let mut response = Response::new(full(text.to_string()));
+    *response.status_mut() = status;
+    response
+}
+
+/// A `200 OK` JSON response.
+pub fn json_response(value: &serde_json::Value) -> Response<Body> {
+    let mut response = Response::new(full(value.to_string()));
+    response.headers_mut().insert(
+        hyper::header::CONTENT_TYPE,
+        hyper::header::HeaderValue::from_static("application/json"),
+    );
+    response
+}
+
+/// A `200 OK` HTML page.
+pub fn html_response(page: String) -> Response<Body> {
+    let mut response = Response::new(full(page));
+    response.headers_mut().insert(
+        hyper::header::CONTENT_TYPE,
+        hyper::header::HeaderValue::from_static("text/html; charset=utf-8"),
+    );
+    response
+}
+
+/// A body sent in one piece.
+pub fn full(body: impl Into<Bytes>) -> Body {
+    Full::new(body.into()).boxed_unsync()
+}
+
+/// A body sent chunk by chunk as `chunks` yields them.
+pub fn streamed(chunks: impl futures::Stream<Item = Bytes> + Send + 'static) -> Body {
+    use futures::StreamExt;
+    StreamBody::new(chunks.map(|chunk| Ok::<_, Infallible>(Frame::data(chunk)))).boxed_unsync()
+}
+
+/// A random token for authorizing requests to an endpoint (32 hex digits).
+pub fn new_token() -> String {
+    let mut bytes = [0u8; 16];
+    ring::rand::SecureRandom::fill(&ring::rand::SystemRandom::new(), &mut bytes)
+        .expect("system random number generator failed");
+    hex::encode(bytes)
+}
+
+/// Compares a presented token with the expected one in constant time.
+pub fn tokens_match(presented: &str, expected: &str) -> bool {
+    presented.len() == expected.len()
+        && presented
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+/// The token of an `Authorization: Bearer <token>` header.
+pub fn bearer_token<B>(request: &Request<B>) -> Option<&str> {
+    request
+        .headers()
+        .get(hyper::header::AUTHORIZATION)?
+        .to_str()
+        .ok()?
+        .strip_prefix("Bearer ")
+}
+
+/// Fake HTTP backends for channel tests.
+#[cfg(test)]
+pub mod test_support {
+    use super::{Body, Handler};
+    use bytes::Bytes;
+    use hyper::{Request, Response};
+    use serde_json::Value;
+    use std::sync::Arc;
+    use tokio::sync::mpsc;
+    use tokio_util::sync::CancellationToken;
+
+    /// Serves HTTP on a free local port, reporting each request as `record`
+    /// describes it (or not at all, for `None`) and answering it with
+    /// `respond`. Returns the server's `http://host:port` URL.
+    pub async fn recording_server<T, R>(
+        record: impl Fn(&Request<Bytes>) -> Option<T> + Send + Sync + 'static,
+        respond: impl Fn(&Request<Bytes>) -> R + Send + Sync + 'static,
+    ) -> (String, mpsc::UnboundedReceiver<T>)
+    where
+        T: Send + 'static,
+        R: Future<Output = Response<Body>> + Send + 'static,
+    {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let (tx, rx) = mpsc::unbounded_channel();
+        let handler: Handler = Arc::new(move |request: Request<Bytes>| {
+            if let Some(call) = record(&request) {
+                let _ = tx.send(call);
+            }
+            Box::pin(respond(&request))
+        });
+        tokio::spawn(super::serve_listener(
+            listener,
+            handler,
+            CancellationToken::new(),
+        ));
+        (url, rx)
+    }
+
+    /// The request's JSON body, or `null` if it has none.
+    pub fn json_body(request: &Request<Bytes>) -> Value {
+        serde_json::from_slice(request.body()).unwrap_or(Value::Null)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokens_are_random_and_compared_exactly() {
+        let token = new_token();
+        assert_eq!(token.len(), 32);
+        assert_ne!(token, new_token());
+        assert!(tokens_match(&token, &token.clone()));
+        assert!(!tokens_match(&token[..31], &token));
+        assert!(!tokens_match(&"0".repeat(32), &token));
+    }
+
+    #[test]
+    fn bearer_token_is_read_from_authorization() {
+        let request = Request::post("/")
+            .header("authorization", "Bearer abc")
+            .body(())
+            .unwrap();
+        assert_eq!(bearer_token(&request), Some("abc"));
+        let request = Request::post("/")
+            .header("authorization", "Basic abc")
+            .body(())
+            .unwrap();
+        assert_eq!(bearer_token(&request), None);
+    }
+}
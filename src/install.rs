@@ -1,6 +1,10 @@
 use crate::error::InstallError;
 use std::path::{Path, PathBuf};
 
+/// Claude Code's hard limit for the hook: the longest possible wait
+/// (including "+5 min" extensions) plus the hook's 30s buffer and some slack.
+const HOOK_TIMEOUT_SECONDS: u64 = crate::config::MAX_TIMEOUT_SECONDS + 60;
+
 pub fn run_install() -> anyhow::Result<()> {
     let settings_path = settings_file_path()?;
     install_hook(&settings_path)
@@ -31,7 +35,7 @@ pub fn install_hook(settings_path: &Path) -> anyhow::Result<()> {
     let hook_entry = serde_json::json!({
         "type": "command",
         "command": "vibe-reachout",
-        "timeout": 600
+        "timeout": HOOK_TIMEOUT_SECONDS
     });
 
     let matcher_entry = serde_json::json!({
@@ -109,7 +113,7 @@ mod tests {
         let inner_hook = &arr[0]["hooks"][0];
         assert_eq!(inner_hook["command"], "vibe-reachout");
         assert_eq!(inner_hook["type"], "command");
-        assert_eq!(inner_hook["timeout"], 600);
+        assert_eq!(inner_hook["timeout"], 3660);
     }
 
     #[test]
@@ -1,9 +1,9 @@
 use crate::error::HookError;
-use crate::models::{IpcRequest, IpcResponse};
+use crate::models::{IpcRequest, IpcResponse, IpcServerMessage};
 use std::path::Path;
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
 use tokio::net::UnixStream;
-use tokio::time::{Duration, timeout};
+use tokio::time::{Duration, Instant, timeout_at};
 
 pub async fn send_request(
     socket_path: &Path,
@@ -35,25 +35,29 @@ pub async fn send_request(
         .await
         .map_err(HookError::ConnectionFailed)?;
 
-    // Read NDJSON response with timeout
+    // Read NDJSON lines until the response; keepalives push the timeout back
     let mut buf_reader = BufReader::new(reader);
-    let mut line = String::new();
-
-    let read_result = timeout(
-        Duration::from_secs(timeout_secs),
-        buf_reader.read_line(&mut line),
-    )
-    .await
-    .map_err(|_| HookError::Timeout(timeout_secs))?;
-
-    read_result.map_err(HookError::ConnectionFailed)?;
-
-    if line.trim().is_empty() {
-        return Err(HookError::InvalidResponse(
-            "Empty response from bot".to_string(),
-        ));
+    let mut deadline = Instant::now() + Duration::from_secs(timeout_secs);
+
+    loop {
+        let mut line = String::new();
+        let read_result = timeout_at(deadline, buf_reader.read_line(&mut line))
+            .await
+            .map_err(|_| HookError::Timeout(timeout_secs))?;
+
+        read_result.map_err(HookError::ConnectionFailed)?;
+
+        if line.trim().is_empty() {
+            return Err(HookError::InvalidResponse(
+                "Empty response from bot".to_string(),
+            ));
+        }
+
+        match serde_json::from_str(line.trim())? {
+            IpcServerMessage::Response(response) => return Ok(response),
+            IpcServerMessage::Keepalive(keepalive) => {
+                deadline += Duration::from_secs(keepalive.extend_seconds);
+            }
+        }
     }
-
-    let response: IpcResponse = serde_json::from_str(line.trim())?;
-    Ok(response)
 }
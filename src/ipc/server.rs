@@ -1,20 +1,50 @@
 use crate::error::BotError;
-use crate::models::{IpcRequest, IpcResponse, PendingRequest};
+use crate::models::{IpcKeepalive, IpcRequest, IpcResponse, PendingRequest};
 use dashmap::DashMap;
 use std::path::Path;
 use std::sync::Arc;
-use teloxide::prelude::*;
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
 use tokio::net::UnixListener;
-use tokio::sync::{Semaphore, oneshot};
-use tokio::time::Instant;
+use tokio::net::unix::OwnedWriteHalf;
+use tokio::sync::{Semaphore, oneshot, watch};
+use tokio::time::{Duration, Instant};
 use tokio_util::sync::CancellationToken;
 use uuid::Uuid;
 
-use crate::config::Config;
+use crate::activity::ActivityState;
+use crate::burst::{Arrival, BufferedRequest, BurstState};
+use crate::channel::{Channels, Page, StatusUpdate};
+use crate::config::{Config, EscalationTier, MAX_TIMEOUT_SECONDS};
+use crate::mute::{MUTED_MESSAGE, MuteOutcome, MuteState};
+use crate::pause::PauseState;
+use crate::quiet::{Delivery, QuietState};
+use crate::rota::OnCallState;
+use chrono::Utc;
 
 pub type PendingMap = Arc<DashMap<Uuid, PendingRequest>>;
 
+/// How often the remaining-time indicator on open requests is refreshed.
+const COUNTDOWN_INTERVAL: Duration = Duration::from_mins(1);
+/// How often requests held for a paused session are kept alive.
+const HOLD_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Shared handles cloned into every connection and channel update handler.
+#[derive(Clone)]
+pub struct ServerContext {
+    pub config: Arc<Config>,
+    pub pending_map: PendingMap,
+    /// Where requests are posted and decisions come from.
+    pub channels: Channels,
+    pub on_call: OnCallState,
+    pub activity: ActivityState,
+    pub quiet: QuietState,
+    pub mutes: MuteState,
+    pub pauses: PauseState,
+    pub bursts: BurstState,
+    /// When the bot started, for `/status`.
+    pub started_at: Instant,
+}
+
 pub fn detect_and_clean_stale_socket(socket_path: &Path) -> Result<(), BotError> {
     if !socket_path.exists() {
         return Ok(());
@@ -47,13 +77,15 @@ pub fn detect_and_clean_stale_socket(socket_path: &Path) -> Result<(), BotError>
 pub async fn run_server(
     socket_path: &Path,
     cancel_token: CancellationToken,
-    bot: Bot,
-    config: Arc<Config>,
-    pending_map: PendingMap,
+    ctx: ServerContext,
 ) -> Result<(), BotError> {
     let listener = UnixListener::bind(socket_path).map_err(BotError::SocketBind)?;
     let semaphore = Arc::new(Semaphore::new(50));
     tracing::info!("Socket server listening on {}", socket_path.display());
+    tokio::spawn(crate::channel::run_decisions(
+        ctx.clone(),
+        cancel_token.clone(),
+    ));
 
     loop {
         tokio::select! {
@@ -68,13 +100,11 @@ pub async fn run_server(
                             tracing::warn!("Max concurrent connections reached, dropping connection");
                             continue;
                         };
-                        let bot = bot.clone();
-                        let config = config.clone();
-                        let pending_map = pending_map.clone();
+                        let ctx = ctx.clone();
                         let cancel = cancel_token.clone();
                         tokio::spawn(async move {
                             let _permit = permit;
-                            if let Err(e) = handle_connection(stream, bot, config, pending_map, cancel).await {
+                            if let Err(e) = handle_connection(stream, ctx, cancel).await {
                                 tracing::error!("Connection handler error: {e}");
                             }
                         });
@@ -97,9 +127,7 @@ pub async fn run_server(
 
 async fn handle_connection(
     stream: tokio::net::UnixStream,
-    bot: Bot,
-    config: Arc<Config>,
-    pending_map: PendingMap,
+    ctx: ServerContext,
     cancel_token: CancellationToken,
 ) -> anyhow::Result<()> {
     let (reader, mut writer) = stream.into_split();
@@ -119,66 +147,364 @@ async fn handle_connection(
         "Received permission request"
     );
 
-    let (tx, rx) = oneshot::channel::<IpcResponse>();
+    let response = process_request(&ctx, &ipc_request, &mut writer, &cancel_token).await?;
 
+    // Write NDJSON response back
+    let mut json = serde_json::to_string(&response)?;
+    json.push('\n');
+    writer.write_all(json.as_bytes()).await?;
+
+    Ok(())
+}
+
+/// Posts the request to the first notification tier and waits for a decision.
+async fn process_request(
+    ctx: &ServerContext,
+    ipc_request: &IpcRequest,
+    writer: &mut OwnedWriteHalf,
+    cancel_token: &CancellationToken,
+) -> anyhow::Result<IpcResponse> {
     let request_id = ipc_request.request_id;
+    ctx.activity.touch_session(ipc_request);
+    let tiers = crate::routing::notification_tiers(&ctx.config, &ctx.on_call, ipc_request).await;
+
+    // Muted sessions and quiet hours may answer before anyone is notified
+    let deliveries = match early_response(ctx, ipc_request, &tiers[0].chat_ids) {
+        Ok(deliveries) => deliveries,
+        Err(response) => {
+            ctx.activity.record_outcome(&response.decision);
+            return Ok(response);
+        }
+    };
 
-    // Send to Telegram and store pending request
-    let sent_messages =
-        crate::bot::send_permission_to_telegram(&bot, &config, &ipc_request).await?;
+    let (tx, rx) = oneshot::channel::<IpcResponse>();
+    let required_approvals = crate::quorum::required_approvals(&ctx.config.quorum, ipc_request);
+    let now = Instant::now();
+    let (deadline_tx, deadline_rx) =
+        watch::channel(now + Duration::from_secs(ctx.config.timeout_seconds));
+    let pending = PendingRequest {
+        sender: tx,
+        request: ipc_request.clone(),
+        summary: crate::telegram::formatter::format_summary(ipc_request),
+        posted: false,
+        chat_ids: tiers
+            .iter()
+            .flat_map(|tier| tier.chat_ids.iter().copied())
+            .collect(),
+        created_at: now,
+        deadline_tx,
+        required_approvals,
+        approvals: Vec::new(),
+    };
 
-    let original_text = crate::telegram::formatter::format_permission_message(&ipc_request);
+    // Store the pending request and post it to the first tier (unless
+    // queued or part of a burst)
+    ctx.pending_map.insert(request_id, pending);
+    let arrival =
+        (!deliveries.is_empty()).then(|| ctx.bursts.arrive(ipc_request, &deliveries, now));
+    if arrival == Some(Arrival::Immediate) {
+        let page = Page {
+            tier: 0,
+            deliveries: &deliveries,
+        };
+        if let Err(e) = post_request(ctx, request_id, page).await {
+            ctx.pending_map.remove(&request_id);
+            return Err(e);
+        }
+    }
+    if arrival == Some(Arrival::Buffered { flush: true }) {
+        flush_burst(ctx, &ipc_request.session_id, cancel_token).await;
+    }
 
-    pending_map.insert(
-        request_id,
-        PendingRequest {
-            request_id,
-            sender: tx,
-            sent_messages,
-            original_text,
-            permission_suggestions: ipc_request.permission_suggestions,
-            created_at: Instant::now(),
-        },
-    );
+    let channels = (rx, deadline_rx);
+    Ok(wait_for_decision(ctx, ipc_request, &tiers, channels, writer, cancel_token).await)
+}
 
-    // Wait for response with timeout
-    let timeout_duration = std::time::Duration::from_secs(config.timeout_seconds);
-    let response = tokio::select! {
-        () = cancel_token.cancelled() => {
-            pending_map.remove(&request_id);
-            IpcResponse::timeout(request_id)
+/// Decides how a request is delivered to `chat_ids`, or answers it straight
+/// away for muted sessions and chats in deny-mode quiet hours. An empty
+/// delivery list queues (or, for paused sessions, holds) the request
+/// without notifying anyone.
+fn early_response(
+    ctx: &ServerContext,
+    ipc_request: &IpcRequest,
+    chat_ids: &[i64],
+) -> Result<Vec<Delivery>, IpcResponse> {
+    let request_id = ipc_request.request_id;
+    let now = Utc::now();
+    match ctx
+        .mutes
+        .get(&ipc_request.session_id, now)
+        .map(|m| m.outcome)
+    {
+        Some(MuteOutcome::Terminal) => {
+            tracing::info!(request_id = %request_id, "Muted session, falling back to terminal");
+            Err(IpcResponse::timeout(request_id))
         }
-        result = tokio::time::timeout(timeout_duration, rx) => {
-            match result {
-                Ok(Ok(response)) => response,
-                Ok(Err(_)) => {
-                    // Sender dropped (shouldn't happen normally)
-                    pending_map.remove(&request_id);
-                    IpcResponse::timeout(request_id)
-                }
-                Err(_) => {
-                    // Timeout
-                    tracing::warn!(request_id = %request_id, "Request timed out");
-                    if let Some((_, pending)) = pending_map.remove(&request_id) {
-                        crate::bot::edit_messages_status(&bot, &pending.sent_messages, &pending.original_text, "\u{23f1}\u{fe0f} Timed out").await;
-                    }
-                    IpcResponse::timeout(request_id)
+        Some(MuteOutcome::Deny) => {
+            tracing::info!(request_id = %request_id, "Muted session, denying request");
+            Err(IpcResponse::deny(request_id, MUTED_MESSAGE.to_string()))
+        }
+        Some(MuteOutcome::Queue) => {
+            tracing::info!(request_id = %request_id, "Muted session, queueing request");
+            Ok(Vec::new())
+        }
+        None if ctx.pauses.is_paused(&ipc_request.session_id) => {
+            tracing::info!(request_id = %request_id, "Session paused, holding request");
+            Ok(Vec::new())
+        }
+        None => ctx.quiet.deliveries(chat_ids, now).map_err(|message| {
+            tracing::info!(request_id = %request_id, "Denying request during quiet hours");
+            IpcResponse::deny(request_id, message)
+        }),
+    }
+}
+
+/// Waits for the decision on a posted request, paging later tiers while it
+/// is unanswered, refreshing the countdown, sending the reminder and telling
+/// the hook about deadline extensions.
+async fn wait_for_decision(
+    ctx: &ServerContext,
+    ipc_request: &IpcRequest,
+    tiers: &[EscalationTier],
+    (rx, mut deadline_rx): (oneshot::Receiver<IpcResponse>, watch::Receiver<Instant>),
+    writer: &mut OwnedWriteHalf,
+    cancel_token: &CancellationToken,
+) -> IpcResponse {
+    let ServerContext {
+        config,
+        pending_map,
+        ..
+    } = ctx;
+    let request_id = ipc_request.request_id;
+    let now = Instant::now();
+    let mut deadline = *deadline_rx.borrow_and_update();
+    let mut next_tier = 1;
+    let mut escalate_at = now + Duration::from_secs(tiers[0].escalate_after_seconds);
+    let mut next_countdown = now + COUNTDOWN_INTERVAL;
+    let mut next_hold = now;
+    let remind_before = Duration::from_secs(config.reminder_before_seconds);
+    let mut remind_at = deadline.checked_sub(remind_before).unwrap_or(now);
+    let mut reminder_due = !remind_before.is_zero() && remind_at > now;
+    tokio::pin!(rx);
+
+    loop {
+        tokio::select! {
+            () = cancel_token.cancelled() => {
+                pending_map.remove(&request_id);
+                break IpcResponse::timeout(request_id);
+            }
+            result = &mut rx => {
+                if let Ok(response) = result {
+                    break response;
                 }
+                // Sender dropped (shouldn't happen normally)
+                pending_map.remove(&request_id);
+                break IpcResponse::timeout(request_id);
+            }
+            () = tokio::time::sleep_until(deadline) => {
+                tracing::warn!(request_id = %request_id, "Request timed out");
+                let timeout = IpcResponse::timeout(request_id);
+                crate::channel::resolve(ctx, request_id, timeout, "\u{23f1}\u{fe0f} Timed out").await;
+                break IpcResponse::timeout(request_id);
+            }
+            () = tokio::time::sleep_until(escalate_at), if next_tier < tiers.len() => {
+                let tier = &tiers[next_tier];
+                escalate(ctx, ipc_request, next_tier, &tier.chat_ids).await;
+                escalate_at += Duration::from_secs(tier.escalate_after_seconds);
+                next_tier += 1;
+            }
+            () = tokio::time::sleep_until(next_countdown) => {
+                crate::channel::update_open(ctx, request_id, &StatusUpdate::Progress).await;
+                next_countdown += COUNTDOWN_INTERVAL;
+            }
+            () = tokio::time::sleep_until(next_hold) => {
+                hold_while_paused(ctx, request_id, Instant::now());
+                next_hold += HOLD_INTERVAL;
+            }
+            Ok(()) = deadline_rx.changed() => {
+                let extended = *deadline_rx.borrow_and_update();
+                send_keepalive(writer, request_id, extended.saturating_duration_since(deadline)).await;
+                deadline = extended;
+                // Re-arm the reminder for the new deadline
+                crate::channel::update_open(ctx, request_id, &StatusUpdate::Extended).await;
+                remind_at = deadline.checked_sub(remind_before).unwrap_or(deadline);
+                reminder_due = !remind_before.is_zero() && remind_at > Instant::now();
+            }
+            () = tokio::time::sleep_until(remind_at), if reminder_due => {
+                reminder_due = false;
+                crate::channel::update_open(ctx, request_id, &StatusUpdate::Reminder).await;
             }
         }
+    }
+}
+
+/// Keeps a request held for a paused session from timing out, by pushing
+/// its deadline to a full `timeout_seconds` away. The hook hears about it
+/// through the usual keepalives. Claude Code gives up on the hook after the
+/// overall one-hour limit, so a request held that long times out anyway.
+fn hold_while_paused(ctx: &ServerContext, request_id: Uuid, now: Instant) {
+    let Some(pending) = ctx.pending_map.get(&request_id) else {
+        return;
     };
+    if pending.posted || !ctx.pauses.is_paused(&pending.request.session_id) {
+        return;
+    }
+    let target = now + Duration::from_secs(ctx.config.timeout_seconds) + HOLD_INTERVAL;
+    let by = target.saturating_duration_since(pending.deadline());
+    if !by.is_zero() {
+        pending.extend_deadline(by, Duration::from_secs(MAX_TIMEOUT_SECONDS));
+    }
+}
 
-    // Write NDJSON response back
-    let mut json = serde_json::to_string(&response)?;
-    json.push('\n');
-    writer.write_all(json.as_bytes()).await?;
+/// Tells the hook its request was extended so it doesn't give up first.
+async fn send_keepalive(writer: &mut OwnedWriteHalf, request_id: Uuid, extension: Duration) {
+    let keepalive = IpcKeepalive {
+        request_id,
+        extend_seconds: extension.as_secs(),
+    };
+    let result = match serde_json::to_string(&keepalive) {
+        Ok(mut json) => {
+            json.push('\n');
+            writer
+                .write_all(json.as_bytes())
+                .await
+                .map_err(anyhow::Error::from)
+        }
+        Err(e) => Err(e.into()),
+    };
+    if let Err(e) = result {
+        tracing::warn!(request_id = %request_id, "Failed to send keepalive: {e}");
+    }
+}
 
+/// Pages the next escalation tier for an unanswered request.
+async fn escalate(ctx: &ServerContext, request: &IpcRequest, tier_index: usize, chat_ids: &[i64]) {
+    let ServerContext {
+        pending_map,
+        quiet,
+        mutes,
+        pauses,
+        ..
+    } = ctx;
+    let request_id = request.request_id;
+    let held = pauses.is_paused(&request.session_id)
+        && pending_map
+            .get(&request_id)
+            .is_some_and(|pending| !pending.posted);
+    if held || mutes.get(&request.session_id, Utc::now()).is_some() {
+        tracing::info!(request_id = %request_id, tier = tier_index, "Not escalating muted or held request");
+        return;
+    }
+    let Ok(deliveries) = quiet.deliveries(chat_ids, Utc::now()) else {
+        tracing::info!(request_id = %request_id, tier = tier_index, "Skipping quiet escalation tier");
+        return;
+    };
+    tracing::info!(
+        request_id = %request_id,
+        tier = tier_index,
+        "Escalating unanswered request"
+    );
+    let page = Page {
+        tier: tier_index,
+        deliveries: &deliveries,
+    };
+    if let Err(e) = post_request(ctx, request_id, page).await {
+        tracing::warn!(request_id = %request_id, tier = tier_index, "Escalation failed: {e}");
+    }
+}
+
+/// Posts an open request to every channel. Fails only if no channel
+/// took it.
+async fn post_request(ctx: &ServerContext, request_id: Uuid, page: Page<'_>) -> anyhow::Result<()> {
+    let Some(view) = ctx.pending_map.get(&request_id).map(|p| p.view()) else {
+        return Ok(());
+    };
+    let mut result = Ok(());
+    let mut posted = false;
+    let channels = ctx
+        .channels
+        .iter()
+        .filter(|channel| page.tier == 0 || channel.pages_escalation_tiers());
+    for channel in channels {
+        match channel.send_request(&view, page).await {
+            Ok(()) => posted = true,
+            Err(e) => {
+                tracing::warn!(request_id = %request_id, channel = channel.name(), "Failed to post request: {e}");
+                result = Err(e);
+            }
+        }
+    }
+    if !posted {
+        return result;
+    }
+    if let Some(mut pending) = ctx.pending_map.get_mut(&request_id) {
+        pending.posted = true;
+    }
     Ok(())
 }
 
+/// Waits until a session's burst is over, then posts it: as one batch when
+/// several requests are still open, or on its own.
+async fn flush_burst(ctx: &ServerContext, session_id: &str, cancel_token: &CancellationToken) {
+    while let Some(flush_at) = ctx.bursts.flush_at(session_id)
+        && Instant::now() < flush_at
+    {
+        tokio::select! {
+            () = cancel_token.cancelled() => return,
+            () = tokio::time::sleep_until(flush_at) => {}
+        }
+    }
+
+    let buffered: Vec<BufferedRequest> = ctx.bursts.take(session_id);
+    let Some(deliveries) = buffered.first().map(|b| b.deliveries.clone()) else {
+        return;
+    };
+    let page = Page {
+        tier: 0,
+        deliveries: &deliveries,
+    };
+    let request_ids: Vec<Uuid> = buffered.iter().map(|b| b.request.request_id).collect();
+    post_together(ctx, &request_ids, page).await;
+}
+
+/// Posts open requests from one session to every channel: as one batch
+/// when there are several, or on its own.
+pub async fn post_together(ctx: &ServerContext, request_ids: &[Uuid], page: Page<'_>) {
+    // Requests answered meanwhile (e.g. from /pending) are left out
+    let views: Vec<_> = request_ids
+        .iter()
+        .filter_map(|id| ctx.pending_map.get(id).map(|p| p.view()))
+        .collect();
+    if let [single] = views.as_slice() {
+        if let Err(e) = post_request(ctx, single.request_id(), page).await {
+            tracing::warn!(request_id = %single.request_id(), "Failed to send request: {e}");
+        }
+        return;
+    }
+    if views.is_empty() {
+        return;
+    }
+
+    tracing::info!(count = views.len(), "Sending requests as one message");
+    for channel in ctx.channels.iter() {
+        if let Err(e) = channel.send_batch(&views, page).await {
+            tracing::warn!(channel = channel.name(), "Failed to send burst: {e}");
+        }
+    }
+    for view in &views {
+        if let Some(mut pending) = ctx.pending_map.get_mut(&view.request_id()) {
+            pending.posted = true;
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::channel::memory::MemoryChannel;
+    use crate::channel::{ApprovalChannel, Verdict};
+    use crate::models::Decision;
+    use crate::rota::OnCall;
 
     #[test]
     fn no_socket_returns_ok() {
@@ -216,23 +542,57 @@ mod tests {
             telegram_bot_token: "fake-token".to_string(),
             allowed_chat_ids: std::collections::HashSet::from([12345]),
             timeout_seconds: 5,
+            reminder_before_seconds: 60,
             socket_path: None,
+            escalation: vec![],
+            rota: None,
+            routes: vec![],
+            quorum: vec![],
+            forum_topics: false,
+            dashboard: false,
+            tui: false,
+            quiet_hours: vec![],
+            mute_outcome: crate::mute::MuteOutcome::Terminal,
+            coalesce_window_ms: 0,
+            reactions: crate::telegram::reaction::default_reactions(),
+            slack: None,
+            discord: None,
+            matrix: None,
+            ntfy: None,
+            web: None,
+            email: None,
+            webhook: None,
+            signal: None,
         }
     }
 
+    fn make_test_context() -> (ServerContext, Arc<MemoryChannel>) {
+        let channel = Arc::new(MemoryChannel::default());
+        let ctx = ServerContext {
+            config: Arc::new(make_test_config()),
+            pending_map: Arc::new(DashMap::new()),
+            channels: Arc::new(vec![channel.clone() as Arc<dyn ApprovalChannel>]),
+            on_call: Arc::new(OnCall::new(None)),
+            activity: Arc::default(),
+            quiet: Arc::new(crate::quiet::Quiet::new(vec![])),
+            mutes: Arc::default(),
+            pauses: Arc::default(),
+            bursts: Arc::new(crate::burst::Bursts::new(Duration::ZERO)),
+            started_at: Instant::now(),
+        };
+        (ctx, channel)
+    }
+
     #[tokio::test]
     async fn run_server_stops_on_cancel() {
         let tmp = tempfile::tempdir().unwrap();
         let sock = tmp.path().join("test.sock");
         let cancel = CancellationToken::new();
-        let bot = Bot::new("fake-token");
-        let config = Arc::new(make_test_config());
-        let pending: PendingMap = Arc::new(DashMap::new());
+        let (ctx, _) = make_test_context();
 
         let cancel2 = cancel.clone();
         let sock2 = sock.clone();
-        let handle =
-            tokio::spawn(async move { run_server(&sock2, cancel2, bot, config, pending).await });
+        let handle = tokio::spawn(async move { run_server(&sock2, cancel2, ctx).await });
 
         tokio::time::sleep(std::time::Duration::from_millis(100)).await;
         assert!(sock.exists());
@@ -250,14 +610,11 @@ mod tests {
         let tmp = tempfile::tempdir().unwrap();
         let sock = tmp.path().join("empty.sock");
         let cancel = CancellationToken::new();
-        let bot = Bot::new("fake-token");
-        let config = Arc::new(make_test_config());
-        let pending: PendingMap = Arc::new(DashMap::new());
+        let (ctx, _) = make_test_context();
 
         let cancel2 = cancel.clone();
         let sock2 = sock.clone();
-        let handle =
-            tokio::spawn(async move { run_server(&sock2, cancel2, bot, config, pending).await });
+        let handle = tokio::spawn(async move { run_server(&sock2, cancel2, ctx).await });
 
         tokio::time::sleep(std::time::Duration::from_millis(100)).await;
 
@@ -280,14 +637,11 @@ mod tests {
         let tmp = tempfile::tempdir().unwrap();
         let sock = tmp.path().join("invalid.sock");
         let cancel = CancellationToken::new();
-        let bot = Bot::new("fake-token");
-        let config = Arc::new(make_test_config());
-        let pending: PendingMap = Arc::new(DashMap::new());
+        let (ctx, _) = make_test_context();
 
         let cancel2 = cancel.clone();
         let sock2 = sock.clone();
-        let handle =
-            tokio::spawn(async move { run_server(&sock2, cancel2, bot, config, pending).await });
+        let handle = tokio::spawn(async move { run_server(&sock2, cancel2, ctx).await });
 
         tokio::time::sleep(std::time::Duration::from_millis(100)).await;
 
@@ -303,24 +657,20 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn server_handles_valid_request_with_fake_bot() {
-        use tokio::io::AsyncWriteExt;
+    async fn server_answers_with_channel_decision() {
+        use tokio::io::{AsyncBufReadExt, AsyncWriteExt};
 
         let tmp = tempfile::tempdir().unwrap();
         let sock = tmp.path().join("valid.sock");
         let cancel = CancellationToken::new();
-        let bot = Bot::new("fake-token");
-        let config = Arc::new(make_test_config());
-        let pending: PendingMap = Arc::new(DashMap::new());
+        let (ctx, channel) = make_test_context();
 
         let cancel2 = cancel.clone();
         let sock2 = sock.clone();
-        let handle =
-            tokio::spawn(async move { run_server(&sock2, cancel2, bot, config, pending).await });
+        let handle = tokio::spawn(async move { run_server(&sock2, cancel2, ctx).await });
 
         tokio::time::sleep(std::time::Duration::from_millis(100)).await;
 
-        // Connect and send a valid IPC request (will fail at Telegram API call)
         let request = crate::models::IpcRequest {
             request_id: Uuid::new_v4(),
             tool_name: "Bash".to_string(),
@@ -329,16 +679,328 @@ mod tests {
             session_id: "test-session".to_string(),
             permission_suggestions: vec![],
             assistant_context: None,
+            first_prompt: None,
         };
         let mut json = serde_json::to_string(&request).unwrap();
         json.push('\n');
 
         let mut stream = tokio::net::UnixStream::connect(&sock).await.unwrap();
         stream.write_all(json.as_bytes()).await.unwrap();
-        drop(stream);
 
-        // Give server time to process (Telegram API call will fail quickly)
-        tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+        let view = channel.next_sent().await;
+        assert_eq!(view.request_id(), request.request_id);
+        assert_eq!(
+            channel.decide(request.request_id, Verdict::Allow).await,
+            crate::channel::DecisionOutcome::Resolved
+        );
+
+        let mut line = String::new();
+        tokio::io::BufReader::new(stream)
+            .read_line(&mut line)
+            .await
+            .unwrap();
+        let response: IpcResponse = serde_json::from_str(&line).unwrap();
+        assert_eq!(response.request_id, request.request_id);
+        assert_eq!(response.decision, Decision::Allow);
+        assert!(channel.updates().contains(&(
+            request.request_id,
+            StatusUpdate::Resolved("\u{2705} Approved".to_string())
+        )));
+        assert_eq!(
+            channel.decide(request.request_id, Verdict::Deny).await,
+            crate::channel::DecisionOutcome::AlreadyHandled
+        );
+
+        cancel.cancel();
+        handle.await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn quorum_never_resolves_as_always_allow() {
+        use crate::channel::DecisionOutcome;
+
+        let cancel = CancellationToken::new();
+        let (ctx, channel) = make_test_context();
+        let (sender, response) = oneshot::channel();
+        let mut pending = crate::channel::memory::pending_request("git push");
+        pending.sender = sender;
+        pending.required_approvals = 2;
+        pending.request.permission_suggestions = vec![serde_json::json!({"tool": "Bash"})];
+        let request_id = pending.request.request_id;
+        ctx.pending_map.insert(request_id, pending);
+        tokio::spawn(crate::channel::run_decisions(ctx.clone(), cancel.clone()));
+
+        assert_eq!(
+            channel
+                .decide_as(request_id, Verdict::AlwaysAllow, "alice")
+                .await,
+            DecisionOutcome::ApprovalRecorded
+        );
+        assert_eq!(
+            channel
+                .decide_as(request_id, Verdict::AlwaysAllow, "bob")
+                .await,
+            DecisionOutcome::Resolved
+        );
+        let response = response.await.unwrap();
+        assert_eq!(response.decision, Decision::Allow);
+        assert!(response.always_allow_suggestion.is_none());
+
+        cancel.cancel();
+    }
+
+    #[tokio::test]
+    async fn anonymous_approvals_dont_count_towards_a_quorum() {
+        use crate::channel::DecisionOutcome;
+
+        let cancel = CancellationToken::new();
+        let (mut ctx, channel) = make_test_context();
+        let web = Arc::new(MemoryChannel::anonymous());
+        ctx.channels = Arc::new(vec![
+            channel.clone() as Arc<dyn ApprovalChannel>,
+            web.clone(),
+        ]);
+        let mut pending = crate::channel::memory::pending_request("git push");
+        pending.required_approvals = 2;
+        let request_id = pending.request.request_id;
+        ctx.pending_map.insert(request_id, pending);
+        tokio::spawn(crate::channel::run_decisions(ctx.clone(), cancel.clone()));
+
+        assert_eq!(
+            channel.decide_as(request_id, Verdict::Allow, "1").await,
+            DecisionOutcome::ApprovalRecorded
+        );
+        assert_eq!(
+            web.decide(request_id, Verdict::Allow).await,
+            DecisionOutcome::UnidentifiedApprover
+        );
+        assert_eq!(ctx.pending_map.get(&request_id).unwrap().approvals.len(), 1);
+        // Anonymous channels can still deny
+        assert_eq!(
+            web.decide(request_id, Verdict::Deny).await,
+            DecisionOutcome::Resolved
+        );
+
+        cancel.cancel();
+    }
+
+    #[tokio::test]
+    async fn requests_held_while_paused_are_posted_everywhere_on_resume() {
+        let (ctx, channel) = make_test_context();
+        let mut requests = Vec::new();
+        for command in ["ls", "pwd"] {
+            let mut pending = crate::channel::memory::pending_request(command);
+            pending.posted = false;
+            requests.push(pending.request.request_id);
+            ctx.pending_map.insert(pending.request.request_id, pending);
+        }
+
+        let deliveries = [crate::quiet::Delivery {
+            chat_id: 1,
+            silent: false,
+        }];
+        let page = Page {
+            tier: 0,
+            deliveries: &deliveries,
+        };
+        post_together(&ctx, &requests, page).await;
+        for request_id in requests {
+            assert_eq!(channel.next_sent().await.request_id(), request_id);
+            assert!(ctx.pending_map.get(&request_id).unwrap().posted);
+        }
+    }
+
+    #[tokio::test]
+    async fn escalation_skips_channels_with_one_audience() {
+        let (ctx, channel) = make_test_context();
+        let pending = crate::channel::memory::pending_request("ls");
+        let request = pending.request.clone();
+        ctx.pending_map.insert(request.request_id, pending);
+
+        escalate(&ctx, &request, 1, &[1]).await;
+        let sent = tokio::time::timeout(Duration::from_millis(50), channel.next_sent()).await;
+        assert!(sent.is_err());
+    }
+
+    /// A channel whose status updates never finish, like an unreachable
+    /// backend.
+    struct StuckChannel;
+
+    impl ApprovalChannel for StuckChannel {
+        fn name(&self) -> &'static str {
+            "Stuck"
+        }
+
+        fn send_request<'a>(
+            &'a self,
+            _view: &'a crate::channel::RequestView,
+            _page: Page<'a>,
+        ) -> futures::future::BoxFuture<'a, anyhow::Result<()>> {
+            Box::pin(async { Ok(()) })
+        }
+
+        fn update_status<'a>(
+            &'a self,
+            _view: &'a crate::channel::RequestView,
+            _update: &'a StatusUpdate,
+        ) -> futures::future::BoxFuture<'a, ()> {
+            Box::pin(std::future::pending())
+        }
+
+        fn decisions(&self) -> crate::channel::DecisionStream {
+            Box::pin(futures::stream::empty())
+        }
+    }
+
+    #[tokio::test]
+    async fn slow_channels_dont_hold_up_decisions() {
+        let cancel = CancellationToken::new();
+        let (mut ctx, channel) = make_test_context();
+        ctx.channels = Arc::new(vec![
+            Arc::new(StuckChannel) as Arc<dyn ApprovalChannel>,
+            channel.clone(),
+        ]);
+        let mut requests = Vec::new();
+        for command in ["ls", "pwd"] {
+            let (sender, response) = oneshot::channel();
+            let mut pending = crate::channel::memory::pending_request(command);
+            pending.sender = sender;
+            requests.push((pending.request.request_id, response));
+            ctx.pending_map.insert(pending.request.request_id, pending);
+        }
+        tokio::spawn(crate::channel::run_decisions(ctx.clone(), cancel.clone()));
+
+        for (request_id, response) in requests {
+            tokio::spawn({
+                let channel = channel.clone();
+                async move { channel.decide(request_id, Verdict::Allow).await }
+            });
+            let response = tokio::time::timeout(Duration::from_secs(1), response)
+                .await
+                .expect("decision held up by a stuck channel")
+                .unwrap();
+            assert_eq!(response.decision, Decision::Allow);
+        }
+
+        cancel.cancel();
+    }
+
+    #[tokio::test]
+    async fn pause_holds_requests_for_at_most_an_hour() {
+        let (ctx, _) = make_test_context();
+        let mut pending = crate::channel::memory::pending_request("git push");
+        pending.posted = false;
+        let request_id = pending.request.request_id;
+        let created_at = pending.created_at;
+        ctx.pauses.pause(&pending.request.session_id);
+        ctx.pending_map.insert(request_id, pending);
+        let deadline = || ctx.pending_map.get(&request_id).unwrap().deadline();
+
+        let early = created_at + Duration::from_mins(10);
+        hold_while_paused(&ctx, request_id, early);
+        assert_eq!(deadline(), early + Duration::from_secs(5) + HOLD_INTERVAL);
+
+        let limit = created_at + Duration::from_secs(MAX_TIMEOUT_SECONDS);
+        hold_while_paused(&ctx, request_id, limit - Duration::from_secs(10));
+        assert_eq!(deadline(), limit);
+        hold_while_paused(&ctx, request_id, limit);
+        assert_eq!(deadline(), limit);
+    }
+
+    #[tokio::test]
+    async fn muted_session_is_answered_without_telegram() {
+        use tokio::io::{AsyncBufReadExt, AsyncWriteExt};
+
+        let tmp = tempfile::tempdir().unwrap();
+        let sock = tmp.path().join("muted.sock");
+        let cancel = CancellationToken::new();
+        let (ctx, _) = make_test_context();
+        ctx.mutes.mute(
+            "test-ses",
+            crate::mute::Mute {
+                outcome: MuteOutcome::Deny,
+                until: None,
+            },
+        );
+
+        let cancel2 = cancel.clone();
+        let sock2 = sock.clone();
+        let handle = tokio::spawn(async move { run_server(&sock2, cancel2, ctx).await });
+
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+
+        let request = crate::models::IpcRequest {
+            request_id: Uuid::new_v4(),
+            tool_name: "Bash".to_string(),
+            tool_input: serde_json::json!({"command": "echo hello"}),
+            cwd: "/tmp".to_string(),
+            session_id: "test-session".to_string(),
+            permission_suggestions: vec![],
+            assistant_context: None,
+            first_prompt: None,
+        };
+        let mut json = serde_json::to_string(&request).unwrap();
+        json.push('\n');
+
+        let mut stream = tokio::net::UnixStream::connect(&sock).await.unwrap();
+        stream.write_all(json.as_bytes()).await.unwrap();
+        let mut line = String::new();
+        tokio::io::BufReader::new(stream)
+            .read_line(&mut line)
+            .await
+            .unwrap();
+        let response: IpcResponse = serde_json::from_str(&line).unwrap();
+        assert_eq!(response.request_id, request.request_id);
+        assert_eq!(response.decision, Decision::Deny);
+        assert_eq!(response.message.as_deref(), Some(MUTED_MESSAGE));
+
+        cancel.cancel();
+        handle.await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn paused_session_is_held_with_keepalives() {
+        use tokio::io::{AsyncBufReadExt, AsyncWriteExt};
+
+        let tmp = tempfile::tempdir().unwrap();
+        let sock = tmp.path().join("paused.sock");
+        let cancel = CancellationToken::new();
+        let (ctx, _) = make_test_context();
+        ctx.pauses.pause("test-session");
+        let pending_map = ctx.pending_map.clone();
+
+        let cancel2 = cancel.clone();
+        let sock2 = sock.clone();
+        let handle = tokio::spawn(async move { run_server(&sock2, cancel2, ctx).await });
+
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+
+        let request = crate::models::IpcRequest {
+            request_id: Uuid::new_v4(),
+            tool_name: "Bash".to_string(),
+            tool_input: serde_json::json!({"command": "echo hello"}),
+            cwd: "/tmp".to_string(),
+            session_id: "test-session".to_string(),
+            permission_suggestions: vec![],
+            assistant_context: None,
+            first_prompt: None,
+        };
+        let mut json = serde_json::to_string(&request).unwrap();
+        json.push('\n');
+
+        let mut stream = tokio::net::UnixStream::connect(&sock).await.unwrap();
+        stream.write_all(json.as_bytes()).await.unwrap();
+        let mut line = String::new();
+        let mut reader = tokio::io::BufReader::new(stream);
+        reader.read_line(&mut line).await.unwrap();
+        let keepalive: IpcKeepalive = serde_json::from_str(&line).unwrap();
+        assert_eq!(keepalive.request_id, request.request_id);
+        assert!(keepalive.extend_seconds > 0);
+
+        let pending = pending_map.get(&request.request_id).unwrap();
+        assert!(!pending.posted);
+        assert_eq!(pending.summary, "Bash: echo hello");
+        drop(pending);
 
         cancel.cancel();
         handle.await.unwrap().unwrap();
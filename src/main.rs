@@ -1,11 +1,31 @@
+mod activity;
 mod bot;
+mod burst;
+mod channel;
 mod config;
+mod control;
+mod discord;
+mod email;
 mod error;
 mod hook;
+mod http;
 mod install;
 mod ipc;
+mod matrix;
 mod models;
+mod mute;
+mod ntfy;
+mod pause;
+mod quiet;
+mod quorum;
+mod rota;
+mod routing;
+mod signal;
+mod slack;
 mod telegram;
+mod tui;
+mod web;
+mod webhook;
 
 use clap::{Parser, Subcommand};
 use tracing_subscriber::EnvFilter;
@@ -26,10 +46,12 @@ enum Commands {
     Bot,
     /// Register the permission hook in Claude Code settings
     Install,
+    /// Answer pending requests from the terminal
+    Tui,
 }
 
-fn init_tracing(is_hook_mode: bool) {
-    let default_level = if is_hook_mode { "warn" } else { "info" };
+fn init_tracing(is_quiet: bool) {
+    let default_level = if is_quiet { "warn" } else { "info" };
     let filter =
         EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(default_level));
 
@@ -42,8 +64,10 @@ fn init_tracing(is_hook_mode: bool) {
 fn main() {
     let cli = Cli::parse();
 
-    let is_hook_mode = cli.command.is_none();
-    init_tracing(is_hook_mode);
+    // The TUI doesn't log at all: anything on stderr would scribble over it
+    if !matches!(cli.command, Some(Commands::Tui)) {
+        init_tracing(cli.command.is_none());
+    }
 
     match cli.command {
         Some(Commands::Bot) => {
@@ -67,6 +91,21 @@ fn main() {
                 std::process::exit(1);
             }
         }
+        Some(Commands::Tui) => {
+            let config = match config::Config::load() {
+                Ok(c) => c,
+                Err(e) => {
+                    eprintln!("Error: {e}");
+                    std::process::exit(1);
+                }
+            };
+
+            let rt = tokio::runtime::Runtime::new().expect("Failed to create tokio runtime");
+            if let Err(e) = rt.block_on(tui::run_tui(&config)) {
+                eprintln!("TUI error: {e}");
+                std::process::exit(1);
+            }
+        }
         None => {
             // Hook mode: read stdin, forward to bot, write stdout
             let config = match config::Config::load() {
@@ -0,0 +1,93 @@
+use reqwest::Url;
+use serde_json::Value;
+use std::time::Duration;
+
+/// The few client-server API endpoints the bot uses, authenticated with an
+/// access token.
+#[derive(Clone)]
+pub struct MatrixApi {
+    client: reqwest::Client,
+    homeserver_url: String,
+    token: String,
+}
+
+impl MatrixApi {
+    pub fn new(homeserver_url: &str, token: &str) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            homeserver_url: homeserver_url.trim_end_matches('/').to_string(),
+            token: token.to_string(),
+        }
+    }
+
+    /// Sends a room event. Returns its event ID.
+    pub async fn send_event(
+        &self,
+        room_id: &str,
+        event_type: &str,
+        content: &Value,
+    ) -> anyhow::Result<String> {
+        let url = self.url(&["rooms", room_id, "send", event_type, &txn_id()])?;
+        let response = self.call(self.client.put(url).json(content)).await?;
+        response["event_id"]
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| anyhow::anyhow!("Matrix send returned no event_id"))
+    }
+
+    pub async fn redact(&self, room_id: &str, event_id: &str) -> anyhow::Result<()> {
+        let url = self.url(&["rooms", room_id, "redact", event_id, &txn_id()])?;
+        self.call(self.client.put(url).json(&serde_json::json!({})))
+            .await
+            .map(drop)
+    }
+
+    /// One `/sync` long poll, waiting up to `timeout` for new events.
+    pub async fn sync(
+        &self,
+        since: Option<&str>,
+        filter: &str,
+        timeout: Duration,
+    ) -> anyhow::Result<Value> {
+        let mut url = self.url(&["sync"])?;
+        url.query_pairs_mut()
+            .append_pair("filter", filter)
+            .append_pair("timeout", &timeout.as_millis().to_string());
+        if let Some(since) = since {
+            url.query_pairs_mut().append_pair("since", since);
+        }
+        // Leave the server its full timeout before giving up
+        let request = self
+            .client
+            .get(url)
+            .timeout(timeout + Duration::from_secs(30));
+        self.call(request).await
+    }
+
+    /// `/_matrix/client/v3/{segments...}`, each segment percent-encoded.
+    fn url(&self, segments: &[&str]) -> anyhow::Result<Url> {
+        let mut url = Url::parse(&self.homeserver_url)?;
+        url.path_segments_mut()
+            .map_err(|()| anyhow::anyhow!("matrix homeserver_url can't take a path"))?
+            .pop_if_empty()
+            .extend(["_matrix", "client", "v3"])
+            .extend(segments);
+        Ok(url)
+    }
+
+    async fn call(&self, request: reqwest::RequestBuilder) -> anyhow::Result<Value> {
+        let response = request.bearer_auth(&self.token).send().await?;
+        let status = response.status();
+        let body: Value = response.json().await.unwrap_or(Value::Null);
+        if !status.is_success() {
+            let error = body["error"].as_str().unwrap_or("unknown error");
+            anyhow::bail!("Matrix request failed ({status}): {error}");
+        }
+        Ok(body)
+    }
+}
+
+/// Transaction IDs make retried sends idempotent; each send gets a new one.
+fn txn_id() -> String {
+    uuid::Uuid::new_v4().simple().to_string()
+}
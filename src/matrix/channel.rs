@@ -0,0 +1,595 @@
+use crate::channel::{
+    ApprovalChannel, DecisionQueue, DecisionStream, Page, RequestView, StatusUpdate, Verdict,
+};
+use crate::matrix::MatrixConfig;
+use crate::matrix::api::MatrixApi;
+use crate::matrix::message;
+use crate::models::Approver;
+use crate::telegram::reaction::ReactionAction;
+use dashmap::DashMap;
+use futures::future::BoxFuture;
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::Instant;
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
+
+/// How long one `/sync` long poll waits for new events.
+const SYNC_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Pause after a failed `/sync` before trying again.
+const SYNC_RETRY_DELAY: Duration = Duration::from_secs(5);
+
+/// A request's events in the room.
+#[derive(Debug)]
+struct Posted {
+    event_id: String,
+    /// Approvals shown in the message, which only changes when they do.
+    shown_approvals: usize,
+    /// Pre-timeout reminders replying to the message.
+    reminders: Vec<String>,
+}
+
+/// Matrix as an approval channel: messages in one unencrypted room,
+/// answered by reactions or replies that relate to them.
+pub struct MatrixChannel {
+    api: MatrixApi,
+    config: MatrixConfig,
+    reactions: HashMap<String, ReactionAction>,
+    posted: DashMap<Uuid, Posted>,
+    decisions: DecisionQueue,
+}
+
+pub type MatrixState = Arc<MatrixChannel>;
+
+impl MatrixChannel {
+    pub fn new(config: MatrixConfig, reactions: HashMap<String, ReactionAction>) -> Self {
+        Self {
+            api: MatrixApi::new(&config.homeserver_url, &config.access_token),
+            config,
+            reactions,
+            posted: DashMap::new(),
+            decisions: DecisionQueue::default(),
+        }
+    }
+
+    /// Follows the room with `/sync` until `cancel_token` fires. Events from
+    /// before the bot started are skipped.
+    pub async fn run(self: Arc<Self>, cancel_token: CancellationToken) {
+        let filter = self.sync_filter();
+        let mut since: Option<String> = None;
+        loop {
+            // The first sync only finds out where the room is now
+            let timeout = if since.is_some() {
+                SYNC_TIMEOUT
+            } else {
+                Duration::ZERO
+            };
+            let response = tokio::select! {
+                () = cancel_token.cancelled() => return,
+                response = self.api.sync(since.as_deref(), &filter, timeout) => response,
+            };
+            match response {
+                Ok(response) => {
+                    if since.is_some() {
+                        self.handle_sync(&response).await;
+                    }
+                    if let Some(next_batch) = response["next_batch"].as_str() {
+                        since = Some(next_batch.to_string());
+                    }
+                }
+                Err(e) => {
+                    tracing::warn!("Matrix sync failed: {e}");
+                    tokio::select! {
+                        () = cancel_token.cancelled() => return,
+                        () = tokio::time::sleep(SYNC_RETRY_DELAY) => {}
+                    }
+                }
+            }
+        }
+    }
+
+    /// Only the timeline of the requests room.
+    fn sync_filter(&self) -> String {
+        json!({
+            "presence": { "types": [] },
+            "account_data": { "types": [] },
+            "room": {
+                "rooms": [self.config.room_id],
+                "account_data": { "types": [] },
+                "ephemeral": { "types": [] },
+                "state": { "types": [] },
+                "timeline": { "types": ["m.reaction", "m.room.message"] },
+            },
+        })
+        .to_string()
+    }
+
+    async fn handle_sync(&self, response: &Value) {
+        let events = &response["rooms"]["join"][&self.config.room_id]["timeline"]["events"];
+        let Some(events) = events.as_array() else {
+            return;
+        };
+        for event in events {
+            match serde_json::from_value::<RoomEvent>(event.clone()) {
+                Ok(event) => self.handle_event(event).await,
+                Err(e) => tracing::debug!("Skipping unexpected Matrix event: {e}"),
+            }
+        }
+    }
+
+    /// Turns a reaction to, or reply to, a request's message into a
+    /// decision.
+    async fn handle_event(&self, event: RoomEvent) {
+        if event.sender == self.config.user_id {
+            return;
+        }
+        let Some((target, verdict)) = self.verdict(&event) else {
+            return;
+        };
+        let Some(request_id) = self.request_for_event(target) else {
+            return;
+        };
+        if !self.config.allowed_user_ids.contains(&event.sender) {
+            tracing::warn!(sender = %event.sender, "Unauthorized Matrix answer");
+            self.notify(
+                &event.event_id,
+                "You are not allowed to answer permission requests",
+            )
+            .await;
+            return;
+        }
+
+        let approver = Approver {
+            id: format!("matrix:{}", event.sender),
+            name: event.sender.clone(),
+        };
+        let outcome = self.decisions.decide(request_id, verdict, approver).await;
+        if let Some(text) = outcome.feedback() {
+            self.notify(&event.event_id, text).await;
+        }
+    }
+
+    /// The event an answer relates to, and what it says.
+    fn verdict<'a>(&self, event: &'a RoomEvent) -> Option<(&'a str, Verdict)> {
+        let relates_to = &event.content["m.relates_to"];
+        match event.kind.as_str() {
+            "m.reaction" if relates_to["rel_type"] == "m.annotation" => {
+                let verdict = match self.reactions.get(relates_to["key"].as_str()?)? {
+                    ReactionAction::Allow => Verdict::Allow,
+                    ReactionAction::Deny => Verdict::Deny,
+                };
+                Some((relates_to["event_id"].as_str()?, verdict))
+            }
+            // Edits carry the reply relation of the message they edit
+            "m.room.message" if relates_to["rel_type"] != "m.replace" => {
+                let target = relates_to["m.in_reply_to"]["event_id"].as_str()?;
+                let text = message::strip_reply_fallback(event.content["body"].as_str()?);
+                (!text.is_empty()).then_some((target, Verdict::Reply(text)))
+            }
+            _ => None,
+        }
+    }
+
+    fn request_for_event(&self, event_id: &str) -> Option<Uuid> {
+        self.posted
+            .iter()
+            .find(|posted| posted.event_id == event_id)
+            .map(|posted| *posted.key())
+    }
+
+    /// A notice replying to `event_id`.
+    async fn notify(&self, event_id: &str, text: &str) {
+        let content = message::notice_content(event_id, text);
+        if let Err(e) = self.send(&content).await {
+            tracing::warn!("Failed to send Matrix feedback: {e}");
+        }
+    }
+
+    async fn send(&self, content: &Value) -> anyhow::Result<String> {
+        self.api
+            .send_event(&self.config.room_id, "m.room.message", content)
+            .await
+    }
+
+    async fn refresh(&self, view: &RequestView) {
+        let Some(event_id) = self.posted.get_mut(&view.request_id()).map(|mut posted| {
+            posted.shown_approvals = view.approvals.len();
+            posted.event_id.clone()
+        }) else {
+            return;
+        };
+        let text = message::open_text(view, &self.reactions, Instant::now());
+        self.edit(&event_id, &text).await;
+    }
+
+    /// Replies to the message so the request isn't missed.
+    async fn remind(&self, view: &RequestView) {
+        let Some(event_id) = self.event_id(view) else {
+            return;
+        };
+        let text = crate::telegram::formatter::reminder_text(
+            view.deadline.saturating_duration_since(Instant::now()),
+        );
+        match self.send(&message::notice_content(&event_id, &text)).await {
+            Ok(reminder) => {
+                if let Some(mut posted) = self.posted.get_mut(&view.request_id()) {
+                    posted.reminders.push(reminder);
+                }
+            }
+            Err(e) => tracing::warn!("Failed to send Matrix reminder: {e}"),
+        }
+    }
+
+    async fn redact_reminders(&self, reminders: &[String]) {
+        for event_id in reminders {
+            if let Err(e) = self.api.redact(&self.config.room_id, event_id).await {
+                tracing::debug!("Failed to redact Matrix reminder: {e}");
+            }
+        }
+    }
+
+    /// Shows `status` in the message and forgets it.
+    async fn close(&self, view: &RequestView, status: &str) {
+        let Some((_, posted)) = self.posted.remove(&view.request_id()) else {
+            return;
+        };
+        self.redact_reminders(&posted.reminders).await;
+        let text = message::closed_text(&view.request, status);
+        self.edit(&posted.event_id, &text).await;
+    }
+
+    fn event_id(&self, view: &RequestView) -> Option<String> {
+        self.posted
+            .get(&view.request_id())
+            .map(|posted| posted.event_id.clone())
+    }
+
+    async fn edit(&self, event_id: &str, text: &message::Text) {
+        if let Err(e) = self.send(&message::edit_content(event_id, text)).await {
+            tracing::warn!(event_id = %event_id, "Failed to edit Matrix message: {e}");
+        }
+    }
+}
+
+impl ApprovalChannel for MatrixChannel {
+    fn name(&self) -> &'static str {
+        "Matrix"
+    }
+
+    fn send_request<'a>(
+        &'a self,
+        view: &'a RequestView,
+        _page: Page<'a>,
+    ) -> BoxFuture<'a, anyhow::Result<()>> {
+        Box::pin(async move {
+            if self.posted.contains_key(&view.request_id()) {
+                return Ok(());
+            }
+            let text = message::open_text(view, &self.reactions, Instant::now());
+            let event_id = self.send(&text.content("m.text")).await?;
+            self.posted.insert(
+                view.request_id(),
+                Posted {
+                    event_id,
+                    shown_approvals: view.approvals.len(),
+                    reminders: Vec::new(),
+                },
+            );
+            Ok(())
+        })
+    }
+
+    fn update_status<'a>(
+        &'a self,
+        view: &'a RequestView,
+        update: &'a StatusUpdate,
+    ) -> BoxFuture<'a, ()> {
+        Box::pin(async move {
+            match update {
+                // Also sent every minute for countdowns, which Matrix doesn't show
+                StatusUpdate::Progress => {
+                    let changed = self
+                        .posted
+                        .get(&view.request_id())
+                        .is_some_and(|posted| posted.shown_approvals != view.approvals.len());
+                    if changed {
+                        self.refresh(view).await;
+                    }
+                }
+                StatusUpdate::Extended => {
+                    let reminders = self
+                        .posted
+                        .get_mut(&view.request_id())
+                        .map(|mut posted| std::mem::take(&mut posted.reminders))
+                        .unwrap_or_default();
+                    self.redact_reminders(&reminders).await;
+                    self.refresh(view).await;
+                }
+                StatusUpdate::Reminder => self.remind(view).await,
+                StatusUpdate::Withdrawn(status) | StatusUpdate::Resolved(status) => {
+                    self.close(view, status).await;
+                }
+            }
+        })
+    }
+
+    fn decisions(&self) -> DecisionStream {
+        self.decisions.stream()
+    }
+}
+
+/// The part of a timeline event the bot acts on.
+#[derive(Debug, Deserialize)]
+struct RoomEvent {
+    event_id: String,
+    sender: String,
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    content: Value,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::channel::DecisionOutcome;
+    use crate::http::json_response;
+    use crate::http::test_support::{json_body, recording_server};
+    use crate::models::IpcRequest;
+    use crate::telegram::reaction::default_reactions;
+    use bytes::Bytes;
+    use futures::StreamExt;
+    use hyper::Request;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tokio::sync::mpsc;
+
+    const ROOM: &str = "!room:example.org";
+
+    /// Stands in for a homeserver: reports each send or redaction as
+    /// `(path, body)`, and answers `/sync` with `syncs` in turn (then with
+    /// nothing new).
+    async fn fake_homeserver(
+        syncs: Vec<Value>,
+    ) -> (String, mpsc::UnboundedReceiver<(String, Value)>) {
+        let path = |request: &Request<Bytes>| {
+            let path = request.uri().path();
+            path.trim_start_matches("/_matrix/client/v3").to_string()
+        };
+        let record = move |request: &Request<Bytes>| {
+            let path = path(request);
+            (path != "/sync").then(|| (path, json_body(request)))
+        };
+        let served = AtomicUsize::new(0);
+        let respond = move |request: &Request<Bytes>| {
+            let sync = (path(request) == "/sync")
+                .then(|| syncs.get(served.fetch_add(1, Ordering::SeqCst)).cloned());
+            async move {
+                match sync {
+                    None => json_response(&json!({ "event_id": "$posted" })),
+                    Some(Some(sync)) => json_response(&sync),
+                    Some(None) => {
+                        tokio::time::sleep(Duration::from_millis(50)).await;
+                        json_response(&json!({ "next_batch": "end" }))
+                    }
+                }
+            }
+        };
+        recording_server(record, respond).await
+    }
+
+    fn make_channel(homeserver_url: &str) -> Arc<MatrixChannel> {
+        let config = MatrixConfig {
+            homeserver_url: homeserver_url.to_string(),
+            access_token: "token".to_string(),
+            user_id: "@bot:example.org".to_string(),
+            room_id: ROOM.to_string(),
+            allowed_user_ids: ["@alice:example.org".to_string()].into(),
+        };
+        Arc::new(MatrixChannel::new(config, default_reactions()))
+    }
+
+    fn make_view() -> RequestView {
+        RequestView {
+            request: IpcRequest {
+                request_id: Uuid::new_v4(),
+                tool_name: "Bash".to_string(),
+                tool_input: json!({"command": "ls"}),
+                cwd: "/tmp/proj".to_string(),
+                session_id: "s".to_string(),
+                permission_suggestions: vec![],
+                assistant_context: None,
+                first_prompt: None,
+            },
+            summary: "Bash: ls".to_string(),
+            deadline: Instant::now() + Duration::from_secs(150),
+            required_approvals: 1,
+            approvals: vec![],
+        }
+    }
+
+    fn page() -> Page<'static> {
+        Page {
+            tier: 0,
+            deliveries: &[],
+        }
+    }
+
+    fn sync_with(next_batch: &str, events: &[Value]) -> Value {
+        json!({
+            "next_batch": next_batch,
+            "rooms": { "join": { ROOM: { "timeline": { "events": events } } } },
+        })
+    }
+
+    fn reaction(sender: &str, key: &str) -> Value {
+        json!({
+            "event_id": format!("$reaction-{key}"),
+            "sender": sender,
+            "type": "m.reaction",
+            "content": { "m.relates_to": {
+                "rel_type": "m.annotation", "event_id": "$posted", "key": key,
+            } },
+        })
+    }
+
+    fn reply(sender: &str, body: &str) -> Value {
+        json!({
+            "event_id": "$reply",
+            "sender": sender,
+            "type": "m.room.message",
+            "content": {
+                "msgtype": "m.text",
+                "body": body,
+                "m.relates_to": { "m.in_reply_to": { "event_id": "$posted" } },
+            },
+        })
+    }
+
+    #[tokio::test]
+    async fn posts_edits_and_redacts() {
+        let (url, mut calls) = fake_homeserver(vec![]).await;
+        let channel = make_channel(&url);
+        let view = make_view();
+
+        channel.send_request(&view, page()).await.unwrap();
+        let (path, body) = calls.recv().await.unwrap();
+        assert!(path.starts_with(&format!("/rooms/{ROOM}/send/m.room.message/")));
+        assert_eq!(body["msgtype"], "m.text");
+        assert!(
+            body["formatted_body"]
+                .as_str()
+                .unwrap()
+                .contains("<pre><code>ls")
+        );
+
+        // The countdown tick doesn't edit; a new approval does
+        channel.update_status(&view, &StatusUpdate::Progress).await;
+        let mut approved = view.clone();
+        approved.required_approvals = 2;
+        approved.approvals = vec![Approver {
+            id: "matrix:@alice:example.org".to_string(),
+            name: "@alice:example.org".to_string(),
+        }];
+        channel
+            .update_status(&approved, &StatusUpdate::Progress)
+            .await;
+        let (_, body) = calls.recv().await.unwrap();
+        assert_eq!(body["m.relates_to"]["rel_type"], "m.replace");
+        assert!(
+            body["m.new_content"]["body"]
+                .as_str()
+                .unwrap()
+                .contains("1/2 approvals")
+        );
+
+        channel.update_status(&view, &StatusUpdate::Reminder).await;
+        let (_, body) = calls.recv().await.unwrap();
+        assert_eq!(body["msgtype"], "m.notice");
+        assert_eq!(body["m.relates_to"]["m.in_reply_to"]["event_id"], "$posted");
+
+        let status = StatusUpdate::Resolved("\u{2705} Approved".to_string());
+        channel.update_status(&view, &status).await;
+        let (path, _) = calls.recv().await.unwrap();
+        assert!(path.starts_with(&format!("/rooms/{ROOM}/redact/$posted/")));
+        let (_, body) = calls.recv().await.unwrap();
+        assert_eq!(body["m.relates_to"]["rel_type"], "m.replace");
+        assert!(
+            body["m.new_content"]["body"]
+                .as_str()
+                .unwrap()
+                .ends_with("\u{2705} Approved")
+        );
+        assert!(calls.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn reactions_and_replies_become_decisions() {
+        let (url, mut calls) = fake_homeserver(vec![]).await;
+        let channel = make_channel(&url);
+        let mut decisions = channel.decisions();
+        let view = make_view();
+        channel.send_request(&view, page()).await.unwrap();
+        calls.recv().await.unwrap();
+
+        let handled = tokio::spawn({
+            let channel = channel.clone();
+            async move {
+                let events = [
+                    // Its own reactions, unmapped emoji and strangers don't count
+                    reaction("@bot:example.org", "\u{1f44d}"),
+                    reaction("@alice:example.org", "\u{1f525}"),
+                    reaction("@mallory:example.org", "\u{1f44d}"),
+                    reaction("@alice:example.org", "\u{1f44e}"),
+                    reply(
+                        "@alice:example.org",
+                        "> <@bot:example.org> Bash: ls\n\nuse pytest",
+                    ),
+                ];
+                channel.handle_sync(&sync_with("s2", &events)).await;
+            }
+        });
+
+        let (_, body) = calls.recv().await.unwrap();
+        assert_eq!(
+            body["body"],
+            "You are not allowed to answer permission requests"
+        );
+        assert_eq!(
+            body["m.relates_to"]["m.in_reply_to"]["event_id"],
+            "$reaction-\u{1f44d}"
+        );
+
+        let decision = decisions.next().await.unwrap();
+        assert_eq!(decision.request_id, view.request_id());
+        assert_eq!(decision.verdict, Verdict::Deny);
+        assert_eq!(decision.approver.id, "matrix:@alice:example.org");
+        decision
+            .ack
+            .unwrap()
+            .send(DecisionOutcome::Resolved)
+            .unwrap();
+
+        let decision = decisions.next().await.unwrap();
+        assert_eq!(decision.verdict, Verdict::Reply("use pytest".to_string()));
+        decision
+            .ack
+            .unwrap()
+            .send(DecisionOutcome::AlreadyHandled)
+            .unwrap();
+        let (_, body) = calls.recv().await.unwrap();
+        assert_eq!(body["body"], "This request has already been handled");
+        assert_eq!(body["m.relates_to"]["m.in_reply_to"]["event_id"], "$reply");
+
+        handled.await.unwrap();
+        assert!(calls.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn sync_loop_skips_history() {
+        let old = reaction("@alice:example.org", "\u{1f44e}");
+        let new = reaction("@alice:example.org", "\u{1f44d}");
+        let (url, mut calls) =
+            fake_homeserver(vec![sync_with("s1", &[old]), sync_with("s2", &[new])]).await;
+        let channel = make_channel(&url);
+        let mut decisions = channel.decisions();
+        let view = make_view();
+        channel.send_request(&view, page()).await.unwrap();
+        calls.recv().await.unwrap();
+
+        let cancel_token = CancellationToken::new();
+        let running = tokio::spawn(channel.clone().run(cancel_token.clone()));
+        let decision = decisions.next().await.unwrap();
+        assert_eq!(decision.verdict, Verdict::Allow);
+        decision
+            .ack
+            .unwrap()
+            .send(DecisionOutcome::Resolved)
+            .unwrap();
+        cancel_token.cancel();
+        running.await.unwrap();
+    }
+}
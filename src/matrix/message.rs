@@ -0,0 +1,266 @@
+use crate::channel::RequestView;
+use crate::models::IpcRequest;
+use crate::telegram::formatter::{
+    ToolDetails, escape_html, project_name, short_session_id, tool_details, truncate,
+};
+use crate::telegram::reaction::ReactionAction;
+use chrono::Utc;
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use tokio::time::Instant;
+
+const MAX_CONTEXT_CHARS: usize = 500;
+
+/// A message as plain text and as the HTML Matrix clients render.
+#[derive(Debug, Default)]
+pub struct Text {
+    pub plain: String,
+    pub html: String,
+}
+
+impl Text {
+    fn push(&mut self, plain: &str, html: &str) {
+        self.plain.push_str(plain);
+        self.html.push_str(html);
+    }
+
+    /// A paragraph: a blank line in plain text, `<p>` in HTML.
+    fn paragraph(&mut self, plain: &str, html: &str) {
+        if !self.plain.is_empty() {
+            self.plain.push_str("\n\n");
+        }
+        self.push(plain, &format!("<p>{html}</p>"));
+    }
+
+    /// `m.room.message` content of type `msgtype`.
+    pub fn content(&self, msgtype: &str) -> Value {
+        json!({
+            "msgtype": msgtype,
+            "body": self.plain,
+            "format": "org.matrix.custom.html",
+            "formatted_body": self.html,
+        })
+    }
+}
+
+/// The permission message, with the same fields as the Telegram one.
+pub fn permission_text(request: &IpcRequest) -> Text {
+    let mut text = Text::default();
+    let project = project_name(&request.cwd);
+    text.paragraph(
+        &format!("\u{1f4cb} {project}"),
+        &format!("<b>\u{1f4cb} {}</b>", escape_html(project)),
+    );
+    if let Some(context) = request.assistant_context.as_deref() {
+        let context = truncate(context, MAX_CONTEXT_CHARS);
+        text.paragraph(
+            &format!("\u{1f4ac} {context}"),
+            &format!("\u{1f4ac} {}", escape_html(&context)),
+        );
+    }
+    let tool = &request.tool_name;
+    text.paragraph(
+        &format!("\u{1f527} {tool}"),
+        &format!("<b>\u{1f527} {}</b>", escape_html(tool)),
+    );
+    match tool_details(tool, &request.tool_input) {
+        ToolDetails::Code(code) => text.push(
+            &format!("\n{code}"),
+            &format!("<pre><code>{}</code></pre>", escape_html(&code)),
+        ),
+        ToolDetails::Write { file_path, size } => text.paragraph(
+            &format!("\u{1f4c4} {file_path} ({size})"),
+            &format!(
+                "\u{1f4c4} <code>{}</code> ({size})",
+                escape_html(&file_path)
+            ),
+        ),
+        ToolDetails::Edit {
+            file_path,
+            old,
+            new,
+        } => {
+            text.paragraph(
+                &format!("\u{1f4c4} {file_path}"),
+                &format!("\u{1f4c4} <code>{}</code>", escape_html(&file_path)),
+            );
+            text.push(
+                &format!("\n- {old}\n+ {new}"),
+                &format!(
+                    "<pre><code>- {}\n+ {}</code></pre>",
+                    escape_html(&old),
+                    escape_html(&new)
+                ),
+            );
+        }
+    }
+    let session = short_session_id(&request.session_id);
+    text.paragraph(
+        &format!("\u{1f4c1} {}\n\u{1f194} Session: {session}", request.cwd),
+        &format!(
+            "\u{1f4c1} {}<br>\u{1f194} Session: <code>{}</code>",
+            escape_html(&request.cwd),
+            escape_html(session)
+        ),
+    );
+    text
+}
+
+/// A still-open request: the message, its progress and how to answer.
+/// Matrix keeps every edit in the room's history, so the deadline is shown
+/// as a time rather than a countdown that would need refreshing.
+pub fn open_text(
+    view: &RequestView,
+    reactions: &HashMap<String, ReactionAction>,
+    now: Instant,
+) -> Text {
+    let mut text = permission_text(&view.request);
+    let mut status = Vec::new();
+    if view.required_approvals > 1 {
+        status.push(crate::quorum::format_progress(
+            &view.approvals,
+            view.required_approvals,
+        ));
+    }
+    let until = Utc::now() + view.deadline.saturating_duration_since(now);
+    status.push(format!("\u{23f3} Open until {} UTC", until.format("%H:%M")));
+    text.paragraph(&status.join("\n"), &status.join("<br>"));
+    let hint = answer_hint(reactions);
+    text.paragraph(&hint, &format!("<i>{}</i>", escape_html(&hint)));
+    text
+}
+
+/// A request that is no longer open: the message and `status`.
+pub fn closed_text(request: &IpcRequest, status: &str) -> Text {
+    let mut text = permission_text(request);
+    text.paragraph(status, status);
+    text
+}
+
+/// e.g. "React 👍 to allow or 👎 to deny, or reply with instructions".
+fn answer_hint(reactions: &HashMap<String, ReactionAction>) -> String {
+    let emojis = |action| {
+        let mut emojis: Vec<&str> = reactions
+            .iter()
+            .filter(|(_, a)| **a == action)
+            .map(|(emoji, _)| emoji.as_str())
+            .collect();
+        emojis.sort_unstable();
+        emojis.join("/")
+    };
+    let mut options = Vec::new();
+    for (action, verb) in [
+        (ReactionAction::Allow, "allow"),
+        (ReactionAction::Deny, "deny"),
+    ] {
+        let emojis = emojis(action);
+        if !emojis.is_empty() {
+            options.push(format!("{emojis} to {verb}"));
+        }
+    }
+    if options.is_empty() {
+        "Reply to this message with instructions".to_string()
+    } else {
+        format!("React {}, or reply with instructions", options.join(" or "))
+    }
+}
+
+/// Content replacing `event_id`'s with `text` (`m.replace`).
+pub fn edit_content(event_id: &str, text: &Text) -> Value {
+    let mut content = text.content("m.text");
+    content["body"] = json!(format!("* {}", text.plain));
+    content["formatted_body"] = json!(format!("* {}", text.html));
+    content["m.new_content"] = text.content("m.text");
+    content["m.relates_to"] = json!({ "rel_type": "m.replace", "event_id": event_id });
+    content
+}
+
+/// An `m.notice` replying to `event_id`.
+pub fn notice_content(event_id: &str, notice: &str) -> Value {
+    json!({
+        "msgtype": "m.notice",
+        "body": notice,
+        "m.relates_to": { "m.in_reply_to": { "event_id": event_id } },
+    })
+}
+
+/// The reply's own text, without the quoted fallback (`> ...` lines)
+/// older clients put in front of it.
+pub fn strip_reply_fallback(body: &str) -> String {
+    body.lines()
+        .skip_while(|line| line.starts_with('>'))
+        .skip_while(|line| line.trim().is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::telegram::reaction::default_reactions;
+    use uuid::Uuid;
+
+    fn make_request(tool_input: Value) -> IpcRequest {
+        IpcRequest {
+            request_id: Uuid::new_v4(),
+            tool_name: "Bash".to_string(),
+            tool_input,
+            cwd: "/home/me/proj".to_string(),
+            session_id: "abcdef1234".to_string(),
+            permission_suggestions: vec![],
+            assistant_context: Some("Running <tests>".to_string()),
+            first_prompt: None,
+        }
+    }
+
+    #[test]
+    fn permission_text_has_plain_and_html_bodies() {
+        let text = permission_text(&make_request(json!({"command": "ls && pwd"})));
+        assert_eq!(
+            text.plain,
+            "\u{1f4cb} proj\n\n\u{1f4ac} Running <tests>\n\n\u{1f527} Bash\nls && pwd\n\n\u{1f4c1} /home/me/proj\n\u{1f194} Session: abcdef12"
+        );
+        assert_eq!(
+            text.html,
+            "<p><b>\u{1f4cb} proj</b></p><p>\u{1f4ac} Running &lt;tests&gt;</p><p><b>\u{1f527} Bash</b></p><pre><code>ls &amp;&amp; pwd</code></pre><p>\u{1f4c1} /home/me/proj<br>\u{1f194} Session: <code>abcdef12</code></p>"
+        );
+    }
+
+    #[test]
+    fn hint_follows_reaction_mapping() {
+        assert_eq!(
+            answer_hint(&default_reactions()),
+            "React \u{1f44d} to allow or \u{1f44e} to deny, or reply with instructions"
+        );
+        assert_eq!(
+            answer_hint(&HashMap::new()),
+            "Reply to this message with instructions"
+        );
+    }
+
+    #[test]
+    fn edits_replace_the_original_event() {
+        let text = closed_text(&make_request(json!({"command": "ls"})), "\u{2705} Approved");
+        let content = edit_content("$orig", &text);
+        assert_eq!(content["m.relates_to"]["rel_type"], "m.replace");
+        assert_eq!(content["m.relates_to"]["event_id"], "$orig");
+        assert!(
+            content["m.new_content"]["body"]
+                .as_str()
+                .unwrap()
+                .ends_with("\u{2705} Approved")
+        );
+    }
+
+    #[test]
+    fn reply_fallback_is_stripped() {
+        assert_eq!(
+            strip_reply_fallback("> <@bot:example.org> Bash: ls\n> more\n\nuse pytest\nplease"),
+            "use pytest\nplease"
+        );
+        assert_eq!(strip_reply_fallback("  just this "), "just this");
+        assert_eq!(strip_reply_fallback("> quoted only"), "");
+    }
+}
@@ -0,0 +1,54 @@
+pub mod api;
+pub mod channel;
+pub mod message;
+
+use serde::Deserialize;
+use std::collections::HashSet;
+
+/// Posting requests to a Matrix room, answered by reacting or replying.
+#[derive(Debug, Deserialize, Clone)]
+pub struct MatrixConfig {
+    /// Base URL of the homeserver's client API (e.g. `https://matrix.example.org`).
+    pub homeserver_url: String,
+    /// Access token of the bot's account.
+    pub access_token: String,
+    /// The bot's own MXID, whose events are ignored.
+    pub user_id: String,
+    /// ID of the (unencrypted) room requests are posted to.
+    pub room_id: String,
+    /// MXIDs allowed to answer requests.
+    pub allowed_user_ids: HashSet<String>,
+}
+
+/// `@localpart:server`.
+fn is_mxid(id: &str) -> bool {
+    id.strip_prefix('@')
+        .and_then(|rest| rest.split_once(':'))
+        .is_some_and(|(local, server)| !local.is_empty() && !server.is_empty())
+}
+
+impl MatrixConfig {
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if !self.homeserver_url.starts_with("https://")
+            && !self.homeserver_url.starts_with("http://")
+        {
+            anyhow::bail!("matrix homeserver_url must be an http(s) URL");
+        }
+        if self.access_token.is_empty() {
+            anyhow::bail!("matrix access_token must not be empty");
+        }
+        if !is_mxid(&self.user_id) {
+            anyhow::bail!("matrix user_id must look like @bot:example.org");
+        }
+        if !self.room_id.starts_with('!') || !self.room_id.contains(':') {
+            anyhow::bail!("matrix room_id must look like !room:example.org");
+        }
+        if self.allowed_user_ids.is_empty() {
+            anyhow::bail!("matrix allowed_user_ids must have at least one entry");
+        }
+        if let Some(id) = self.allowed_user_ids.iter().find(|id| !is_mxid(id)) {
+            anyhow::bail!("matrix allowed_user_ids entry {id:?} is not an MXID");
+        }
+        Ok(())
+    }
+}
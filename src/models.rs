@@ -1,7 +1,7 @@
 use serde::{Deserialize, Serialize};
 use teloxide::types::{ChatId, MessageId};
-use tokio::sync::oneshot;
-use tokio::time::Instant;
+use tokio::sync::{oneshot, watch};
+use tokio::time::{Duration, Instant};
 use uuid::Uuid;
 
 /// Claude Code's JSON sent to hook via stdin.
@@ -105,7 +105,7 @@ impl HookOutput {
 }
 
 /// Permission details sent from hook to bot over Unix socket (NDJSON).
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct IpcRequest {
     pub request_id: Uuid,
     pub tool_name: String,
@@ -117,6 +117,9 @@ pub struct IpcRequest {
     /// Claude's last assistant message, extracted from the transcript for context.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub assistant_context: Option<String>,
+    /// The session's first user prompt, used to name per-session forum topics.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub first_prompt: Option<String>,
 }
 
 /// Decision sent from bot to hook over Unix socket (NDJSON).
@@ -189,6 +192,22 @@ impl IpcResponse {
     }
 }
 
+/// Sent from bot to hook while a request is still open to push the hook's
+/// own timeout back (e.g. after "+5 min" in Telegram).
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct IpcKeepalive {
+    pub request_id: Uuid,
+    pub extend_seconds: u64,
+}
+
+/// One NDJSON line from bot to hook: any number of keepalives, then the response.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum IpcServerMessage {
+    Response(IpcResponse),
+    Keepalive(IpcKeepalive),
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub enum Decision {
     Allow,
@@ -205,24 +224,93 @@ pub struct SentMessage {
     pub message_id: MessageId,
 }
 
-/// In-memory state for a request awaiting Telegram response.
+/// Someone who answered a request in an approval channel.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Approver {
+    /// Unique across channels, e.g. `telegram:12345`.
+    pub id: String,
+    pub name: String,
+}
+
+/// Outcome of recording one approval on a pending request.
+#[derive(Debug, PartialEq, Eq)]
+pub enum ApprovalProgress {
+    /// This user already approved; nothing changed.
+    Duplicate,
+    /// More approvals are needed.
+    Pending,
+    /// The quorum is reached; the request can be allowed.
+    Reached,
+}
+
+/// In-memory state for a request awaiting a decision.
 pub struct PendingRequest {
-    /// Redundant with the `DashMap` key; kept for logging convenience.
-    #[allow(dead_code)]
-    pub request_id: Uuid,
     pub sender: oneshot::Sender<IpcResponse>,
-    pub sent_messages: Vec<SentMessage>,
-    pub original_text: String,
-    pub permission_suggestions: Vec<serde_json::Value>,
-    /// Stored for future timeout diagnostics.
-    #[allow(dead_code)]
+    pub request: IpcRequest,
+    /// One-line summary for batched lists.
+    pub summary: String,
+    /// Whether the request was posted to the approval channels. Not while it
+    /// is queued for a muted session or held for a paused one.
+    pub posted: bool,
+    /// Every chat the request is routed to, across its notification tiers.
+    pub chat_ids: Vec<i64>,
     pub created_at: Instant,
+    /// When the request falls back to the terminal; the connection handler
+    /// watches it so extensions take effect immediately.
+    pub deadline_tx: watch::Sender<Instant>,
+    /// Distinct approvals needed before the request is allowed (1 = no quorum).
+    pub required_approvals: usize,
+    pub approvals: Vec<Approver>,
+}
+
+impl PendingRequest {
+    pub fn deadline(&self) -> Instant {
+        *self.deadline_tx.borrow()
+    }
+
+    /// Pushes the deadline back by `by`, but no further than `limit` after
+    /// creation. Returns the new deadline, or `None` if already at the limit.
+    pub fn extend_deadline(&self, by: Duration, limit: Duration) -> Option<Instant> {
+        let current = self.deadline();
+        let extended = (current + by).min(self.created_at + limit);
+        if extended <= current {
+            return None;
+        }
+        self.deadline_tx.send_replace(extended);
+        Some(extended)
+    }
+
+    /// Records an approval from `approver`, ignoring repeat taps by the same user.
+    pub fn record_approval(&mut self, approver: Approver) -> ApprovalProgress {
+        if self.approvals.iter().any(|a| a.id == approver.id) {
+            return ApprovalProgress::Duplicate;
+        }
+        self.approvals.push(approver);
+        if self.approvals.len() >= self.required_approvals {
+            ApprovalProgress::Reached
+        } else {
+            ApprovalProgress::Pending
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn make_request() -> IpcRequest {
+        IpcRequest {
+            request_id: Uuid::new_v4(),
+            tool_name: "Bash".to_string(),
+            tool_input: serde_json::json!({"command": "ls"}),
+            cwd: "/tmp".to_string(),
+            session_id: "s".to_string(),
+            permission_suggestions: vec![],
+            assistant_context: None,
+            first_prompt: None,
+        }
+    }
+
     #[test]
     fn hook_output_allow_produces_correct_json() {
         let output = HookOutput::allow();
@@ -331,6 +419,7 @@ mod tests {
             session_id: "session-123".to_string(),
             permission_suggestions: vec![],
             assistant_context: None,
+            first_prompt: None,
         };
 
         let json = serde_json::to_string(&request).unwrap();
@@ -384,4 +473,89 @@ mod tests {
         assert_eq!(reply.decision, Decision::Reply);
         assert_eq!(reply.user_message.as_deref(), Some("use pytest"));
     }
+
+    #[test]
+    fn record_approval_counts_distinct_users() {
+        let (tx, _rx) = oneshot::channel();
+        let mut pending = PendingRequest {
+            sender: tx,
+            request: make_request(),
+            summary: "Bash".to_string(),
+            posted: true,
+            chat_ids: vec![],
+            created_at: Instant::now(),
+            deadline_tx: watch::channel(Instant::now()).0,
+            required_approvals: 2,
+            approvals: vec![],
+        };
+        let alice = Approver {
+            id: "telegram:1".to_string(),
+            name: "alice".to_string(),
+        };
+        let bob = Approver {
+            id: "telegram:2".to_string(),
+            name: "bob".to_string(),
+        };
+
+        assert_eq!(
+            pending.record_approval(alice.clone()),
+            ApprovalProgress::Pending
+        );
+        assert_eq!(pending.record_approval(alice), ApprovalProgress::Duplicate);
+        assert_eq!(pending.record_approval(bob), ApprovalProgress::Reached);
+        assert_eq!(pending.approvals.len(), 2);
+    }
+
+    #[test]
+    fn extend_deadline_is_capped() {
+        let now = Instant::now();
+        let (deadline_tx, deadline_rx) = watch::channel(now + Duration::from_mins(5));
+        let pending = PendingRequest {
+            sender: oneshot::channel().0,
+            request: make_request(),
+            summary: "Bash".to_string(),
+            posted: true,
+            chat_ids: vec![],
+            created_at: now,
+            deadline_tx,
+            required_approvals: 1,
+            approvals: vec![],
+        };
+        let limit = Duration::from_secs(700);
+        let by = Duration::from_mins(5);
+
+        assert_eq!(
+            pending.extend_deadline(by, limit),
+            Some(now + Duration::from_mins(10))
+        );
+        assert_eq!(*deadline_rx.borrow(), now + Duration::from_mins(10));
+        assert_eq!(pending.extend_deadline(by, limit), Some(now + limit));
+        assert_eq!(pending.extend_deadline(by, limit), None);
+    }
+
+    #[test]
+    fn server_message_distinguishes_keepalive() {
+        let id = Uuid::new_v4();
+        let keepalive = serde_json::to_string(&IpcKeepalive {
+            request_id: id,
+            extend_seconds: 300,
+        })
+        .unwrap();
+        assert!(matches!(
+            serde_json::from_str::<IpcServerMessage>(&keepalive).unwrap(),
+            IpcServerMessage::Keepalive(IpcKeepalive {
+                extend_seconds: 300,
+                ..
+            })
+        ));
+
+        let response = serde_json::to_string(&IpcResponse::allow(id)).unwrap();
+        assert!(matches!(
+            serde_json::from_str::<IpcServerMessage>(&response).unwrap(),
+            IpcServerMessage::Response(IpcResponse {
+                decision: Decision::Allow,
+                ..
+            })
+        ));
+    }
 }
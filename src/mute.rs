@@ -0,0 +1,148 @@
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+pub const MUTED_MESSAGE: &str =
+    "This session is muted in Telegram; ask in the terminal or try again later.";
+
+/// What happens to requests from a muted session.
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum MuteOutcome {
+    /// Fall back to the terminal prompt straight away.
+    #[default]
+    Terminal,
+    /// Deny with a message telling Claude the session is muted.
+    Deny,
+    /// Keep the request open without notifying anyone until `/pending`.
+    Queue,
+}
+
+impl MuteOutcome {
+    pub fn parse(text: &str) -> Option<Self> {
+        match text {
+            "terminal" => Some(Self::Terminal),
+            "deny" => Some(Self::Deny),
+            "queue" => Some(Self::Queue),
+            _ => None,
+        }
+    }
+
+    pub const fn describe(self) -> &'static str {
+        match self {
+            Self::Terminal => "requests fall back to the terminal",
+            Self::Deny => "requests are denied",
+            Self::Queue => "requests are queued for /pending",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Mute {
+    pub outcome: MuteOutcome,
+    /// `None` mutes until `/unmute`.
+    pub until: Option<DateTime<Utc>>,
+}
+
+/// Muted sessions, keyed by session id or a prefix of one (as shown in
+/// permission messages).
+#[derive(Default)]
+pub struct Mutes {
+    sessions: Mutex<HashMap<String, Mute>>,
+}
+
+pub type MuteState = Arc<Mutes>;
+
+impl Mutes {
+    pub fn mute(&self, session: &str, mute: Mute) {
+        self.sessions
+            .lock()
+            .expect("mute lock poisoned")
+            .insert(session.to_ascii_lowercase(), mute);
+    }
+
+    /// Unmutes every entry matching `session`. Returns whether any did.
+    pub fn unmute(&self, session: &str) -> bool {
+        let session = session.to_ascii_lowercase();
+        let mut sessions = self.sessions.lock().expect("mute lock poisoned");
+        let before = sessions.len();
+        sessions.retain(|key, _| !(key.starts_with(&session) || session.starts_with(key.as_str())));
+        sessions.len() != before
+    }
+
+    /// The active mute for `session_id` at `now`, if any.
+    pub fn get(&self, session_id: &str, now: DateTime<Utc>) -> Option<Mute> {
+        let session_id = session_id.to_ascii_lowercase();
+        let mut sessions = self.sessions.lock().expect("mute lock poisoned");
+        sessions.retain(|_, mute| mute.until.is_none_or(|until| now < until));
+        sessions
+            .iter()
+            .find(|(key, _)| session_id.starts_with(key.as_str()))
+            .map(|(_, mute)| *mute)
+    }
+
+    /// Active mutes, sorted by session.
+    pub fn list(&self, now: DateTime<Utc>) -> Vec<(String, Mute)> {
+        let mut sessions = self.sessions.lock().expect("mute lock poisoned");
+        sessions.retain(|_, mute| mute.until.is_none_or(|until| now < until));
+        let mut list: Vec<(String, Mute)> = sessions.iter().map(|(k, v)| (k.clone(), *v)).collect();
+        drop(sessions);
+        list.sort_by(|a, b| a.0.cmp(&b.0));
+        list
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    const FOREVER: Mute = Mute {
+        outcome: MuteOutcome::Deny,
+        until: None,
+    };
+
+    #[test]
+    fn prefix_mutes_matching_sessions() {
+        let mutes = Mutes::default();
+        mutes.mute("ABCDEF12", FOREVER);
+        let now = Utc::now();
+        assert_eq!(mutes.get("abcdef1234567890", now), Some(FOREVER));
+        assert!(mutes.get("12345678", now).is_none());
+    }
+
+    #[test]
+    fn timed_mute_expires() {
+        let mutes = Mutes::default();
+        let now = Utc::now();
+        mutes.mute(
+            "abc",
+            Mute {
+                outcome: MuteOutcome::Queue,
+                until: Some(now + Duration::minutes(30)),
+            },
+        );
+        assert!(mutes.get("abcdef", now).is_some());
+        assert!(mutes.get("abcdef", now + Duration::minutes(30)).is_none());
+        assert!(mutes.list(now + Duration::hours(1)).is_empty());
+    }
+
+    #[test]
+    fn unmute_by_full_id_or_prefix() {
+        let mutes = Mutes::default();
+        mutes.mute("abcdef12", FOREVER);
+        assert!(mutes.unmute("abcdef1234567890"));
+        assert!(!mutes.unmute("abcdef12"));
+
+        mutes.mute("abcdef1234567890", FOREVER);
+        assert!(mutes.unmute("abcdef12"));
+        assert!(mutes.list(Utc::now()).is_empty());
+    }
+
+    #[test]
+    fn outcome_parsing() {
+        assert_eq!(MuteOutcome::parse("queue"), Some(MuteOutcome::Queue));
+        assert_eq!(MuteOutcome::parse("loud"), None);
+    }
+}
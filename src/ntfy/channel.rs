@@ -0,0 +1,353 @@
+use crate::channel::{
+    ApprovalChannel, DecisionOutcome, DecisionQueue, DecisionStream, Page, RequestView,
+    StatusUpdate, Verdict, already_handled,
+};
+use crate::http::{Body, Handler, bearer_token, text_response, tokens_match};
+use crate::models::Approver;
+use crate::ntfy::NtfyConfig;
+use crate::telegram::formatter::{format_time_left, project_name};
+use bytes::Bytes;
+use dashmap::DashMap;
+use futures::future::BoxFuture;
+use hyper::{Method, Request, Response, StatusCode};
+use serde_json::{Value, json};
+use std::sync::Arc;
+use tokio::time::Instant;
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
+
+/// Prefix of the action endpoint: `POST /ntfy/{request_id}/{action}`.
+pub const ACTIONS_PATH: &str = "/ntfy/";
+
+/// Notification priority: "high", so it pops up on the lock screen.
+const PRIORITY: u8 = 4;
+
+/// ntfy as an approval channel: push notifications whose action buttons call
+/// back into the bot. The notification can't be edited later, so each
+/// request's token dies with it instead.
+pub struct NtfyChannel {
+    client: reqwest::Client,
+    config: NtfyConfig,
+    /// Token authorizing the actions of each open request.
+    tokens: DashMap<Uuid, String>,
+    decisions: DecisionQueue,
+}
+
+pub type NtfyState = Arc<NtfyChannel>;
+
+impl NtfyChannel {
+    pub fn new(config: NtfyConfig) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            config,
+            tokens: DashMap::new(),
+            decisions: DecisionQueue::default(),
+        }
+    }
+
+    /// Serves the action endpoint until `cancel_token` fires.
+    pub async fn serve(self: Arc<Self>, cancel_token: CancellationToken) -> anyhow::Result<()> {
+        let addr = self.config.listen;
+        let handler: Handler = Arc::new(move |request| {
+            let channel = self.clone();
+            Box::pin(async move { channel.handle_request(&request).await })
+        });
+        crate::http::serve(addr, handler, cancel_token).await
+    }
+
+    /// Applies a tapped action; the phone shows the answer if it fails.
+    async fn handle_request(&self, request: &Request<Bytes>) -> Response<Body> {
+        let action = request
+            .uri()
+            .path()
+            .strip_prefix(ACTIONS_PATH)
+            .and_then(|rest| rest.split_once('/'))
+            .and_then(|(id, action)| Some((id.parse::<Uuid>().ok()?, action)));
+        let Some((request_id, action)) = action.filter(|_| request.method() == Method::POST) else {
+            return text_response(StatusCode::NOT_FOUND, "Not found");
+        };
+        let verdict = match action {
+            "allow" => Verdict::Allow,
+            "deny" => Verdict::Deny,
+            "always" => Verdict::AlwaysAllow,
+            _ => return text_response(StatusCode::NOT_FOUND, "Not found"),
+        };
+        // The token is removed when the request closes
+        let Some(token) = self.tokens.get(&request_id).map(|token| token.clone()) else {
+            return already_handled();
+        };
+        if !bearer_token(request).is_some_and(|presented| tokens_match(presented, &token)) {
+            tracing::warn!(request_id = %request_id, "ntfy action with a bad token");
+            return text_response(StatusCode::UNAUTHORIZED, "Bad token");
+        }
+
+        // Nobody signs in to ntfy, so every tap counts as the same approver
+        let approver = Approver {
+            id: format!("ntfy:{}", self.config.topic),
+            name: "ntfy".to_string(),
+        };
+        match self.decisions.decide(request_id, verdict, approver).await {
+            DecisionOutcome::AlreadyHandled => already_handled(),
+            outcome => text_response(StatusCode::OK, outcome.feedback().unwrap_or("Done")),
+        }
+    }
+
+    /// Publishes the request's notification, with a reminder title if
+    /// `reminder`.
+    async fn publish(&self, view: &RequestView, token: &str, reminder: bool) -> anyhow::Result<()> {
+        let mut title = format!(
+            "{} in {}",
+            view.request.tool_name,
+            project_name(&view.request.cwd)
+        );
+        if reminder {
+            title = format!("\u{23f0} Still waiting: {title}");
+        }
+        let message = format!(
+            "{}\n{}",
+            view.summary,
+            format_time_left(view.deadline.saturating_duration_since(Instant::now()))
+        );
+        let body = json!({
+            "topic": self.config.topic,
+            "title": title,
+            "message": message,
+            "priority": PRIORITY,
+            "tags": ["lock"],
+            "actions": self.actions(view, token),
+        });
+        let mut request = self
+            .client
+            .post(self.config.server_url.trim_end_matches('/'))
+            .json(&body);
+        if let Some(access_token) = &self.config.access_token {
+            request = request.bearer_auth(access_token);
+        }
+        request.send().await?.error_for_status()?;
+        Ok(())
+    }
+
+    /// `http` actions posting back to the action endpoint with the token.
+    fn actions(&self, view: &RequestView, token: &str) -> Vec<Value> {
+        let mut actions = vec![("allow", "\u{2705} Allow"), ("deny", "\u{274c} Deny")];
+        if view.offers_always_allow() {
+            actions.push(("always", "\u{1f513} Always"));
+        }
+        let base = self.config.callback_url.trim_end_matches('/');
+        actions
+            .into_iter()
+            .map(|(action, label)| {
+                json!({
+                    "action": "http",
+                    "label": label,
+                    "url": format!("{base}{ACTIONS_PATH}{}/{action}", view.request_id()),
+                    "method": "POST",
+                    "headers": { "Authorization": format!("Bearer {token}") },
+                    "clear": true,
+                })
+            })
+            .collect()
+    }
+}
+
+impl ApprovalChannel for NtfyChannel {
+    fn name(&self) -> &'static str {
+        "ntfy"
+    }
+
+    fn send_request<'a>(
+        &'a self,
+        view: &'a RequestView,
+        _page: Page<'a>,
+    ) -> BoxFuture<'a, anyhow::Result<()>> {
+        Box::pin(async move {
+            if self.tokens.contains_key(&view.request_id()) {
+                return Ok(());
+            }
+            // Registered first: the phone may tap before the publish returns
+            let token = crate::http::new_token();
+            self.tokens.insert(view.request_id(), token.clone());
+            let published = self.publish(view, &token, false).await;
+            if published.is_err() {
+                self.tokens.remove(&view.request_id());
+            }
+            published
+        })
+    }
+
+    fn update_status<'a>(
+        &'a self,
+        view: &'a RequestView,
+        update: &'a StatusUpdate,
+    ) -> BoxFuture<'a, ()> {
+        Box::pin(async move {
+            match update {
+                // Published notifications stay as they are
+                StatusUpdate::Progress | StatusUpdate::Extended => {}
+                StatusUpdate::Reminder => {
+                    let Some(token) = self.tokens.get(&view.request_id()).map(|t| t.clone()) else {
+                        return;
+                    };
+                    if let Err(e) = self.publish(view, &token, true).await {
+                        tracing::warn!("Failed to publish ntfy reminder: {e}");
+                    }
+                }
+                StatusUpdate::Withdrawn(_) | StatusUpdate::Resolved(_) => {
+                    self.tokens.remove(&view.request_id());
+                }
+            }
+        })
+    }
+
+    fn decisions(&self) -> DecisionStream {
+        self.decisions.stream()
+    }
+
+    // Anyone subscribed to the topic decides alike
+    fn identifies_approvers(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::json_response;
+    use crate::http::test_support::{json_body, recording_server};
+    use crate::models::IpcRequest;
+    use futures::StreamExt;
+    use tokio::sync::mpsc;
+
+    /// Stands in for an ntfy server, reporting each publish as
+    /// `(authorization, body)`.
+    async fn fake_ntfy() -> (String, mpsc::UnboundedReceiver<(String, Value)>) {
+        let record = |request: &Request<Bytes>| {
+            let authorization = bearer_token(request).unwrap_or_default().to_string();
+            Some((authorization, json_body(request)))
+        };
+        let respond = |_: &Request<Bytes>| std::future::ready(json_response(&json!({"id": "n1"})));
+        let (url, published) = recording_server(record, respond).await;
+        (format!("{url}/"), published)
+    }
+
+    fn make_channel(server_url: &str) -> Arc<NtfyChannel> {
+        Arc::new(NtfyChannel::new(NtfyConfig {
+            server_url: server_url.to_string(),
+            topic: "reachout-test".to_string(),
+            access_token: Some("tk_publish".to_string()),
+            callback_url: "http://100.64.0.1:8792/".to_string(),
+            listen: "127.0.0.1:0".parse().unwrap(),
+        }))
+    }
+
+    fn make_view(suggestions: Vec<Value>) -> RequestView {
+        RequestView {
+            request: IpcRequest {
+                request_id: Uuid::new_v4(),
+                tool_name: "Bash".to_string(),
+                tool_input: json!({"command": "ls"}),
+                cwd: "/tmp/proj".to_string(),
+                session_id: "s".to_string(),
+                permission_suggestions: suggestions,
+                assistant_context: None,
+                first_prompt: None,
+            },
+            summary: "Bash: ls".to_string(),
+            deadline: Instant::now() + std::time::Duration::from_secs(150),
+            required_approvals: 1,
+            approvals: vec![],
+        }
+    }
+
+    fn page() -> Page<'static> {
+        Page {
+            tier: 0,
+            deliveries: &[],
+        }
+    }
+
+    /// The request the phone sends when `action` (as published) is tapped.
+    fn tap(action: &Value) -> Request<Bytes> {
+        let url: reqwest::Url = action["url"].as_str().unwrap().parse().unwrap();
+        Request::post(url.path())
+            .header(
+                "authorization",
+                action["headers"]["Authorization"].as_str().unwrap(),
+            )
+            .body(Bytes::new())
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn publishes_actions_with_request_token() {
+        let (url, mut published) = fake_ntfy().await;
+        let channel = make_channel(&url);
+        assert_eq!(channel.name(), "ntfy");
+        let view = make_view(vec![json!({})]);
+
+        channel.send_request(&view, page()).await.unwrap();
+        let (authorization, body) = published.recv().await.unwrap();
+        assert_eq!(authorization, "tk_publish");
+        assert_eq!(body["topic"], "reachout-test");
+        assert_eq!(body["title"], "Bash in proj");
+        let actions = body["actions"].as_array().unwrap();
+        assert_eq!(actions.len(), 3);
+        assert_eq!(
+            actions[0]["url"],
+            format!("http://100.64.0.1:8792/ntfy/{}/allow", view.request_id())
+        );
+        let token = channel.tokens.get(&view.request_id()).unwrap().clone();
+        assert_eq!(
+            actions[2]["headers"]["Authorization"],
+            format!("Bearer {token}")
+        );
+
+        channel.update_status(&view, &StatusUpdate::Reminder).await;
+        let (_, body) = published.recv().await.unwrap();
+        assert!(body["title"].as_str().unwrap().starts_with("\u{23f0}"));
+        assert_eq!(body["actions"][0]["headers"], actions[0]["headers"]);
+    }
+
+    #[tokio::test]
+    async fn taps_with_the_token_become_decisions() {
+        let (url, mut published) = fake_ntfy().await;
+        let channel = make_channel(&url);
+        let mut decisions = channel.decisions();
+        let view = make_view(vec![]);
+        channel.send_request(&view, page()).await.unwrap();
+        let (_, body) = published.recv().await.unwrap();
+        let deny = &body["actions"][1];
+
+        let mut forged = tap(deny);
+        forged.headers_mut().insert(
+            "authorization",
+            format!("Bearer {}", crate::http::new_token())
+                .parse()
+                .unwrap(),
+        );
+        let response = channel.handle_request(&forged).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let tapped = tokio::spawn({
+            let channel = channel.clone();
+            let request = tap(deny);
+            async move { channel.handle_request(&request).await }
+        });
+        let decision = decisions.next().await.unwrap();
+        assert_eq!(decision.request_id, view.request_id());
+        assert_eq!(decision.verdict, Verdict::Deny);
+        assert_eq!(decision.approver.id, "ntfy:reachout-test");
+        decision
+            .ack
+            .unwrap()
+            .send(DecisionOutcome::Resolved)
+            .unwrap();
+        assert_eq!(tapped.await.unwrap().status(), StatusCode::OK);
+
+        // Once resolved, the token no longer works
+        let status = StatusUpdate::Resolved("\u{274c} Denied".to_string());
+        channel.update_status(&view, &status).await;
+        let response = channel.handle_request(&tap(deny)).await;
+        assert_eq!(response.status(), StatusCode::GONE);
+    }
+}
@@ -0,0 +1,55 @@
+pub mod channel;
+
+use serde::Deserialize;
+use std::net::SocketAddr;
+
+/// Publishing requests as push notifications to an ntfy topic, answered
+/// from the notification's action buttons.
+#[derive(Debug, Deserialize, Clone)]
+pub struct NtfyConfig {
+    /// The ntfy server; a self-hosted one works the same.
+    #[serde(default = "default_server_url")]
+    pub server_url: String,
+    /// Topic the phone is subscribed to. Anyone who knows it can read the
+    /// requests, so pick something unguessable.
+    pub topic: String,
+    /// Access token, for servers that require one to publish.
+    pub access_token: Option<String>,
+    /// Base URL at which the phone reaches `listen` (e.g. a tailnet address).
+    pub callback_url: String,
+    /// Address of the action endpoint (`POST /ntfy/{request_id}/{action}`).
+    #[serde(default = "default_listen")]
+    pub listen: SocketAddr,
+}
+
+fn default_server_url() -> String {
+    "https://ntfy.sh".to_string()
+}
+
+fn default_listen() -> SocketAddr {
+    SocketAddr::from(([127, 0, 0, 1], 8792))
+}
+
+fn is_http_url(url: &str) -> bool {
+    url.starts_with("https://") || url.starts_with("http://")
+}
+
+impl NtfyConfig {
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if !is_http_url(&self.server_url) {
+            anyhow::bail!("ntfy server_url must be an http(s) URL");
+        }
+        let topic_ok = !self.topic.is_empty()
+            && self
+                .topic
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        if !topic_ok {
+            anyhow::bail!("ntfy topic must be letters, digits, '-' and '_'");
+        }
+        if !is_http_url(&self.callback_url) {
+            anyhow::bail!("ntfy callback_url must be an http(s) URL");
+        }
+        Ok(())
+    }
+}
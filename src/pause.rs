@@ -0,0 +1,67 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use uuid::Uuid;
+
+/// Paused sessions. Each pause gets its own id, used by the "Resume" button.
+#[derive(Default)]
+pub struct Pauses {
+    sessions: Mutex<HashMap<String, Uuid>>,
+}
+
+pub type PauseState = Arc<Pauses>;
+
+impl Pauses {
+    /// Pauses `session_id`, returning the pause id (the existing one if the
+    /// session is already paused).
+    pub fn pause(&self, session_id: &str) -> Uuid {
+        *self
+            .sessions
+            .lock()
+            .expect("pause lock poisoned")
+            .entry(session_id.to_string())
+            .or_insert_with(Uuid::new_v4)
+    }
+
+    /// Ends the pause with id `pause_id`, returning its session.
+    pub fn resume(&self, pause_id: Uuid) -> Option<String> {
+        let mut sessions = self.sessions.lock().expect("pause lock poisoned");
+        let session_id = sessions
+            .iter()
+            .find(|(_, id)| **id == pause_id)
+            .map(|(session_id, _)| session_id.clone())?;
+        sessions.remove(&session_id);
+        drop(sessions);
+        Some(session_id)
+    }
+
+    pub fn is_paused(&self, session_id: &str) -> bool {
+        self.sessions
+            .lock()
+            .expect("pause lock poisoned")
+            .contains_key(session_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pause_and_resume() {
+        let pauses = Pauses::default();
+        let id = pauses.pause("session-a");
+        assert!(pauses.is_paused("session-a"));
+        assert!(!pauses.is_paused("session-b"));
+
+        assert_eq!(pauses.resume(id).as_deref(), Some("session-a"));
+        assert!(!pauses.is_paused("session-a"));
+        assert!(pauses.resume(id).is_none());
+    }
+
+    #[test]
+    fn pausing_twice_keeps_the_same_id() {
+        let pauses = Pauses::default();
+        assert_eq!(pauses.pause("session-a"), pauses.pause("session-a"));
+        assert_ne!(pauses.pause("session-a"), pauses.pause("session-b"));
+    }
+}
@@ -0,0 +1,371 @@
+use chrono::{DateTime, Duration, NaiveTime, Utc};
+use chrono_tz::Tz;
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+
+const DEFAULT_QUIET_MESSAGE: &str = "The user is asleep; try again later or ask in the terminal.";
+const DEFAULT_DND_MESSAGE: &str =
+    "The user is in do-not-disturb mode; try again later or ask in the terminal.";
+
+/// What happens to requests for a chat while it is quiet.
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum QuietMode {
+    /// Deliver without a notification sound and let it time out if unanswered.
+    #[default]
+    Silent,
+    /// Don't deliver; deny straight away with a message for Claude.
+    Deny,
+}
+
+/// A daily quiet window in `timezone`. `start` after `end` wraps past midnight.
+#[derive(Debug, Deserialize, Clone)]
+pub struct QuietHours {
+    /// Chats this schedule applies to (empty = every allowed chat).
+    #[serde(default)]
+    pub chat_ids: Vec<i64>,
+    #[serde(default = "default_timezone")]
+    pub timezone: Tz,
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+    #[serde(default)]
+    pub mode: QuietMode,
+    /// Denial message for Claude in `deny` mode.
+    pub message: Option<String>,
+}
+
+const fn default_timezone() -> Tz {
+    Tz::UTC
+}
+
+impl QuietHours {
+    pub fn validate(&self, index: usize, allowed_chat_ids: &HashSet<i64>) -> anyhow::Result<()> {
+        if self.start == self.end {
+            anyhow::bail!("quiet_hours entry {index} must have different start and end");
+        }
+        if let Some(chat_id) = self
+            .chat_ids
+            .iter()
+            .find(|id| !allowed_chat_ids.contains(id))
+        {
+            anyhow::bail!(
+                "quiet_hours entry {index} references chat_id {chat_id} not in allowed_chat_ids"
+            );
+        }
+        Ok(())
+    }
+
+    fn applies_to(&self, chat_id: i64) -> bool {
+        self.chat_ids.is_empty() || self.chat_ids.contains(&chat_id)
+    }
+
+    fn contains(&self, now: DateTime<Utc>) -> bool {
+        let local = now.with_timezone(&self.timezone).time();
+        if self.start < self.end {
+            self.start <= local && local < self.end
+        } else {
+            local >= self.start || local < self.end
+        }
+    }
+}
+
+/// An ad-hoc `/dnd` window for one chat.
+#[derive(Debug, Clone, Copy)]
+pub struct DndWindow {
+    pub until: DateTime<Utc>,
+    pub mode: QuietMode,
+}
+
+/// A chat's quiet state at some instant.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuietStatus {
+    pub mode: QuietMode,
+    /// Denial message for Claude when `mode` is `Deny`.
+    pub message: String,
+}
+
+/// How a request is delivered to one chat.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Delivery {
+    pub chat_id: i64,
+    pub silent: bool,
+}
+
+/// Configured quiet hours plus ad-hoc `/dnd` windows.
+pub struct Quiet {
+    schedules: Vec<QuietHours>,
+    dnd: Mutex<HashMap<i64, DndWindow>>,
+}
+
+pub type QuietState = Arc<Quiet>;
+
+impl Quiet {
+    pub fn new(schedules: Vec<QuietHours>) -> Self {
+        Self {
+            schedules,
+            dnd: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// The quiet state of `chat_id` at `now`; an active `/dnd` window wins
+    /// over the schedule.
+    pub fn status(&self, chat_id: i64, now: DateTime<Utc>) -> Option<QuietStatus> {
+        if let Some(window) = self.dnd_window(chat_id, now) {
+            return Some(QuietStatus {
+                mode: window.mode,
+                message: DEFAULT_DND_MESSAGE.to_string(),
+            });
+        }
+        self.schedules
+            .iter()
+            .find(|s| s.applies_to(chat_id) && s.contains(now))
+            .map(|s| QuietStatus {
+                mode: s.mode,
+                message: s
+                    .message
+                    .clone()
+                    .unwrap_or_else(|| DEFAULT_QUIET_MESSAGE.to_string()),
+            })
+    }
+
+    /// Whether `chat_id` is quiet (in any mode) at `now`.
+    pub fn is_quiet(&self, chat_id: i64, now: DateTime<Utc>) -> bool {
+        self.status(chat_id, now).is_some()
+    }
+
+    /// Plans delivery to `chat_ids`: deny-mode chats are skipped and
+    /// silent-mode chats get no notification sound. When every chat is
+    /// skipped, returns the denial message to send back instead.
+    pub fn deliveries(
+        &self,
+        chat_ids: &[i64],
+        now: DateTime<Utc>,
+    ) -> Result<Vec<Delivery>, String> {
+        let mut deliveries = Vec::new();
+        let mut denial = None;
+        for &chat_id in chat_ids {
+            match self.status(chat_id, now) {
+                None => deliveries.push(Delivery {
+                    chat_id,
+                    silent: false,
+                }),
+                Some(QuietStatus {
+                    mode: QuietMode::Silent,
+                    ..
+                }) => deliveries.push(Delivery {
+                    chat_id,
+                    silent: true,
+                }),
+                Some(QuietStatus {
+                    mode: QuietMode::Deny,
+                    message,
+                }) => {
+                    denial.get_or_insert(message);
+                }
+            }
+        }
+        match denial {
+            Some(message) if deliveries.is_empty() => Err(message),
+            _ => Ok(deliveries),
+        }
+    }
+
+    /// Starts a `/dnd` window for `chat_id` lasting `duration` from `now`.
+    /// Returns `None` if the window would end past the end of time.
+    pub fn start_dnd(
+        &self,
+        chat_id: i64,
+        duration: Duration,
+        mode: QuietMode,
+        now: DateTime<Utc>,
+    ) -> Option<DndWindow> {
+        let window = DndWindow {
+            until: now.checked_add_signed(duration)?,
+            mode,
+        };
+        self.dnd
+            .lock()
+            .expect("dnd lock poisoned")
+            .insert(chat_id, window);
+        Some(window)
+    }
+
+    /// Ends the `/dnd` window for `chat_id`. Returns whether one was active.
+    pub fn end_dnd(&self, chat_id: i64, now: DateTime<Utc>) -> bool {
+        let active = self.dnd_window(chat_id, now).is_some();
+        self.dnd.lock().expect("dnd lock poisoned").remove(&chat_id);
+        active
+    }
+
+    pub fn dnd_window(&self, chat_id: i64, now: DateTime<Utc>) -> Option<DndWindow> {
+        let mut dnd = self.dnd.lock().expect("dnd lock poisoned");
+        match dnd.get(&chat_id) {
+            Some(window) if now < window.until => Some(*window),
+            Some(_) => {
+                dnd.remove(&chat_id);
+                None
+            }
+            None => None,
+        }
+    }
+}
+
+/// Longest duration [`parse_duration`] accepts.
+pub const MAX_DURATION_DAYS: i64 = 30;
+
+/// Parses durations like `2h`, `30m`, `1h30m` or a bare number of minutes,
+/// up to [`MAX_DURATION_DAYS`].
+pub fn parse_duration(text: &str) -> Option<Duration> {
+    let text = text.trim().to_ascii_lowercase();
+    let total = if let Ok(minutes) = text.parse::<i64>() {
+        Duration::try_minutes(minutes)?
+    } else {
+        let mut total = Duration::zero();
+        let mut number = String::new();
+        for c in text.chars() {
+            if c.is_ascii_digit() {
+                number.push(c);
+                continue;
+            }
+            let value: i64 = std::mem::take(&mut number).parse().ok()?;
+            let part = match c {
+                'd' => Duration::try_days(value)?,
+                'h' => Duration::try_hours(value)?,
+                'm' => Duration::try_minutes(value)?,
+                _ => return None,
+            };
+            total = total.checked_add(&part)?;
+        }
+        if !number.is_empty() {
+            return None;
+        }
+        total
+    };
+    (total > Duration::zero() && total <= Duration::days(MAX_DURATION_DAYS)).then_some(total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn schedule(start: (u32, u32), end: (u32, u32), mode: QuietMode) -> QuietHours {
+        QuietHours {
+            chat_ids: vec![],
+            timezone: chrono_tz::Europe::Madrid,
+            start: NaiveTime::from_hms_opt(start.0, start.1, 0).unwrap(),
+            end: NaiveTime::from_hms_opt(end.0, end.1, 0).unwrap(),
+            mode,
+            message: None,
+        }
+    }
+
+    fn madrid(h: u32, min: u32) -> DateTime<Utc> {
+        chrono_tz::Europe::Madrid
+            .with_ymd_and_hms(2026, 3, 10, h, min, 0)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    #[test]
+    fn overnight_window_wraps_midnight() {
+        let quiet = schedule((23, 0), (7, 0), QuietMode::Silent);
+        assert!(quiet.contains(madrid(23, 30)));
+        assert!(quiet.contains(madrid(3, 0)));
+        assert!(!quiet.contains(madrid(7, 0)));
+        assert!(!quiet.contains(madrid(12, 0)));
+    }
+
+    #[test]
+    fn daytime_window() {
+        let quiet = schedule((12, 0), (13, 0), QuietMode::Silent);
+        assert!(quiet.contains(madrid(12, 30)));
+        assert!(!quiet.contains(madrid(13, 0)));
+    }
+
+    #[test]
+    fn schedule_only_applies_to_listed_chats() {
+        let mut hours = schedule((0, 0), (23, 59), QuietMode::Deny);
+        hours.chat_ids = vec![1];
+        let quiet = Quiet::new(vec![hours]);
+        assert!(quiet.is_quiet(1, madrid(12, 0)));
+        assert!(!quiet.is_quiet(2, madrid(12, 0)));
+    }
+
+    #[test]
+    fn deliveries_skip_deny_chats_and_silence_others() {
+        let mut deny = schedule((0, 0), (23, 59), QuietMode::Deny);
+        deny.chat_ids = vec![1];
+        let mut silent = schedule((0, 0), (23, 59), QuietMode::Silent);
+        silent.chat_ids = vec![2];
+        let quiet = Quiet::new(vec![deny, silent]);
+
+        assert_eq!(
+            quiet.deliveries(&[1, 2, 3], madrid(12, 0)),
+            Ok(vec![
+                Delivery {
+                    chat_id: 2,
+                    silent: true
+                },
+                Delivery {
+                    chat_id: 3,
+                    silent: false
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn all_deny_chats_return_message() {
+        let mut hours = schedule((0, 0), (23, 59), QuietMode::Deny);
+        hours.message = Some("Asleep".to_string());
+        let quiet = Quiet::new(vec![hours]);
+        assert_eq!(
+            quiet.deliveries(&[1, 2], madrid(12, 0)),
+            Err("Asleep".to_string())
+        );
+    }
+
+    #[test]
+    fn dnd_overrides_schedule_and_expires() {
+        let quiet = Quiet::new(vec![]);
+        let now = madrid(12, 0);
+        quiet.start_dnd(1, Duration::hours(2), QuietMode::Deny, now);
+
+        let status = quiet.status(1, now + Duration::hours(1)).unwrap();
+        assert_eq!(status.mode, QuietMode::Deny);
+        assert!(status.message.contains("do-not-disturb"));
+        assert!(quiet.status(1, now + Duration::hours(2)).is_none());
+    }
+
+    #[test]
+    fn end_dnd_reports_active_window() {
+        let quiet = Quiet::new(vec![]);
+        let now = Utc::now();
+        assert!(!quiet.end_dnd(1, now));
+        quiet.start_dnd(1, Duration::minutes(30), QuietMode::Silent, now);
+        assert!(quiet.end_dnd(1, now));
+        assert!(!quiet.is_quiet(1, now));
+    }
+
+    #[test]
+    fn parse_duration_formats() {
+        assert_eq!(parse_duration("2h"), Some(Duration::hours(2)));
+        assert_eq!(parse_duration("1h30m"), Some(Duration::minutes(90)));
+        assert_eq!(parse_duration("45"), Some(Duration::minutes(45)));
+        assert_eq!(parse_duration("1d"), Some(Duration::days(1)));
+        assert_eq!(parse_duration("0"), None);
+        assert_eq!(parse_duration("2x"), None);
+        assert_eq!(parse_duration("h"), None);
+        assert_eq!(parse_duration("2h5"), None);
+        assert_eq!(parse_duration("30d"), Some(Duration::days(30)));
+        assert_eq!(parse_duration("30d1m"), None);
+        assert_eq!(parse_duration("99999999999d"), None);
+        assert_eq!(parse_duration("99999999999999999"), None);
+        assert_eq!(
+            parse_duration("9223372036854775807m9223372036854775807m"),
+            None
+        );
+    }
+}
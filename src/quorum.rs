@@ -0,0 +1,149 @@
+use crate::models::{Approver, IpcRequest};
+use crate::telegram::formatter::escape_html;
+use glob::Pattern;
+use serde::Deserialize;
+
+/// Requires `approvals` distinct users to allow matching requests.
+/// Omitted patterns match anything.
+#[derive(Debug, Deserialize, Clone)]
+pub struct QuorumRule {
+    /// Glob matched against the tool name (e.g. `"Bash"`).
+    pub tool: Option<String>,
+    /// Glob matched against `tool_input.command` (e.g. `"*deploy*"`).
+    pub command: Option<String>,
+    pub approvals: usize,
+}
+
+impl QuorumRule {
+    pub fn validate(&self, index: usize) -> anyhow::Result<()> {
+        if self.approvals < 2 {
+            anyhow::bail!("quorum rule {index} must require at least 2 approvals");
+        }
+        for pattern in [&self.tool, &self.command].into_iter().flatten() {
+            if let Err(e) = Pattern::new(pattern) {
+                anyhow::bail!("quorum rule {index} has invalid glob {pattern:?}: {e}");
+            }
+        }
+        Ok(())
+    }
+
+    fn matches(&self, request: &IpcRequest) -> bool {
+        let glob_matches =
+            |pattern: &str, value: &str| Pattern::new(pattern).is_ok_and(|p| p.matches(value));
+        let tool_ok = self
+            .tool
+            .as_deref()
+            .is_none_or(|p| glob_matches(p, &request.tool_name));
+        let command_ok = self.command.as_deref().is_none_or(|p| {
+            request
+                .tool_input
+                .get("command")
+                .and_then(|c| c.as_str())
+                .is_some_and(|command| glob_matches(p, command))
+        });
+        tool_ok && command_ok
+    }
+}
+
+/// Number of distinct approvals `request` needs; the strictest matching rule wins.
+pub fn required_approvals(rules: &[QuorumRule], request: &IpcRequest) -> usize {
+    rules
+        .iter()
+        .filter(|rule| rule.matches(request))
+        .map(|rule| rule.approvals)
+        .max()
+        .unwrap_or(1)
+}
+
+/// Progress line (HTML) shown on the message while a quorum is being collected.
+pub fn format_progress(approvals: &[Approver], required: usize) -> String {
+    let names: Vec<String> = approvals.iter().map(|a| escape_html(&a.name)).collect();
+    let mut line = format!("\u{1f5f3} {}/{required} approvals", approvals.len());
+    if !names.is_empty() {
+        line.push_str(": ");
+        line.push_str(&names.join(", "));
+    }
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    fn make_request(tool_name: &str, tool_input: serde_json::Value) -> IpcRequest {
+        IpcRequest {
+            request_id: Uuid::new_v4(),
+            tool_name: tool_name.to_string(),
+            tool_input,
+            cwd: "/tmp".to_string(),
+            session_id: "s".to_string(),
+            permission_suggestions: vec![],
+            assistant_context: None,
+            first_prompt: None,
+        }
+    }
+
+    fn rule(tool: Option<&str>, command: Option<&str>, approvals: usize) -> QuorumRule {
+        QuorumRule {
+            tool: tool.map(str::to_string),
+            command: command.map(str::to_string),
+            approvals,
+        }
+    }
+
+    #[test]
+    fn unmatched_request_needs_one_approval() {
+        let rules = [rule(Some("Bash"), Some("*deploy*"), 2)];
+        let req = make_request("Bash", serde_json::json!({"command": "ls"}));
+        assert_eq!(required_approvals(&rules, &req), 1);
+    }
+
+    #[test]
+    fn command_pattern_matches() {
+        let rules = [rule(Some("Bash"), Some("*deploy*"), 2)];
+        let req = make_request("Bash", serde_json::json!({"command": "./deploy.sh prod"}));
+        assert_eq!(required_approvals(&rules, &req), 2);
+    }
+
+    #[test]
+    fn command_pattern_requires_a_command() {
+        let rules = [rule(None, Some("*migrate*"), 2)];
+        let req = make_request("Write", serde_json::json!({"file_path": "/migrate.sql"}));
+        assert_eq!(required_approvals(&rules, &req), 1);
+    }
+
+    #[test]
+    fn strictest_rule_wins() {
+        let rules = [rule(Some("Bash"), None, 2), rule(None, Some("*prod*"), 3)];
+        let req = make_request("Bash", serde_json::json!({"command": "deploy prod"}));
+        assert_eq!(required_approvals(&rules, &req), 3);
+    }
+
+    #[test]
+    fn single_approval_rule_rejected() {
+        assert!(rule(Some("Bash"), None, 1).validate(0).is_err());
+    }
+
+    #[test]
+    fn progress_lists_approvers() {
+        let approvals = [Approver {
+            id: "telegram:1".to_string(),
+            name: "alice".to_string(),
+        }];
+        assert_eq!(
+            format_progress(&approvals, 2),
+            "\u{1f5f3} 1/2 approvals: alice"
+        );
+        assert_eq!(format_progress(&[], 2), "\u{1f5f3} 0/2 approvals");
+    }
+
+    #[test]
+    fn progress_escapes_names() {
+        let approvals = [Approver {
+            id: "telegram:1".to_string(),
+            name: "<b>".to_string(),
+        }];
+        assert!(format_progress(&approvals, 2).ends_with("&lt;b&gt;"));
+    }
+}
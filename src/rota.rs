@@ -0,0 +1,330 @@
+use crate::config::{Config, EscalationTier};
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use serde::Deserialize;
+use std::sync::{Arc, Mutex};
+
+/// How often the on-call duty passes to the next member.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum RotaSchedule {
+    Daily,
+    Weekly,
+}
+
+impl RotaSchedule {
+    const fn period(self) -> Duration {
+        match self {
+            Self::Daily => Duration::days(1),
+            Self::Weekly => Duration::days(7),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Clone, PartialEq, Eq)]
+pub struct RotaMember {
+    pub name: String,
+    pub chat_id: i64,
+}
+
+/// A fixed window (in the rota's timezone) during which `name` is on call
+/// regardless of the regular schedule.
+#[derive(Debug, Deserialize, Clone)]
+pub struct RotaOverride {
+    pub name: String,
+    pub start: NaiveDateTime,
+    pub end: NaiveDateTime,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct RotaConfig {
+    pub timezone: Tz,
+    pub schedule: RotaSchedule,
+    /// The first member's shift starts on this date at `handover_time`.
+    pub start_date: NaiveDate,
+    #[serde(default = "default_handover_time")]
+    pub handover_time: NaiveTime,
+    pub members: Vec<RotaMember>,
+    #[serde(default)]
+    pub overrides: Vec<RotaOverride>,
+    /// How long to wait for the on-call member before paging the `escalation` tiers.
+    #[serde(default)]
+    pub escalate_after_seconds: u64,
+}
+
+const fn default_handover_time() -> NaiveTime {
+    match NaiveTime::from_hms_opt(9, 0, 0) {
+        Some(time) => time,
+        None => unreachable!(),
+    }
+}
+
+impl RotaConfig {
+    pub fn validate(
+        &self,
+        allowed_chat_ids: &std::collections::HashSet<i64>,
+    ) -> anyhow::Result<()> {
+        if self.members.is_empty() {
+            anyhow::bail!("rota.members must have at least one entry");
+        }
+        for member in &self.members {
+            if !allowed_chat_ids.contains(&member.chat_id) {
+                anyhow::bail!(
+                    "rota member {} has chat_id {} not in allowed_chat_ids",
+                    member.name,
+                    member.chat_id
+                );
+            }
+        }
+        for ov in &self.overrides {
+            if self.member_by_name(&ov.name).is_none() {
+                anyhow::bail!("rota override references unknown member {}", ov.name);
+            }
+            if ov.start >= ov.end {
+                anyhow::bail!("rota override for {} must end after it starts", ov.name);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn member_by_name(&self, name: &str) -> Option<&RotaMember> {
+        self.members
+            .iter()
+            .find(|m| m.name.eq_ignore_ascii_case(name))
+    }
+
+    const fn anchor(&self) -> NaiveDateTime {
+        self.start_date.and_time(self.handover_time)
+    }
+
+    /// Index of the shift containing `local` (negative before `start_date`).
+    fn shift_index(&self, local: NaiveDateTime) -> i64 {
+        let elapsed = (local - self.anchor()).num_seconds();
+        elapsed.div_euclid(self.schedule.period().num_seconds())
+    }
+
+    /// Who is on call at `now`, taking configured overrides into account.
+    pub fn member_at(&self, now: DateTime<Utc>) -> &RotaMember {
+        let local = now.with_timezone(&self.timezone).naive_local();
+
+        if let Some(member) = self
+            .overrides
+            .iter()
+            .find(|ov| ov.start <= local && local < ov.end)
+            .and_then(|ov| self.member_by_name(&ov.name))
+        {
+            return member;
+        }
+
+        let len = i64::try_from(self.members.len()).unwrap_or(i64::MAX);
+        let index = usize::try_from(self.shift_index(local).rem_euclid(len)).unwrap_or(0);
+        &self.members[index]
+    }
+
+    /// The next regular handover after `now`.
+    pub fn next_handover(&self, now: DateTime<Utc>) -> DateTime<Utc> {
+        let local = now.with_timezone(&self.timezone).naive_local();
+        let shift = self.shift_index(local);
+        let next = self.anchor() + self.schedule.period() * i32::try_from(shift + 1).unwrap_or(0);
+        local_to_utc(self.timezone, next)
+    }
+}
+
+/// Resolves a local wall-clock time to UTC, skipping forward over DST gaps.
+fn local_to_utc(tz: Tz, local: NaiveDateTime) -> DateTime<Utc> {
+    tz.from_local_datetime(&local)
+        .earliest()
+        .or_else(|| {
+            tz.from_local_datetime(&(local + Duration::hours(1)))
+                .earliest()
+        })
+        .map_or_else(
+            || Utc.from_utc_datetime(&local),
+            |dt| dt.with_timezone(&Utc),
+        )
+}
+
+/// A manual `/oncall` swap, valid until the next regular handover.
+#[derive(Debug, Clone)]
+struct Swap {
+    member: RotaMember,
+    until: DateTime<Utc>,
+}
+
+/// Runtime on-call state: the configured rota plus any ad-hoc swap.
+pub struct OnCall {
+    rota: Option<RotaConfig>,
+    swap: Mutex<Option<Swap>>,
+}
+
+pub type OnCallState = Arc<OnCall>;
+
+impl OnCall {
+    pub const fn new(rota: Option<RotaConfig>) -> Self {
+        Self {
+            rota,
+            swap: Mutex::new(None),
+        }
+    }
+
+    pub const fn rota(&self) -> Option<&RotaConfig> {
+        self.rota.as_ref()
+    }
+
+    /// Who is on call at `now`, or `None` when no rota is configured.
+    pub fn current(&self, now: DateTime<Utc>) -> Option<RotaMember> {
+        let rota = self.rota.as_ref()?;
+        {
+            let mut swap = self.swap.lock().expect("on-call lock poisoned");
+            match swap.as_ref() {
+                Some(active) if now < active.until => return Some(active.member.clone()),
+                Some(_) => *swap = None,
+                None => {}
+            }
+        }
+        Some(rota.member_at(now).clone())
+    }
+
+    /// Hands the duty to `name` until the next regular handover.
+    pub fn swap_to(&self, name: &str, now: DateTime<Utc>) -> Option<(RotaMember, DateTime<Utc>)> {
+        let rota = self.rota.as_ref()?;
+        let member = rota.member_by_name(name)?.clone();
+        let until = rota.next_handover(now);
+        *self.swap.lock().expect("on-call lock poisoned") = Some(Swap {
+            member: member.clone(),
+            until,
+        });
+        Some((member, until))
+    }
+
+    /// Notification tiers for a request arriving at `now`: the on-call member
+    /// first, then any configured escalation tiers. Without a rota this is
+    /// just `Config::notification_tiers`.
+    pub fn notification_tiers(&self, config: &Config, now: DateTime<Utc>) -> Vec<EscalationTier> {
+        let (Some(rota), Some(member)) = (self.rota.as_ref(), self.current(now)) else {
+            return config.notification_tiers();
+        };
+        let mut tiers = vec![EscalationTier {
+            chat_ids: vec![member.chat_id],
+            escalate_after_seconds: rota.escalate_after_seconds,
+        }];
+        tiers.extend(config.escalation.iter().cloned());
+        tiers
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_rota(schedule: RotaSchedule) -> RotaConfig {
+        RotaConfig {
+            timezone: chrono_tz::Europe::Madrid,
+            schedule,
+            start_date: NaiveDate::from_ymd_opt(2026, 1, 5).unwrap(),
+            handover_time: default_handover_time(),
+            members: vec![
+                RotaMember {
+                    name: "alice".to_string(),
+                    chat_id: 1,
+                },
+                RotaMember {
+                    name: "bob".to_string(),
+                    chat_id: 2,
+                },
+            ],
+            overrides: vec![],
+            escalate_after_seconds: 0,
+        }
+    }
+
+    fn madrid(y: i32, m: u32, d: u32, h: u32, min: u32) -> DateTime<Utc> {
+        chrono_tz::Europe::Madrid
+            .with_ymd_and_hms(y, m, d, h, min, 0)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    #[test]
+    fn weekly_rotation_alternates_members() {
+        let rota = make_rota(RotaSchedule::Weekly);
+        assert_eq!(rota.member_at(madrid(2026, 1, 5, 9, 0)).name, "alice");
+        assert_eq!(rota.member_at(madrid(2026, 1, 12, 8, 59)).name, "alice");
+        assert_eq!(rota.member_at(madrid(2026, 1, 12, 9, 0)).name, "bob");
+        assert_eq!(rota.member_at(madrid(2026, 1, 19, 10, 0)).name, "alice");
+    }
+
+    #[test]
+    fn daily_rotation_alternates_members() {
+        let rota = make_rota(RotaSchedule::Daily);
+        assert_eq!(rota.member_at(madrid(2026, 1, 5, 12, 0)).name, "alice");
+        assert_eq!(rota.member_at(madrid(2026, 1, 6, 12, 0)).name, "bob");
+        assert_eq!(rota.member_at(madrid(2026, 1, 7, 8, 0)).name, "bob");
+    }
+
+    #[test]
+    fn rotation_before_start_date_wraps() {
+        let rota = make_rota(RotaSchedule::Daily);
+        assert_eq!(rota.member_at(madrid(2026, 1, 4, 12, 0)).name, "bob");
+        assert_eq!(rota.member_at(madrid(2026, 1, 3, 12, 0)).name, "alice");
+    }
+
+    #[test]
+    fn override_takes_precedence() {
+        let mut rota = make_rota(RotaSchedule::Weekly);
+        rota.overrides.push(RotaOverride {
+            name: "bob".to_string(),
+            start: NaiveDate::from_ymd_opt(2026, 1, 6)
+                .unwrap()
+                .and_hms_opt(0, 0, 0)
+                .unwrap(),
+            end: NaiveDate::from_ymd_opt(2026, 1, 7)
+                .unwrap()
+                .and_hms_opt(0, 0, 0)
+                .unwrap(),
+        });
+        assert_eq!(rota.member_at(madrid(2026, 1, 6, 12, 0)).name, "bob");
+        assert_eq!(rota.member_at(madrid(2026, 1, 7, 12, 0)).name, "alice");
+    }
+
+    #[test]
+    fn next_handover_is_end_of_current_shift() {
+        let rota = make_rota(RotaSchedule::Weekly);
+        assert_eq!(
+            rota.next_handover(madrid(2026, 1, 7, 15, 0)),
+            madrid(2026, 1, 12, 9, 0)
+        );
+    }
+
+    #[test]
+    fn swap_lasts_until_next_handover() {
+        let on_call = OnCall::new(Some(make_rota(RotaSchedule::Weekly)));
+        let now = madrid(2026, 1, 7, 15, 0);
+        let (member, until) = on_call.swap_to("Bob", now).unwrap();
+        assert_eq!(member.name, "bob");
+        assert_eq!(until, madrid(2026, 1, 12, 9, 0));
+        assert_eq!(on_call.current(now).unwrap().name, "bob");
+        // After handover the regular schedule resumes: bob's own week, then
+        // alice's
+        assert_eq!(
+            on_call.current(madrid(2026, 1, 12, 9, 0)).unwrap().name,
+            "bob"
+        );
+        assert_eq!(
+            on_call.current(madrid(2026, 1, 19, 9, 0)).unwrap().name,
+            "alice"
+        );
+    }
+
+    #[test]
+    fn swap_to_unknown_member_fails() {
+        let on_call = OnCall::new(Some(make_rota(RotaSchedule::Weekly)));
+        assert!(on_call.swap_to("carol", Utc::now()).is_none());
+    }
+
+    #[test]
+    fn no_rota_means_no_on_call() {
+        let on_call = OnCall::new(None);
+        assert!(on_call.current(Utc::now()).is_none());
+    }
+}
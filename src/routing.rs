@@ -0,0 +1,162 @@
+use crate::config::{Config, EscalationTier};
+use crate::models::IpcRequest;
+use crate::rota::OnCall;
+use glob::Pattern;
+use serde::Deserialize;
+use std::time::Duration;
+
+const GIT_REMOTE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Sends requests from matching projects to a dedicated set of chats.
+/// When both patterns are set, both must match.
+#[derive(Debug, Deserialize, Clone)]
+pub struct Route {
+    /// Glob matched against the request's working directory.
+    pub cwd: Option<String>,
+    /// Glob matched against the `origin` remote URL of the repository at `cwd`.
+    pub git_remote: Option<String>,
+    pub chat_ids: Vec<i64>,
+}
+
+impl Route {
+    pub fn validate(
+        &self,
+        index: usize,
+        allowed_chat_ids: &std::collections::HashSet<i64>,
+    ) -> anyhow::Result<()> {
+        if self.cwd.is_none() && self.git_remote.is_none() {
+            anyhow::bail!("route {index} must set cwd or git_remote");
+        }
+        for pattern in [&self.cwd, &self.git_remote].into_iter().flatten() {
+            if let Err(e) = Pattern::new(pattern) {
+                anyhow::bail!("route {index} has invalid glob {pattern:?}: {e}");
+            }
+        }
+        if self.chat_ids.is_empty() {
+            anyhow::bail!("route {index} must have at least one chat_id");
+        }
+        if let Some(chat_id) = self
+            .chat_ids
+            .iter()
+            .find(|id| !allowed_chat_ids.contains(id))
+        {
+            anyhow::bail!("route {index} references chat_id {chat_id} not in allowed_chat_ids");
+        }
+        Ok(())
+    }
+
+    fn matches(&self, cwd: &str, git_remote: Option<&str>) -> bool {
+        let glob_matches =
+            |pattern: &str, value: &str| Pattern::new(pattern).is_ok_and(|p| p.matches(value));
+        let cwd_ok = self.cwd.as_deref().is_none_or(|p| glob_matches(p, cwd));
+        let remote_ok = self
+            .git_remote
+            .as_deref()
+            .is_none_or(|p| git_remote.is_some_and(|url| glob_matches(p, url)));
+        cwd_ok && remote_ok
+    }
+}
+
+/// Returns the first route matching the request's project, if any.
+fn find_route<'a>(routes: &'a [Route], cwd: &str, git_remote: Option<&str>) -> Option<&'a Route> {
+    routes.iter().find(|route| route.matches(cwd, git_remote))
+}
+
+/// Decides who gets notified about `request`: a matching project route wins,
+/// otherwise the on-call rota and escalation chain apply.
+pub async fn notification_tiers(
+    config: &Config,
+    on_call: &OnCall,
+    request: &IpcRequest,
+) -> Vec<EscalationTier> {
+    if !config.routes.is_empty() {
+        let git_remote = if config.routes.iter().any(|r| r.git_remote.is_some()) {
+            git_remote_url(&request.cwd).await
+        } else {
+            None
+        };
+        if let Some(route) = find_route(&config.routes, &request.cwd, git_remote.as_deref()) {
+            return vec![EscalationTier {
+                chat_ids: route.chat_ids.clone(),
+                escalate_after_seconds: 0,
+            }];
+        }
+    }
+    on_call.notification_tiers(config, chrono::Utc::now())
+}
+
+/// Reads the `origin` remote URL of the repository containing `cwd`.
+/// Returns `None` if `cwd` is not a git repository or git is unavailable.
+async fn git_remote_url(cwd: &str) -> Option<String> {
+    let output = tokio::process::Command::new("git")
+        .args(["-C", cwd, "remote", "get-url", "origin"])
+        .kill_on_drop(true)
+        .output();
+    let output = tokio::time::timeout(GIT_REMOTE_TIMEOUT, output)
+        .await
+        .ok()?
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let url = String::from_utf8(output.stdout).ok()?;
+    let url = url.trim();
+    (!url.is_empty()).then(|| url.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn route(cwd: Option<&str>, git_remote: Option<&str>, chat_id: i64) -> Route {
+        Route {
+            cwd: cwd.map(str::to_string),
+            git_remote: git_remote.map(str::to_string),
+            chat_ids: vec![chat_id],
+        }
+    }
+
+    #[test]
+    fn cwd_glob_matches_nested_directories() {
+        let routes = [route(Some("/home/me/work/*"), None, 1)];
+        assert!(find_route(&routes, "/home/me/work/api", None).is_some());
+        assert!(find_route(&routes, "/home/me/work/api/sub", None).is_some());
+        assert!(find_route(&routes, "/home/me/hobby/game", None).is_none());
+    }
+
+    #[test]
+    fn git_remote_glob_requires_a_remote() {
+        let routes = [route(None, Some("*github.com?acme/*"), 1)];
+        assert!(find_route(&routes, "/x", Some("git@github.com:acme/api.git")).is_some());
+        assert!(find_route(&routes, "/x", Some("https://github.com/acme/api")).is_some());
+        assert!(find_route(&routes, "/x", Some("https://github.com/me/toy")).is_none());
+        assert!(find_route(&routes, "/x", None).is_none());
+    }
+
+    #[test]
+    fn both_patterns_must_match() {
+        let routes = [route(Some("/work/*"), Some("*acme*"), 1)];
+        assert!(find_route(&routes, "/work/api", Some("acme/api")).is_some());
+        assert!(find_route(&routes, "/work/api", Some("me/api")).is_none());
+        assert!(find_route(&routes, "/hobby/api", Some("acme/api")).is_none());
+    }
+
+    #[test]
+    fn first_matching_route_wins() {
+        let routes = [route(Some("/work/*"), None, 1), route(Some("/*"), None, 2)];
+        assert_eq!(
+            find_route(&routes, "/work/api", None).unwrap().chat_ids,
+            [1]
+        );
+        assert_eq!(
+            find_route(&routes, "/hobby/game", None).unwrap().chat_ids,
+            [2]
+        );
+    }
+
+    #[tokio::test]
+    async fn git_remote_url_of_non_repo_is_none() {
+        let tmp = tempfile::tempdir().unwrap();
+        assert!(git_remote_url(tmp.path().to_str().unwrap()).await.is_none());
+    }
+}
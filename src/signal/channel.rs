@@ -0,0 +1,486 @@
+use crate::channel::{
+    ApprovalChannel, DecisionQueue, DecisionStream, Page, RequestView, StatusUpdate, Verdict,
+};
+use crate::models::Approver;
+use crate::signal::SignalConfig;
+use crate::signal::message;
+use crate::signal::rpc::SignalRpc;
+use dashmap::DashMap;
+use futures::future::BoxFuture;
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::Instant;
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
+
+/// Pause after losing the daemon before connecting again.
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// The `receive` notification's params, as far as they matter here.
+#[derive(Debug, Deserialize)]
+struct Receive {
+    envelope: Envelope,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Envelope {
+    source_number: Option<String>,
+    source_name: Option<String>,
+    data_message: Option<DataMessage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DataMessage {
+    timestamp: u64,
+    message: Option<String>,
+    quote: Option<Quote>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Quote {
+    /// Timestamp of the quoted message, which identifies it.
+    id: u64,
+}
+
+/// A request's first message.
+#[derive(Debug)]
+struct Posted {
+    timestamp: u64,
+    offers_always_allow: bool,
+}
+
+/// Signal as an approval channel, through a local signal-cli daemon. Signal
+/// messages can't be edited reliably, so replies quote the request instead:
+/// answers quote it too, which tells them apart.
+pub struct SignalChannel {
+    rpc: SignalRpc,
+    config: SignalConfig,
+    posted: DashMap<Uuid, Posted>,
+    /// Timestamp of every message sent for an open request, so quoting any
+    /// of them (e.g. a reminder) answers it.
+    sent: DashMap<u64, Uuid>,
+    decisions: DecisionQueue,
+}
+
+pub type SignalState = Arc<SignalChannel>;
+
+impl SignalChannel {
+    pub fn new(config: SignalConfig) -> Self {
+        Self {
+            rpc: SignalRpc::new(&config.account),
+            config,
+            posted: DashMap::new(),
+            sent: DashMap::new(),
+            decisions: DecisionQueue::default(),
+        }
+    }
+
+    /// Stays connected to the daemon, handling incoming messages, until
+    /// `cancel_token` fires.
+    pub async fn run(self: Arc<Self>, cancel_token: CancellationToken) {
+        loop {
+            match self.rpc.connect(&self.config.socket_path).await {
+                Ok(mut lines) => {
+                    tracing::info!("Connected to signal-cli");
+                    loop {
+                        let line = tokio::select! {
+                            () = cancel_token.cancelled() => return,
+                            line = lines.next_line() => line,
+                        };
+                        let line = match line {
+                            Ok(Some(line)) => line,
+                            Ok(None) => break,
+                            Err(e) => {
+                                tracing::warn!("Lost signal-cli: {e}");
+                                break;
+                            }
+                        };
+                        if let Some((method, params)) = self.rpc.dispatch(&line)
+                            && method == "receive"
+                        {
+                            // Deciding waits for status updates that call
+                            // signal-cli, whose answers this loop reads
+                            let channel = self.clone();
+                            tokio::spawn(async move { channel.handle_receive(params).await });
+                        }
+                    }
+                }
+                Err(e) => tracing::warn!(
+                    "Failed to connect to signal-cli at {}: {e}",
+                    self.config.socket_path.display()
+                ),
+            }
+            self.rpc.disconnect().await;
+            tokio::select! {
+                () = cancel_token.cancelled() => return,
+                () = tokio::time::sleep(RECONNECT_DELAY) => {}
+            }
+        }
+    }
+
+    async fn handle_receive(&self, params: Value) {
+        let Ok(Receive { envelope }) = serde_json::from_value::<Receive>(params) else {
+            return;
+        };
+        let (Some(number), Some(data)) = (envelope.source_number, envelope.data_message) else {
+            return;
+        };
+        let Some(text) = data.message.filter(|text| !text.trim().is_empty()) else {
+            return;
+        };
+        if !self.config.numbers.contains(&number) {
+            tracing::warn!(number = %number, "Signal message from an unknown number");
+            return;
+        }
+        let feedback = |text: &'static str| self.feedback(&number, data.timestamp, text);
+
+        let request_id = match &data.quote {
+            Some(quote) => self.sent.get(&quote.id).map(|id| *id),
+            // Without a quote, only an answer to the one open request is
+            // clear, and only if it was written after that request was sent:
+            // otherwise it may be meant for one answered in the meantime
+            None if self.posted.len() == 1 => self
+                .posted
+                .iter()
+                .next()
+                .filter(|posted| posted.timestamp < data.timestamp)
+                .map(|posted| *posted.key()),
+            None => None,
+        };
+        let Some(request_id) = request_id else {
+            if data.quote.is_some() {
+                feedback("This request has already been handled").await;
+            } else if !self.posted.is_empty() {
+                feedback("Reply to the request you are answering").await;
+            }
+            return;
+        };
+        let verdict = message::parse_reply(&text);
+        let offers_always_allow = self
+            .posted
+            .get(&request_id)
+            .is_some_and(|posted| posted.offers_always_allow);
+        if verdict == Verdict::AlwaysAllow && !offers_always_allow {
+            feedback("Always Allow isn't offered for this request").await;
+            return;
+        }
+
+        let approver = Approver {
+            id: format!("signal:{number}"),
+            name: envelope.source_name.unwrap_or_else(|| number.clone()),
+        };
+        let outcome = self.decisions.decide(request_id, verdict, approver).await;
+        if let Some(text) = outcome.feedback() {
+            feedback(text).await;
+        }
+    }
+
+    /// Answers `number`'s message sent at `timestamp`, quoting it.
+    async fn feedback(&self, number: &str, timestamp: u64, text: &str) {
+        let params = json!({
+            "recipient": [number],
+            "message": text,
+            "quoteTimestamp": timestamp,
+            "quoteAuthor": number,
+        });
+        if let Err(e) = self.rpc.call("send", params).await {
+            tracing::warn!("Failed to send Signal feedback: {e}");
+        }
+    }
+
+    /// Sends `text` to every number, quoting the bot's own message sent at
+    /// `quote`, and returns the new message's timestamp.
+    async fn send(&self, text: &str, quote: Option<u64>) -> anyhow::Result<u64> {
+        let mut params = json!({
+            "recipient": self.config.numbers,
+            "message": text,
+        });
+        if let Some(quote) = quote {
+            params["quoteTimestamp"] = json!(quote);
+            params["quoteAuthor"] = json!(self.config.account);
+        }
+        let result = self.rpc.call("send", params).await?;
+        result["timestamp"]
+            .as_u64()
+            .ok_or_else(|| anyhow::anyhow!("signal-cli send returned no timestamp"))
+    }
+}
+
+impl ApprovalChannel for SignalChannel {
+    fn name(&self) -> &'static str {
+        "Signal"
+    }
+
+    fn send_request<'a>(
+        &'a self,
+        view: &'a RequestView,
+        _page: Page<'a>,
+    ) -> BoxFuture<'a, anyhow::Result<()>> {
+        Box::pin(async move {
+            if self.posted.contains_key(&view.request_id()) {
+                return Ok(());
+            }
+            let text = message::request_text(view, Instant::now());
+            let timestamp = self.send(&text, None).await?;
+            self.sent.insert(timestamp, view.request_id());
+            self.posted.insert(
+                view.request_id(),
+                Posted {
+                    timestamp,
+                    offers_always_allow: view.offers_always_allow(),
+                },
+            );
+            Ok(())
+        })
+    }
+
+    fn update_status<'a>(
+        &'a self,
+        view: &'a RequestView,
+        update: &'a StatusUpdate,
+    ) -> BoxFuture<'a, ()> {
+        Box::pin(async move {
+            let Some(timestamp) = self.posted.get(&view.request_id()).map(|p| p.timestamp) else {
+                return;
+            };
+            match update {
+                StatusUpdate::Progress | StatusUpdate::Extended => {}
+                StatusUpdate::Reminder => {
+                    let text = format!("\u{23f0} Still waiting: {}", view.summary);
+                    match self.send(&text, Some(timestamp)).await {
+                        Ok(reminder) => {
+                            self.sent.insert(reminder, view.request_id());
+                        }
+                        Err(e) => tracing::warn!("Failed to send Signal reminder: {e}"),
+                    }
+                }
+                StatusUpdate::Withdrawn(status) | StatusUpdate::Resolved(status) => {
+                    self.posted.remove(&view.request_id());
+                    self.sent.retain(|_, id| *id != view.request_id());
+                    if let Err(e) = self.send(status, Some(timestamp)).await {
+                        tracing::warn!("Failed to send Signal status: {e}");
+                    }
+                }
+            }
+        })
+    }
+
+    fn decisions(&self) -> DecisionStream {
+        self.decisions.stream()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::channel::DecisionOutcome;
+    use crate::models::IpcRequest;
+    use futures::StreamExt;
+    use std::path::PathBuf;
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::UnixListener;
+    use tokio::sync::mpsc;
+
+    const BOT: &str = "+15550000000";
+    const ALICE: &str = "+15551111111";
+
+    /// Stands in for `signal-cli daemon --socket`: reports each call as
+    /// `(method, params)`, answers sends with timestamps 1000, 1001, ...,
+    /// and writes whatever is pushed to the returned sender.
+    fn fake_signal_cli(
+        dir: &std::path::Path,
+    ) -> (
+        PathBuf,
+        mpsc::UnboundedReceiver<(String, Value)>,
+        mpsc::UnboundedSender<Value>,
+    ) {
+        let path = dir.join("socket");
+        let listener = UnixListener::bind(&path).unwrap();
+        let (calls_tx, calls_rx) = mpsc::unbounded_channel();
+        let (push_tx, mut push_rx) = mpsc::unbounded_channel::<Value>();
+        let out_tx = push_tx.clone();
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let (reader, mut writer) = stream.into_split();
+            tokio::spawn(async move {
+                while let Some(message) = push_rx.recv().await {
+                    let line = format!("{message}\n");
+                    if writer.write_all(line.as_bytes()).await.is_err() {
+                        break;
+                    }
+                }
+            });
+            let mut lines = BufReader::new(reader).lines();
+            let mut timestamp = 1000;
+            while let Ok(Some(line)) = lines.next_line().await {
+                let call: Value = serde_json::from_str(&line).unwrap();
+                let method = call["method"].as_str().unwrap().to_string();
+                let _ = calls_tx.send((method, call["params"].clone()));
+                let result = json!({"timestamp": timestamp});
+                timestamp += 1;
+                let _ = out_tx.send(json!({"jsonrpc": "2.0", "id": call["id"], "result": result}));
+            }
+        });
+        (path, calls_rx, push_tx)
+    }
+
+    fn received(number: &str, timestamp: u64, text: &str, quote: Option<u64>) -> Value {
+        let mut data = json!({"timestamp": timestamp, "message": text});
+        if let Some(quote) = quote {
+            data["quote"] = json!({"id": quote, "author": BOT, "text": "..."});
+        }
+        json!({
+            "jsonrpc": "2.0",
+            "method": "receive",
+            "params": {
+                "account": BOT,
+                "envelope": {
+                    "source": number,
+                    "sourceNumber": number,
+                    "sourceName": "Alice",
+                    "timestamp": timestamp,
+                    "dataMessage": data,
+                },
+            },
+        })
+    }
+
+    fn make_view() -> RequestView {
+        RequestView {
+            request: IpcRequest {
+                request_id: Uuid::new_v4(),
+                tool_name: "Bash".to_string(),
+                tool_input: json!({"command": "ls"}),
+                cwd: "/tmp/proj".to_string(),
+                session_id: "s".to_string(),
+                permission_suggestions: vec![],
+                assistant_context: None,
+                first_prompt: None,
+            },
+            summary: "Bash: ls".to_string(),
+            deadline: Instant::now() + Duration::from_secs(150),
+            required_approvals: 1,
+            approvals: vec![],
+        }
+    }
+
+    /// Sends the request once the channel has connected.
+    async fn send_when_connected(channel: &SignalChannel, view: &RequestView) {
+        let page = Page {
+            tier: 0,
+            deliveries: &[],
+        };
+        while channel.send_request(view, page).await.is_err() {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    }
+
+    #[tokio::test]
+    async fn quoted_replies_answer_requests() {
+        let tmp = tempfile::tempdir().unwrap();
+        let (socket_path, mut calls, push) = fake_signal_cli(tmp.path());
+        let channel = Arc::new(SignalChannel::new(SignalConfig {
+            socket_path,
+            account: BOT.to_string(),
+            numbers: vec![ALICE.to_string()],
+        }));
+        let mut decisions = channel.decisions();
+        let cancel_token = CancellationToken::new();
+        tokio::spawn(channel.clone().run(cancel_token.clone()));
+
+        let view = make_view();
+        send_when_connected(&channel, &view).await;
+        let (method, params) = calls.recv().await.unwrap();
+        assert_eq!(method, "send");
+        assert_eq!(params["account"], BOT);
+        assert_eq!(params["recipient"], json!([ALICE]));
+        assert!(params["message"].as_str().unwrap().contains("\n\nls\n"));
+
+        // Strangers are ignored; "always" isn't offered here
+        push.send(received("+15559999999", 1, "y", Some(1000)))
+            .unwrap();
+        push.send(received(ALICE, 2, "always", Some(1000))).unwrap();
+        let (_, params) = calls.recv().await.unwrap();
+        assert_eq!(params["recipient"], json!([ALICE]));
+        assert_eq!(params["quoteTimestamp"], 2);
+        assert_eq!(
+            params["message"],
+            "Always Allow isn't offered for this request"
+        );
+
+        push.send(received(ALICE, 3, "Y", Some(1000))).unwrap();
+        let decision = decisions.next().await.unwrap();
+        assert_eq!(decision.request_id, view.request_id());
+        assert_eq!(decision.verdict, Verdict::Allow);
+        assert_eq!(decision.approver.id, format!("signal:{ALICE}"));
+        assert_eq!(decision.approver.name, "Alice");
+        decision
+            .ack
+            .unwrap()
+            .send(DecisionOutcome::ApprovalRecorded)
+            .unwrap();
+        let (_, params) = calls.recv().await.unwrap();
+        assert_eq!(params["message"], "Approval recorded");
+        assert_eq!(params["quoteTimestamp"], 3);
+
+        // With one request open, a reply needn't quote it
+        push.send(received(ALICE, 2000, "use ls -la", None))
+            .unwrap();
+        let decision = decisions.next().await.unwrap();
+        assert_eq!(decision.verdict, Verdict::Reply("use ls -la".to_string()));
+        decision
+            .ack
+            .unwrap()
+            .send(DecisionOutcome::Resolved)
+            .unwrap();
+
+        let status = StatusUpdate::Resolved("\u{270f}\u{fe0f} Amended".to_string());
+        channel.update_status(&view, &status).await;
+        let (_, params) = calls.recv().await.unwrap();
+        assert_eq!(params["message"], "\u{270f}\u{fe0f} Amended");
+        assert_eq!(params["quoteTimestamp"], 1000);
+        assert_eq!(params["quoteAuthor"], BOT);
+        assert!(channel.sent.is_empty());
+        cancel_token.cancel();
+    }
+
+    #[tokio::test]
+    async fn unquoted_replies_written_before_the_request_are_ignored() {
+        let tmp = tempfile::tempdir().unwrap();
+        let (socket_path, mut calls, push) = fake_signal_cli(tmp.path());
+        let channel = Arc::new(SignalChannel::new(SignalConfig {
+            socket_path,
+            account: BOT.to_string(),
+            numbers: vec![ALICE.to_string()],
+        }));
+        let mut decisions = channel.decisions();
+        let cancel_token = CancellationToken::new();
+        tokio::spawn(channel.clone().run(cancel_token.clone()));
+
+        // The first request is answered elsewhere, then a second one is
+        // sent while Alice is still typing "y" to the first
+        let first = make_view();
+        send_when_connected(&channel, &first).await;
+        calls.recv().await.unwrap();
+        let status = StatusUpdate::Resolved("\u{2705} Allowed".to_string());
+        channel.update_status(&first, &status).await;
+        calls.recv().await.unwrap();
+        let second = make_view();
+        send_when_connected(&channel, &second).await;
+        calls.recv().await.unwrap();
+
+        // Sent at 1002
+        push.send(received(ALICE, 1001, "y", None)).unwrap();
+        let (_, params) = calls.recv().await.unwrap();
+        assert_eq!(params["message"], "Reply to the request you are answering");
+
+        push.send(received(ALICE, 1003, "y", None)).unwrap();
+        let decision = decisions.next().await.unwrap();
+        assert_eq!(decision.request_id, second.request_id());
+        assert_eq!(decision.verdict, Verdict::Allow);
+        cancel_token.cancel();
+    }
+}
@@ -0,0 +1,91 @@
+use crate::channel::{RequestView, Verdict};
+use crate::telegram::formatter::{format_time_left, plain_tool_input, project_name, truncate};
+use std::fmt::Write;
+use tokio::time::Instant;
+
+/// Longer messages arrive as an attachment on some clients.
+const MAX_MESSAGE_BYTES: usize = 2000;
+
+/// The request as a plain-text message, with how to answer it.
+pub fn request_text(view: &RequestView, now: Instant) -> String {
+    let request = &view.request;
+    let mut text = format!(
+        "\u{1f510} {} in {}\n{}\n",
+        request.tool_name,
+        project_name(&request.cwd),
+        format_time_left(view.deadline.saturating_duration_since(now))
+    );
+    if let Some(context) = &request.assistant_context {
+        let _ = writeln!(text, "\u{1f4ac} {context}");
+    }
+    text.push('\n');
+    text.push_str(&plain_tool_input(request).join("\n"));
+    let mut text = truncate(&text, MAX_MESSAGE_BYTES);
+
+    let answers = if view.offers_always_allow() {
+        "y, n, always"
+    } else {
+        "y, n"
+    };
+    let _ = write!(
+        text,
+        "\n\nReply to this message with {answers} or instructions."
+    );
+    text
+}
+
+/// What a reply says: a yes, a no, "always", or instructions.
+pub fn parse_reply(text: &str) -> Verdict {
+    match text.trim().to_lowercase().as_str() {
+        "y" | "yes" | "allow" | "ok" => Verdict::Allow,
+        "n" | "no" | "deny" => Verdict::Deny,
+        "always" | "always allow" => Verdict::AlwaysAllow,
+        _ => Verdict::Reply(text.trim().to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::IpcRequest;
+    use serde_json::json;
+    use uuid::Uuid;
+
+    #[test]
+    fn replies_parse_to_verdicts() {
+        assert_eq!(parse_reply(" Y "), Verdict::Allow);
+        assert_eq!(parse_reply("no"), Verdict::Deny);
+        assert_eq!(parse_reply("Always"), Verdict::AlwaysAllow);
+        assert_eq!(
+            parse_reply("yes, but use --release\n"),
+            Verdict::Reply("yes, but use --release".to_string())
+        );
+    }
+
+    #[test]
+    fn request_text_is_complete_but_bounded() {
+        let view = RequestView {
+            request: IpcRequest {
+                request_id: Uuid::new_v4(),
+                tool_name: "Bash".to_string(),
+                tool_input: json!({"command": format!("echo {}", "x".repeat(5000))}),
+                cwd: "/home/me/proj".to_string(),
+                session_id: "s".to_string(),
+                permission_suggestions: vec![],
+                assistant_context: Some("Printing".to_string()),
+                first_prompt: None,
+            },
+            summary: "Bash: echo".to_string(),
+            deadline: Instant::now() + std::time::Duration::from_secs(150),
+            required_approvals: 1,
+            approvals: vec![],
+        };
+        let text = request_text(&view, Instant::now());
+        assert!(text.starts_with(
+            "\u{1f510} Bash in proj\n\u{23f3} 3 min left\n\u{1f4ac} Printing\n\necho xxx"
+        ));
+        assert!(text.contains("(truncated)"));
+        assert!(text.ends_with("Reply to this message with y, n or instructions."));
+        assert!(text.len() < MAX_MESSAGE_BYTES + 100);
+    }
+}
@@ -0,0 +1,49 @@
+pub mod channel;
+pub mod message;
+pub mod rpc;
+
+use serde::Deserialize;
+use std::path::PathBuf;
+
+/// Sending requests over Signal through a local `signal-cli` daemon
+/// (`signal-cli daemon --socket`), answered by replying to them.
+#[derive(Debug, Deserialize, Clone)]
+pub struct SignalConfig {
+    /// The daemon's JSON-RPC socket.
+    #[serde(default = "default_socket_path")]
+    pub socket_path: PathBuf,
+    /// The bot's own number, as registered with signal-cli.
+    pub account: String,
+    /// Numbers that get the requests; only their replies count.
+    pub numbers: Vec<String>,
+}
+
+/// Where `signal-cli daemon --socket` listens by default.
+fn default_socket_path() -> PathBuf {
+    let runtime_dir = std::env::var("XDG_RUNTIME_DIR").unwrap_or_else(|_| "/tmp".to_string());
+    PathBuf::from(runtime_dir).join("signal-cli").join("socket")
+}
+
+/// `+` and 7 to 15 digits.
+fn is_e164(number: &str) -> bool {
+    number.strip_prefix('+').is_some_and(|digits| {
+        (7..=15).contains(&digits.len()) && digits.bytes().all(|b| b.is_ascii_digit())
+    })
+}
+
+impl SignalConfig {
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if !is_e164(&self.account) {
+            anyhow::bail!("signal account must be a phone number like +15551234567");
+        }
+        if self.numbers.is_empty() {
+            anyhow::bail!("signal numbers must have at least one entry");
+        }
+        if let Some(number) = self.numbers.iter().find(|number| !is_e164(number)) {
+            anyhow::bail!(
+                "signal numbers entry {number:?} is not a phone number like +15551234567"
+            );
+        }
+        Ok(())
+    }
+}
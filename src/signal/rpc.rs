@@ -0,0 +1,134 @@
+use dashmap::DashMap;
+use serde_json::{Value, json};
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, Lines};
+use tokio::net::UnixStream;
+use tokio::net::unix::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::sync::{Mutex, oneshot};
+
+/// How long a call waits for signal-cli's answer.
+const CALL_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The answer to a call: its `result`, or the `error` message.
+type CallResult = Result<Value, String>;
+
+/// A JSON-RPC connection to `signal-cli daemon --socket`: one JSON object per
+/// line each way. Responses are matched to their calls by id; anything else
+/// is a notification, such as `receive` for an incoming message.
+pub struct SignalRpc {
+    account: String,
+    writer: Mutex<Option<OwnedWriteHalf>>,
+    calls: DashMap<u64, oneshot::Sender<CallResult>>,
+    next_id: AtomicU64,
+}
+
+impl SignalRpc {
+    pub fn new(account: &str) -> Self {
+        Self {
+            account: account.to_string(),
+            writer: Mutex::new(None),
+            calls: DashMap::new(),
+            next_id: AtomicU64::new(1),
+        }
+    }
+
+    /// Connects to the daemon, replacing any previous connection. The
+    /// returned lines must be fed to [`Self::dispatch`].
+    pub async fn connect(&self, path: &Path) -> std::io::Result<Lines<BufReader<OwnedReadHalf>>> {
+        let (reader, writer) = UnixStream::connect(path).await?.into_split();
+        *self.writer.lock().await = Some(writer);
+        Ok(BufReader::new(reader).lines())
+    }
+
+    /// Drops the connection; calls still waiting on it fail.
+    pub async fn disconnect(&self) {
+        *self.writer.lock().await = None;
+        self.calls.clear();
+    }
+
+    /// Calls `method` for the bot's account and waits for the result.
+    pub async fn call(&self, method: &str, mut params: Value) -> anyhow::Result<Value> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        params["account"] = json!(self.account);
+        let mut line =
+            json!({"jsonrpc": "2.0", "id": id, "method": method, "params": params}).to_string();
+        line.push('\n');
+
+        let (tx, rx) = oneshot::channel();
+        self.calls.insert(id, tx);
+        let written = match self.writer.lock().await.as_mut() {
+            Some(writer) => writer.write_all(line.as_bytes()).await.map_err(Into::into),
+            None => Err(anyhow::anyhow!("signal-cli is not connected")),
+        };
+        if let Err(e) = written {
+            self.calls.remove(&id);
+            return Err(e);
+        }
+        match tokio::time::timeout(CALL_TIMEOUT, rx).await {
+            Ok(Ok(Ok(result))) => Ok(result),
+            Ok(Ok(Err(message))) => anyhow::bail!("signal-cli {method} failed: {message}"),
+            Ok(Err(_)) => anyhow::bail!("signal-cli disconnected during {method}"),
+            Err(_) => {
+                self.calls.remove(&id);
+                anyhow::bail!("signal-cli did not answer {method}")
+            }
+        }
+    }
+
+    /// Hands a response to its call, or returns a notification's method and
+    /// params.
+    pub fn dispatch(&self, line: &str) -> Option<(String, Value)> {
+        let mut message: Value = match serde_json::from_str(line) {
+            Ok(message) => message,
+            Err(e) => {
+                tracing::warn!("Bad line from signal-cli: {e}");
+                return None;
+            }
+        };
+        if let Some(method) = message["method"].as_str() {
+            return Some((method.to_string(), message["params"].take()));
+        }
+        let id = message["id"].as_u64()?;
+        let (_, call) = self.calls.remove(&id)?;
+        let result = match message["error"]["message"].as_str() {
+            Some(error) => Err(error.to_string()),
+            None => Ok(message["result"].take()),
+        };
+        let _ = call.send(result);
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn calls_fail_without_a_connection() {
+        let rpc = SignalRpc::new("+15550000000");
+        let err = rpc.call("send", json!({})).await.unwrap_err();
+        assert!(err.to_string().contains("not connected"));
+        assert!(rpc.calls.is_empty());
+    }
+
+    #[test]
+    fn dispatch_routes_responses_and_notifications() {
+        let rpc = SignalRpc::new("+15550000000");
+        let (tx, mut rx) = oneshot::channel();
+        rpc.calls.insert(7, tx);
+        let notification = rpc.dispatch(
+            r#"{"jsonrpc":"2.0","id":7,"error":{"code":-1,"message":"Unregistered user"}}"#,
+        );
+        assert!(notification.is_none());
+        assert_eq!(rx.try_recv().unwrap(), Err("Unregistered user".to_string()));
+
+        let (method, params) = rpc
+            .dispatch(r#"{"jsonrpc":"2.0","method":"receive","params":{"envelope":{}}}"#)
+            .unwrap();
+        assert_eq!(method, "receive");
+        assert_eq!(params, json!({"envelope": {}}));
+        assert!(rpc.dispatch("not json").is_none());
+    }
+}
@@ -0,0 +1,99 @@
+use serde_json::{Value, json};
+
+const DEFAULT_BASE_URL: &str = "https://slack.com/api";
+
+/// The few Slack Web API methods the bot uses, authenticated with a bot
+/// token.
+#[derive(Clone)]
+pub struct SlackApi {
+    client: reqwest::Client,
+    token: String,
+    base_url: String,
+}
+
+impl SlackApi {
+    pub fn new(token: &str) -> Self {
+        Self::with_base_url(token, DEFAULT_BASE_URL)
+    }
+
+    pub fn with_base_url(token: &str, base_url: &str) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            token: token.to_string(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    /// Posts a message, in a thread if `thread_ts` is set. Returns its `ts`.
+    pub async fn post_message(
+        &self,
+        channel: &str,
+        text: &str,
+        blocks: Option<&Value>,
+        thread_ts: Option<&str>,
+    ) -> anyhow::Result<String> {
+        let mut body = json!({ "channel": channel, "text": text });
+        if let Some(blocks) = blocks {
+            body["blocks"] = blocks.clone();
+        }
+        if let Some(thread_ts) = thread_ts {
+            body["thread_ts"] = json!(thread_ts);
+        }
+        let response = self.call("chat.postMessage", &body).await?;
+        response["ts"]
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| anyhow::anyhow!("chat.postMessage returned no ts"))
+    }
+
+    pub async fn update_message(
+        &self,
+        channel: &str,
+        ts: &str,
+        text: &str,
+        blocks: &Value,
+    ) -> anyhow::Result<()> {
+        let body = json!({ "channel": channel, "ts": ts, "text": text, "blocks": blocks });
+        self.call("chat.update", &body).await.map(drop)
+    }
+
+    pub async fn delete_message(&self, channel: &str, ts: &str) -> anyhow::Result<()> {
+        let body = json!({ "channel": channel, "ts": ts });
+        self.call("chat.delete", &body).await.map(drop)
+    }
+
+    /// A message in `channel` only `user` can see.
+    pub async fn post_ephemeral(
+        &self,
+        channel: &str,
+        user: &str,
+        text: &str,
+    ) -> anyhow::Result<()> {
+        let body = json!({ "channel": channel, "user": user, "text": text });
+        self.call("chat.postEphemeral", &body).await.map(drop)
+    }
+
+    pub async fn open_view(&self, trigger_id: &str, view: &Value) -> anyhow::Result<()> {
+        let body = json!({ "trigger_id": trigger_id, "view": view });
+        self.call("views.open", &body).await.map(drop)
+    }
+
+    /// Calls `method`, failing unless Slack answers `"ok": true`.
+    async fn call(&self, method: &str, body: &Value) -> anyhow::Result<Value> {
+        let response: Value = self
+            .client
+            .post(format!("{}/{method}", self.base_url))
+            .bearer_auth(&self.token)
+            .json(body)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        if response["ok"].as_bool() != Some(true) {
+            let error = response["error"].as_str().unwrap_or("unknown error");
+            anyhow::bail!("Slack {method} failed: {error}");
+        }
+        Ok(response)
+    }
+}
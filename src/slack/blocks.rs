@@ -0,0 +1,229 @@
+use crate::channel::RequestView;
+use crate::models::IpcRequest;
+use crate::telegram::formatter::{
+    ToolDetails, escape_html, format_time_left, project_name, short_session_id, tool_details,
+    truncate,
+};
+use serde_json::{Value, json};
+use tokio::time::Instant;
+use uuid::Uuid;
+
+/// Slack caps a section's text at 3000 characters.
+const MAX_SECTION_CHARS: usize = 2900;
+
+/// `action_id`s of the buttons under a permission message.
+pub const ALLOW: &str = "allow";
+pub const DENY: &str = "deny";
+pub const ALWAYS: &str = "always";
+pub const REPLY: &str = "reply";
+
+/// `callback_id` of the reply modal, and the block/action of its text input.
+pub const REPLY_VIEW: &str = "reply";
+pub const REPLY_BLOCK: &str = "reply";
+pub const REPLY_INPUT: &str = "text";
+
+/// Slack escapes the same three characters as HTML.
+fn escape(text: &str) -> String {
+    escape_html(text)
+}
+
+/// The permission message in Slack's mrkdwn, with the same fields as the
+/// Telegram one.
+pub fn permission_text(request: &IpcRequest) -> String {
+    let details = match tool_details(&request.tool_name, &request.tool_input) {
+        ToolDetails::Code(code) => format!("```{}```", escape(&code)),
+        ToolDetails::Write { file_path, size } => {
+            format!("\u{1f4c4} `{}` ({})", escape(&file_path), escape(&size))
+        }
+        ToolDetails::Edit {
+            file_path,
+            old,
+            new,
+        } => format!(
+            "\u{1f4c4} `{}`\n```- {}\n+ {}```",
+            escape(&file_path),
+            escape(&old),
+            escape(&new)
+        ),
+    };
+    let context = request
+        .assistant_context
+        .as_deref()
+        .map(|ctx| format!("\n\n\u{1f4ac} {}", escape(ctx)))
+        .unwrap_or_default();
+    let text = format!(
+        "*\u{1f4cb} {project}*{context}\n\n*\u{1f527} {tool}*\n{details}\n\n\u{1f4c1} {cwd}\n\u{1f194} Session: `{session}`",
+        project = escape(project_name(&request.cwd)),
+        tool = escape(&request.tool_name),
+        cwd = escape(&request.cwd),
+        session = escape(short_session_id(&request.session_id)),
+    );
+    truncate(&text, MAX_SECTION_CHARS)
+}
+
+/// Plain-text fallback shown in notifications.
+pub fn notification_text(view: &RequestView) -> String {
+    format!(
+        "Permission request: {} in {}",
+        view.summary,
+        project_name(&view.request.cwd)
+    )
+}
+
+/// Blocks of a still-open request: the message, its progress and buttons.
+pub fn open_blocks(view: &RequestView, now: Instant) -> Value {
+    let mut status = Vec::new();
+    if view.required_approvals > 1 {
+        status.push(crate::quorum::format_progress(
+            &view.approvals,
+            view.required_approvals,
+        ));
+    }
+    status.push(format_time_left(
+        view.deadline.saturating_duration_since(now),
+    ));
+
+    let id = view.request_id().to_string();
+    let mut buttons = vec![
+        button(ALLOW, "\u{2705} Allow", &id, Some("primary")),
+        button(DENY, "\u{274c} Deny", &id, Some("danger")),
+        button(REPLY, "\u{1f4ac} Reply", &id, None),
+    ];
+    if view.offers_always_allow() {
+        buttons.push(button(ALWAYS, "\u{1f513} Always Allow", &id, None));
+    }
+    json!([
+        section(&permission_text(&view.request)),
+        context(&status.join("\n")),
+        { "type": "actions", "block_id": "decision", "elements": buttons },
+    ])
+}
+
+/// Blocks of a request that is no longer open: the message and `status`,
+/// without buttons.
+pub fn closed_blocks(request: &IpcRequest, status: &str) -> Value {
+    json!([section(&permission_text(request)), context(status)])
+}
+
+/// The modal opened by "Reply".
+pub fn reply_modal(request_id: Uuid) -> Value {
+    json!({
+        "type": "modal",
+        "callback_id": REPLY_VIEW,
+        "private_metadata": request_id.to_string(),
+        "title": { "type": "plain_text", "text": "Reply to Claude" },
+        "submit": { "type": "plain_text", "text": "Send" },
+        "close": { "type": "plain_text", "text": "Cancel" },
+        "blocks": [{
+            "type": "input",
+            "block_id": REPLY_BLOCK,
+            "label": { "type": "plain_text", "text": "Your reply" },
+            "element": {
+                "type": "plain_text_input",
+                "action_id": REPLY_INPUT,
+                "multiline": true,
+                "min_length": 1,
+            },
+        }],
+    })
+}
+
+fn section(text: &str) -> Value {
+    json!({ "type": "section", "text": { "type": "mrkdwn", "text": text } })
+}
+
+fn context(text: &str) -> Value {
+    json!({ "type": "context", "elements": [{ "type": "mrkdwn", "text": text }] })
+}
+
+fn button(action_id: &str, label: &str, value: &str, style: Option<&str>) -> Value {
+    let mut button = json!({
+        "type": "button",
+        "action_id": action_id,
+        "text": { "type": "plain_text", "text": label, "emoji": true },
+        "value": value,
+    });
+    if let Some(style) = style {
+        button["style"] = json!(style);
+    }
+    button
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_view(
+        tool_input: Value,
+        suggestions: Vec<Value>,
+        required_approvals: usize,
+    ) -> RequestView {
+        RequestView {
+            request: IpcRequest {
+                request_id: Uuid::new_v4(),
+                tool_name: "Bash".to_string(),
+                tool_input,
+                cwd: "/home/me/proj".to_string(),
+                session_id: "abcdef1234".to_string(),
+                permission_suggestions: suggestions,
+                assistant_context: Some("Running <tests>".to_string()),
+                first_prompt: None,
+            },
+            summary: "Bash: ls".to_string(),
+            deadline: Instant::now() + std::time::Duration::from_secs(150),
+            required_approvals,
+            approvals: vec![],
+        }
+    }
+
+    #[test]
+    fn permission_text_is_mrkdwn() {
+        let view = make_view(json!({"command": "ls && echo <hi>"}), vec![], 1);
+        assert_eq!(
+            permission_text(&view.request),
+            "*\u{1f4cb} proj*\n\n\u{1f4ac} Running &lt;tests&gt;\n\n*\u{1f527} Bash*\n```ls &amp;&amp; echo &lt;hi&gt;```\n\n\u{1f4c1} /home/me/proj\n\u{1f194} Session: `abcdef12`"
+        );
+    }
+
+    #[test]
+    fn open_blocks_offer_always_allow_only_with_suggestions() {
+        let now = Instant::now();
+        let view = make_view(json!({"command": "ls"}), vec![], 1);
+        let blocks = open_blocks(&view, now);
+        let actions: Vec<&str> = blocks[2]["elements"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|b| b["action_id"].as_str().unwrap())
+            .collect();
+        assert_eq!(actions, [ALLOW, DENY, REPLY]);
+        assert_eq!(
+            blocks[2]["elements"][0]["value"],
+            view.request_id().to_string()
+        );
+
+        let view = make_view(json!({"command": "ls"}), vec![json!({})], 1);
+        assert_eq!(
+            open_blocks(&view, now)[2]["elements"][3]["action_id"],
+            ALWAYS
+        );
+        // Always Allow would bypass the quorum
+        let view = make_view(json!({"command": "ls"}), vec![json!({})], 2);
+        let blocks = open_blocks(&view, now);
+        assert_eq!(blocks[2]["elements"].as_array().unwrap().len(), 3);
+        assert!(
+            blocks[1]["elements"][0]["text"]
+                .as_str()
+                .unwrap()
+                .starts_with("\u{1f5f3} 0/2 approvals")
+        );
+    }
+
+    #[test]
+    fn closed_blocks_have_no_buttons() {
+        let view = make_view(json!({"command": "ls"}), vec![], 1);
+        let blocks = closed_blocks(&view.request, "\u{2705} Approved");
+        assert_eq!(blocks.as_array().unwrap().len(), 2);
+        assert_eq!(blocks[1]["elements"][0]["text"], "\u{2705} Approved");
+    }
+}
@@ -0,0 +1,565 @@
+use crate::channel::{
+    ApprovalChannel, DecisionOutcome, DecisionQueue, DecisionStream, Page, RequestView,
+    StatusUpdate, Verdict,
+};
+use crate::http::{Body, Handler, text_response};
+use crate::models::Approver;
+use crate::slack::SlackConfig;
+use crate::slack::api::SlackApi;
+use crate::slack::blocks;
+use bytes::Bytes;
+use dashmap::DashMap;
+use futures::future::BoxFuture;
+use hyper::{Method, Request, Response, StatusCode};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::time::Instant;
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
+
+/// Path Slack posts button clicks and modal submissions to.
+pub const INTERACTIONS_PATH: &str = "/slack/interactions";
+
+/// A request's messages in the Slack channel.
+#[derive(Debug)]
+struct Posted {
+    ts: String,
+    /// Pre-timeout reminders in the message's thread.
+    reminders: Vec<String>,
+}
+
+/// Slack as an approval channel: Block Kit messages in one channel,
+/// answered through the interactivity endpoint.
+pub struct SlackChannel {
+    api: SlackApi,
+    config: SlackConfig,
+    posted: DashMap<Uuid, Posted>,
+    decisions: DecisionQueue,
+}
+
+pub type SlackState = Arc<SlackChannel>;
+
+impl SlackChannel {
+    pub fn new(config: SlackConfig) -> Self {
+        Self::with_api(SlackApi::new(&config.bot_token), config)
+    }
+
+    pub fn with_api(api: SlackApi, config: SlackConfig) -> Self {
+        Self {
+            api,
+            config,
+            posted: DashMap::new(),
+            decisions: DecisionQueue::default(),
+        }
+    }
+
+    /// Serves the interactivity endpoint until `cancel_token` fires.
+    pub async fn serve(self: Arc<Self>, cancel_token: CancellationToken) -> anyhow::Result<()> {
+        let addr = self.config.listen;
+        let handler: Handler = Arc::new(move |request| {
+            let channel = self.clone();
+            Box::pin(async move { channel.handle_request(&request) })
+        });
+        crate::http::serve(addr, handler, cancel_token).await
+    }
+
+    /// Checks an interactivity request and acts on it in the background:
+    /// Slack wants an answer within three seconds.
+    fn handle_request(self: Arc<Self>, request: &Request<Bytes>) -> Response<Body> {
+        if request.method() != Method::POST || request.uri().path() != INTERACTIONS_PATH {
+            return text_response(StatusCode::NOT_FOUND, "Not found");
+        }
+        let header = |name: &str| {
+            request
+                .headers()
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .unwrap_or_default()
+        };
+        let signed = crate::slack::signature::verify(
+            &self.config.signing_secret,
+            header("x-slack-request-timestamp"),
+            request.body(),
+            header("x-slack-signature"),
+            chrono::Utc::now().timestamp(),
+        );
+        if !signed {
+            tracing::warn!("Slack interaction with a bad signature");
+            return text_response(StatusCode::UNAUTHORIZED, "Bad signature");
+        }
+        let Some(interaction) = parse_interaction(request.body()) else {
+            return text_response(StatusCode::BAD_REQUEST, "Unrecognized payload");
+        };
+        tokio::spawn(async move { self.handle_interaction(interaction).await });
+        text_response(StatusCode::OK, "")
+    }
+
+    async fn handle_interaction(&self, interaction: Interaction) {
+        let (user, request_id, verdict) = match interaction {
+            Interaction::BlockActions {
+                user,
+                trigger_id,
+                actions,
+            } => {
+                let Some(action) = actions.into_iter().next() else {
+                    return;
+                };
+                let Ok(request_id) = action.value.parse::<Uuid>() else {
+                    return;
+                };
+                let verdict = match action.action_id.as_str() {
+                    blocks::ALLOW => Verdict::Allow,
+                    blocks::DENY => Verdict::Deny,
+                    blocks::ALWAYS => Verdict::AlwaysAllow,
+                    blocks::REPLY => {
+                        if self.authorize(&user).await {
+                            self.open_reply(&user, &trigger_id, request_id).await;
+                        }
+                        return;
+                    }
+                    _ => return,
+                };
+                (user, request_id, verdict)
+            }
+            Interaction::ViewSubmission { user, view } => {
+                let Some((request_id, text)) = view.reply() else {
+                    return;
+                };
+                (user, request_id, Verdict::Reply(text))
+            }
+            Interaction::Other => return,
+        };
+        if !self.authorize(&user).await {
+            return;
+        }
+
+        let approver = Approver {
+            id: format!("slack:{}", user.id),
+            name: user.name.clone(),
+        };
+        let outcome = self.decisions.decide(request_id, verdict, approver).await;
+        if let Some(text) = outcome.feedback() {
+            self.tell(&user, text).await;
+        }
+    }
+
+    /// Whether `user` may answer requests; tells them if not.
+    async fn authorize(&self, user: &SlackUser) -> bool {
+        if self.config.allowed_user_ids.contains(&user.id) {
+            return true;
+        }
+        tracing::warn!(user_id = %user.id, "Unauthorized Slack interaction");
+        self.tell(user, "You are not allowed to answer permission requests")
+            .await;
+        false
+    }
+
+    /// Opens the reply modal, unless the request is already resolved.
+    async fn open_reply(&self, user: &SlackUser, trigger_id: &str, request_id: Uuid) {
+        if !self.posted.contains_key(&request_id) {
+            let text = DecisionOutcome::AlreadyHandled
+                .feedback()
+                .unwrap_or_default();
+            self.tell(user, text).await;
+            return;
+        }
+        if let Err(e) = self
+            .api
+            .open_view(trigger_id, &blocks::reply_modal(request_id))
+            .await
+        {
+            tracing::warn!(request_id = %request_id, "Failed to open Slack reply modal: {e}");
+        }
+    }
+
+    /// An ephemeral message to `user` in the requests channel.
+    async fn tell(&self, user: &SlackUser, text: &str) {
+        if let Err(e) = self
+            .api
+            .post_ephemeral(&self.config.channel_id, &user.id, text)
+            .await
+        {
+            tracing::warn!(user_id = %user.id, "Failed to send Slack feedback: {e}");
+        }
+    }
+
+    async fn refresh(&self, view: &RequestView) {
+        let Some(ts) = self.posted.get(&view.request_id()).map(|p| p.ts.clone()) else {
+            return;
+        };
+        let blocks = blocks::open_blocks(view, Instant::now());
+        self.edit(&ts, &blocks::notification_text(view), &blocks)
+            .await;
+    }
+
+    /// Replies in the message's thread so the request isn't missed.
+    async fn remind(&self, view: &RequestView) {
+        let Some(ts) = self.posted.get(&view.request_id()).map(|p| p.ts.clone()) else {
+            return;
+        };
+        let text = crate::telegram::formatter::reminder_text(
+            view.deadline.saturating_duration_since(Instant::now()),
+        );
+        match self
+            .api
+            .post_message(&self.config.channel_id, &text, None, Some(&ts))
+            .await
+        {
+            Ok(reminder) => {
+                if let Some(mut posted) = self.posted.get_mut(&view.request_id()) {
+                    posted.reminders.push(reminder);
+                }
+            }
+            Err(e) => tracing::warn!("Failed to send Slack reminder: {e}"),
+        }
+    }
+
+    async fn delete_reminders(&self, reminders: &[String]) {
+        for ts in reminders {
+            if let Err(e) = self.api.delete_message(&self.config.channel_id, ts).await {
+                tracing::debug!("Failed to delete Slack reminder: {e}");
+            }
+        }
+    }
+
+    /// Shows `status` in place of the buttons and forgets the message.
+    async fn close(&self, view: &RequestView, status: &str) {
+        let Some((_, posted)) = self.posted.remove(&view.request_id()) else {
+            return;
+        };
+        self.delete_reminders(&posted.reminders).await;
+        let blocks = blocks::closed_blocks(&view.request, status);
+        self.edit(&posted.ts, &blocks::notification_text(view), &blocks)
+            .await;
+    }
+
+    async fn edit(&self, ts: &str, text: &str, blocks: &serde_json::Value) {
+        if let Err(e) = self
+            .api
+            .update_message(&self.config.channel_id, ts, text, blocks)
+            .await
+        {
+            tracing::warn!(ts = %ts, "Failed to edit Slack message: {e}");
+        }
+    }
+}
+
+impl ApprovalChannel for SlackChannel {
+    fn name(&self) -> &'static str {
+        "Slack"
+    }
+
+    fn send_request<'a>(
+        &'a self,
+        view: &'a RequestView,
+        _page: Page<'a>,
+    ) -> BoxFuture<'a, anyhow::Result<()>> {
+        Box::pin(async move {
+            if self.posted.contains_key(&view.request_id()) {
+                return Ok(());
+            }
+            let blocks = blocks::open_blocks(view, Instant::now());
+            let ts = self
+                .api
+                .post_message(
+                    &self.config.channel_id,
+                    &blocks::notification_text(view),
+                    Some(&blocks),
+                    None,
+                )
+                .await?;
+            self.posted.insert(
+                view.request_id(),
+                Posted {
+                    ts,
+                    reminders: Vec::new(),
+                },
+            );
+            Ok(())
+        })
+    }
+
+    fn update_status<'a>(
+        &'a self,
+        view: &'a RequestView,
+        update: &'a StatusUpdate,
+    ) -> BoxFuture<'a, ()> {
+        Box::pin(async move {
+            match update {
+                StatusUpdate::Progress => self.refresh(view).await,
+                StatusUpdate::Extended => {
+                    let reminders = self
+                        .posted
+                        .get_mut(&view.request_id())
+                        .map(|mut posted| std::mem::take(&mut posted.reminders))
+                        .unwrap_or_default();
+                    self.delete_reminders(&reminders).await;
+                    self.refresh(view).await;
+                }
+                StatusUpdate::Reminder => self.remind(view).await,
+                StatusUpdate::Withdrawn(status) | StatusUpdate::Resolved(status) => {
+                    self.close(view, status).await;
+                }
+            }
+        })
+    }
+
+    fn decisions(&self) -> DecisionStream {
+        self.decisions.stream()
+    }
+}
+
+/// The part of an interaction payload the bot acts on.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Interaction {
+    BlockActions {
+        user: SlackUser,
+        trigger_id: String,
+        actions: Vec<Action>,
+    },
+    ViewSubmission {
+        user: SlackUser,
+        view: SubmittedView,
+    },
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Debug, Deserialize)]
+struct SlackUser {
+    id: String,
+    #[serde(default)]
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Action {
+    action_id: String,
+    #[serde(default)]
+    value: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct SubmittedView {
+    callback_id: String,
+    private_metadata: String,
+    state: ViewState,
+}
+
+#[derive(Debug, Deserialize)]
+struct ViewState {
+    values: HashMap<String, HashMap<String, InputValue>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct InputValue {
+    value: Option<String>,
+}
+
+impl SubmittedView {
+    /// The request and text of a submitted reply modal.
+    fn reply(&self) -> Option<(Uuid, String)> {
+        if self.callback_id != blocks::REPLY_VIEW {
+            return None;
+        }
+        let request_id = self.private_metadata.parse().ok()?;
+        let text = self
+            .state
+            .values
+            .get(blocks::REPLY_BLOCK)?
+            .get(blocks::REPLY_INPUT)?
+            .value
+            .as_deref()?
+            .trim();
+        (!text.is_empty()).then(|| (request_id, text.to_string()))
+    }
+}
+
+/// Slack sends interactions as a form with one `payload` field of JSON.
+fn parse_interaction(body: &[u8]) -> Option<Interaction> {
+    #[derive(Deserialize)]
+    struct Form {
+        payload: String,
+    }
+    let form: Form = serde_urlencoded::from_bytes(body).ok()?;
+    serde_json::from_str(&form.payload).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::json_response;
+    use crate::http::test_support::{json_body, recording_server};
+    use crate::models::IpcRequest;
+    use futures::StreamExt;
+    use serde_json::{Value, json};
+    use tokio::sync::mpsc;
+
+    const SECRET: &str = "secret";
+
+    /// Stands in for the Slack Web API, reporting each call as
+    /// `(method, body)`.
+    async fn fake_slack() -> (SlackApi, mpsc::UnboundedReceiver<(String, Value)>) {
+        let record = |request: &Request<Bytes>| {
+            let method = request.uri().path().trim_start_matches("/api/");
+            Some((method.to_string(), json_body(request)))
+        };
+        let reply = json!({"ok": true, "ts": "1.0001"});
+        let respond = move |_: &Request<Bytes>| std::future::ready(json_response(&reply));
+        let (url, calls) = recording_server(record, respond).await;
+        (
+            SlackApi::with_base_url("xoxb-test", &format!("{url}/api")),
+            calls,
+        )
+    }
+
+    fn make_channel(api: SlackApi) -> Arc<SlackChannel> {
+        let config = SlackConfig {
+            bot_token: "xoxb-test".to_string(),
+            signing_secret: SECRET.to_string(),
+            channel_id: "C1".to_string(),
+            allowed_user_ids: ["U1".to_string()].into(),
+            listen: "127.0.0.1:0".parse().unwrap(),
+        };
+        Arc::new(SlackChannel::with_api(api, config))
+    }
+
+    fn make_view() -> RequestView {
+        RequestView {
+            request: IpcRequest {
+                request_id: Uuid::new_v4(),
+                tool_name: "Bash".to_string(),
+                tool_input: json!({"command": "ls"}),
+                cwd: "/tmp/proj".to_string(),
+                session_id: "s".to_string(),
+                permission_suggestions: vec![],
+                assistant_context: None,
+                first_prompt: None,
+            },
+            summary: "Bash: ls".to_string(),
+            deadline: Instant::now() + std::time::Duration::from_secs(150),
+            required_approvals: 1,
+            approvals: vec![],
+        }
+    }
+
+    /// An interactivity request as Slack would sign it.
+    fn interaction(payload: &Value, secret: &str) -> Request<Bytes> {
+        let body = serde_urlencoded::to_string([("payload", payload.to_string())]).unwrap();
+        let timestamp = chrono::Utc::now().timestamp().to_string();
+        let signature = crate::slack::signature::sign(secret, &timestamp, body.as_bytes());
+        Request::post(INTERACTIONS_PATH)
+            .header("x-slack-request-timestamp", timestamp)
+            .header("x-slack-signature", signature)
+            .body(Bytes::from(body))
+            .unwrap()
+    }
+
+    fn click(user: &str, action_id: &str, request_id: Uuid) -> Value {
+        json!({
+            "type": "block_actions",
+            "user": {"id": user, "name": "alice"},
+            "trigger_id": "t1",
+            "actions": [{"action_id": action_id, "value": request_id.to_string()}],
+        })
+    }
+
+    #[tokio::test]
+    async fn posts_and_resolves_requests() {
+        let (api, mut calls) = fake_slack().await;
+        let channel = make_channel(api);
+        let view = make_view();
+
+        let page = Page {
+            tier: 0,
+            deliveries: &[],
+        };
+        channel.send_request(&view, page).await.unwrap();
+        let (method, body) = calls.recv().await.unwrap();
+        assert_eq!(method, "chat.postMessage");
+        assert_eq!(body["channel"], "C1");
+        assert_eq!(body["blocks"][2]["elements"][0]["action_id"], "allow");
+
+        // Later tiers are Telegram chats; Slack already has the request
+        assert!(!channel.pages_escalation_tiers());
+
+        let status = StatusUpdate::Resolved("\u{2705} Approved".to_string());
+        channel.update_status(&view, &status).await;
+        let (method, body) = calls.recv().await.unwrap();
+        assert_eq!(method, "chat.update");
+        assert_eq!(body["ts"], "1.0001");
+        assert_eq!(
+            body["blocks"][1]["elements"][0]["text"],
+            "\u{2705} Approved"
+        );
+        assert!(calls.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn verified_clicks_become_decisions() {
+        let (api, mut calls) = fake_slack().await;
+        let channel = make_channel(api);
+        let mut decisions = channel.decisions();
+        let id = Uuid::new_v4();
+
+        let response = channel
+            .clone()
+            .handle_request(&interaction(&click("U1", "deny", id), "wrong"));
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let response = channel
+            .clone()
+            .handle_request(&interaction(&click("U1", "deny", id), SECRET));
+        assert_eq!(response.status(), StatusCode::OK);
+        let decision = decisions.next().await.unwrap();
+        assert_eq!(decision.request_id, id);
+        assert_eq!(decision.verdict, Verdict::Deny);
+        assert_eq!(decision.approver.id, "slack:U1");
+        decision
+            .ack
+            .unwrap()
+            .send(DecisionOutcome::AlreadyHandled)
+            .unwrap();
+        let (method, body) = calls.recv().await.unwrap();
+        assert_eq!(method, "chat.postEphemeral");
+        assert_eq!(body["user"], "U1");
+        assert_eq!(body["text"], "This request has already been handled");
+
+        // Strangers are told off and nothing is decided
+        channel
+            .clone()
+            .handle_request(&interaction(&click("U2", "allow", id), SECRET));
+        let (method, body) = calls.recv().await.unwrap();
+        assert_eq!(method, "chat.postEphemeral");
+        assert_eq!(body["user"], "U2");
+    }
+
+    #[test]
+    fn reply_modal_submission_is_parsed() {
+        let id = Uuid::new_v4();
+        let payload = json!({
+            "type": "view_submission",
+            "user": {"id": "U1", "name": "alice"},
+            "view": {
+                "callback_id": "reply",
+                "private_metadata": id.to_string(),
+                "state": {"values": {"reply": {"text": {"type": "plain_text_input", "value": " use pytest "}}}},
+            },
+        });
+        let body = serde_urlencoded::to_string([("payload", payload.to_string())]).unwrap();
+        let Some(Interaction::ViewSubmission { view, .. }) = parse_interaction(body.as_bytes())
+        else {
+            panic!("not a view submission");
+        };
+        assert_eq!(view.reply(), Some((id, "use pytest".to_string())));
+
+        let other = serde_urlencoded::to_string([("payload", r#"{"type":"shortcut"}"#)]).unwrap();
+        assert!(matches!(
+            parse_interaction(other.as_bytes()),
+            Some(Interaction::Other)
+        ));
+        assert!(parse_interaction(b"payload=not-json").is_none());
+    }
+}
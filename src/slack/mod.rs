@@ -0,0 +1,43 @@
+pub mod api;
+pub mod blocks;
+pub mod channel;
+pub mod signature;
+
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::net::SocketAddr;
+
+/// Posting requests to a Slack channel, answered from Block Kit buttons.
+#[derive(Debug, Deserialize, Clone)]
+pub struct SlackConfig {
+    /// Bot token (`xoxb-...`) with the `chat:write` scope.
+    pub bot_token: String,
+    /// Verifies that interactions really come from Slack.
+    pub signing_secret: String,
+    /// ID of the channel requests are posted to (e.g. `C0123456789`).
+    pub channel_id: String,
+    /// Slack user IDs allowed to answer requests.
+    pub allowed_user_ids: HashSet<String>,
+    /// Address of the interactivity endpoint (`POST /slack/interactions`).
+    #[serde(default = "default_listen")]
+    pub listen: SocketAddr,
+}
+
+fn default_listen() -> SocketAddr {
+    SocketAddr::from(([127, 0, 0, 1], 8790))
+}
+
+impl SlackConfig {
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if self.bot_token.is_empty() || self.signing_secret.is_empty() {
+            anyhow::bail!("slack bot_token and signing_secret must not be empty");
+        }
+        if self.channel_id.is_empty() {
+            anyhow::bail!("slack channel_id must not be empty");
+        }
+        if self.allowed_user_ids.is_empty() {
+            anyhow::bail!("slack allowed_user_ids must have at least one entry");
+        }
+        Ok(())
+    }
+}
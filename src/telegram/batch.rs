@@ -0,0 +1,298 @@
+use crate::models::SentMessage;
+use crate::telegram::formatter::escape_html;
+use dashmap::DashMap;
+use std::fmt::Write;
+use std::sync::Arc;
+use teloxide::prelude::*;
+use teloxide::types::{InlineKeyboardButton, InlineKeyboardMarkup, ParseMode, ThreadId};
+use uuid::Uuid;
+
+/// One request in a batched list.
+#[derive(Debug, Clone)]
+pub struct BatchItem {
+    pub request_id: Uuid,
+    /// Plain-text one-liner, see `format_summary`.
+    pub summary: String,
+    /// Whether "Approve/Deny all" applies to this request.
+    pub selected: bool,
+    /// Final status once the request is resolved.
+    pub status: Option<String>,
+}
+
+impl BatchItem {
+    pub const fn new(request_id: Uuid, summary: String) -> Self {
+        Self {
+            request_id,
+            summary,
+            selected: true,
+            status: None,
+        }
+    }
+}
+
+/// A single message listing several requests. Each open one has a
+/// selection toggle and its own Allow/Deny buttons; the last row approves
+/// or denies every selected request at once.
+#[derive(Debug, Clone)]
+pub struct Batch {
+    pub message: SentMessage,
+    /// HTML heading shown above the list.
+    pub title: String,
+    pub items: Vec<BatchItem>,
+}
+
+/// Batched list messages that still have open requests, by batch id.
+#[derive(Default)]
+pub struct Batches {
+    batches: DashMap<Uuid, Batch>,
+}
+
+pub type BatchState = Arc<Batches>;
+
+impl Batches {
+    pub fn insert(&self, batch_id: Uuid, batch: Batch) {
+        self.batches.insert(batch_id, batch);
+    }
+
+    /// Flips the selection of the open item at `index` (0-based).
+    /// Returns the updated batch, or `None` if there is no such open item.
+    pub fn toggle(&self, batch_id: Uuid, index: usize) -> Option<Batch> {
+        let mut batch = self.batches.get_mut(&batch_id)?;
+        let item = batch.items.get_mut(index).filter(|i| i.status.is_none())?;
+        item.selected = !item.selected;
+        Some(batch.clone())
+    }
+
+    /// Open, selected requests of a batch (`None` if the batch is gone).
+    pub fn selected(&self, batch_id: Uuid) -> Option<Vec<Uuid>> {
+        self.batches.get(&batch_id).map(|batch| {
+            batch
+                .items
+                .iter()
+                .filter(|item| item.selected && item.status.is_none())
+                .map(|item| item.request_id)
+                .collect()
+        })
+    }
+
+    /// Records `status` for `request_id` and returns the batches listing it.
+    /// Batches with nothing left open are forgotten.
+    pub fn resolve(&self, request_id: Uuid, status: &str) -> Vec<(Uuid, Batch)> {
+        let mut updated = Vec::new();
+        for mut batch in self.batches.iter_mut() {
+            if let Some(item) = batch
+                .items
+                .iter_mut()
+                .find(|item| item.request_id == request_id && item.status.is_none())
+            {
+                item.status = Some(status.to_string());
+                updated.push((*batch.key(), batch.clone()));
+            }
+        }
+        self.batches
+            .retain(|_, batch| batch.items.iter().any(|item| item.status.is_none()));
+        updated
+    }
+}
+
+/// Text and buttons of a batched list.
+pub fn render(batch_id: Uuid, batch: &Batch) -> (String, InlineKeyboardMarkup) {
+    let mut text = batch.title.clone();
+    let mut rows = Vec::new();
+    let mut open = 0;
+    let mut selected = 0;
+    for (index, item) in batch.items.iter().enumerate() {
+        let n = index + 1;
+        let summary = escape_html(&item.summary);
+        if let Some(status) = &item.status {
+            let _ = write!(text, "\n{n}. <s>{summary}</s> \u{2014} {status}");
+            continue;
+        }
+        let _ = write!(text, "\n{n}. <code>{summary}</code>");
+        open += 1;
+        let check = if item.selected {
+            selected += 1;
+            '\u{2611}'
+        } else {
+            '\u{2610}'
+        };
+        let id = item.request_id;
+        rows.push(vec![
+            InlineKeyboardButton::callback(
+                format!("{check} {n}"),
+                format!("{batch_id}:toggle:{index}"),
+            ),
+            InlineKeyboardButton::callback("\u{2705}", format!("{id}:allow")),
+            InlineKeyboardButton::callback("\u{274c}", format!("{id}:deny")),
+        ]);
+    }
+
+    if selected > 0 {
+        let (approve, deny) = if selected == open {
+            (
+                "\u{2705} Approve all".to_string(),
+                "\u{274c} Deny all".to_string(),
+            )
+        } else {
+            (
+                format!("\u{2705} Approve {selected} selected"),
+                format!("\u{274c} Deny {selected} selected"),
+            )
+        };
+        rows.push(vec![
+            InlineKeyboardButton::callback(approve, format!("{batch_id}:approveall")),
+            InlineKeyboardButton::callback(deny, format!("{batch_id}:denyall")),
+        ]);
+    }
+    (text, InlineKeyboardMarkup::new(rows))
+}
+
+/// Posts `items` as one batched list (optionally without a notification
+/// sound) and keeps it up to date as they resolve.
+pub async fn send_batch(
+    bot: &Bot,
+    batches: &Batches,
+    (chat_id, thread_id, silent): (ChatId, Option<ThreadId>, bool),
+    title: String,
+    items: Vec<BatchItem>,
+) -> Result<(), teloxide::RequestError> {
+    let batch_id = Uuid::new_v4();
+    let mut batch = Batch {
+        message: SentMessage {
+            chat_id,
+            message_id: teloxide::types::MessageId(0),
+        },
+        title,
+        items,
+    };
+    let (text, keyboard) = render(batch_id, &batch);
+    let mut send = bot
+        .send_message(chat_id, text)
+        .parse_mode(ParseMode::Html)
+        .reply_markup(keyboard)
+        .disable_notification(silent);
+    if let Some(thread_id) = thread_id {
+        send = send.message_thread_id(thread_id);
+    }
+    batch.message.message_id = send.await?.id;
+    batches.insert(batch_id, batch);
+    Ok(())
+}
+
+/// Re-renders a batched list message.
+pub async fn show(bot: &Bot, batch_id: Uuid, batch: &Batch) {
+    let (text, keyboard) = render(batch_id, batch);
+    let message = &batch.message;
+    if let Err(e) = bot
+        .edit_message_text(message.chat_id, message.message_id, text)
+        .parse_mode(ParseMode::Html)
+        .reply_markup(keyboard)
+        .await
+    {
+        tracing::warn!(
+            chat_id = message.chat_id.0,
+            message_id = message.message_id.0,
+            "Failed to edit batch message: {e}"
+        );
+    }
+}
+
+/// Marks `request_id` as resolved with `status` in every batched list.
+pub async fn mark_resolved(bot: &Bot, batches: &Batches, request_id: Uuid, status: &str) {
+    for (batch_id, batch) in batches.resolve(request_id, status) {
+        show(bot, batch_id, &batch).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use teloxide::types::{InlineKeyboardButtonKind, MessageId};
+
+    fn batch(items: &[(Uuid, &str)]) -> Batch {
+        Batch {
+            message: SentMessage {
+                chat_id: ChatId(1),
+                message_id: MessageId(7),
+            },
+            title: "<b>Held</b>".to_string(),
+            items: items
+                .iter()
+                .map(|&(request_id, summary)| BatchItem::new(request_id, summary.to_string()))
+                .collect(),
+        }
+    }
+
+    fn callback_data(button: &InlineKeyboardButton) -> &str {
+        match &button.kind {
+            InlineKeyboardButtonKind::CallbackData(data) => data,
+            _ => panic!("Expected CallbackData button kind"),
+        }
+    }
+
+    #[test]
+    fn render_lists_items_with_buttons_for_open_ones() {
+        let batch_id = Uuid::new_v4();
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+        let mut batch = batch(&[(a, "Bash: ls <dir>"), (b, "Write: src/main.rs")]);
+        batch.items[1].status = Some("\u{2705} Approved".to_string());
+
+        let (text, keyboard) = render(batch_id, &batch);
+        assert!(text.starts_with("<b>Held</b>\n1. <code>Bash: ls &lt;dir&gt;</code>"));
+        assert!(text.contains("2. <s>Write: src/main.rs</s> \u{2014} \u{2705} Approved"));
+
+        let rows = &keyboard.inline_keyboard;
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0][0].text, "\u{2611} 1");
+        assert_eq!(callback_data(&rows[0][0]), format!("{batch_id}:toggle:0"));
+        assert_eq!(callback_data(&rows[0][2]), format!("{a}:deny"));
+        assert_eq!(rows[1][0].text, "\u{2705} Approve all");
+        assert_eq!(callback_data(&rows[1][1]), format!("{batch_id}:denyall"));
+    }
+
+    #[test]
+    fn partial_selection_changes_batch_buttons() {
+        let batch_id = Uuid::new_v4();
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+        let batches = Batches::default();
+        batches.insert(batch_id, batch(&[(a, "one"), (b, "two")]));
+
+        let updated = batches.toggle(batch_id, 0).unwrap();
+        let (_, keyboard) = render(batch_id, &updated);
+        assert_eq!(keyboard.inline_keyboard[0][0].text, "\u{2610} 1");
+        assert_eq!(
+            keyboard.inline_keyboard[2][0].text,
+            "\u{2705} Approve 1 selected"
+        );
+        assert_eq!(batches.selected(batch_id), Some(vec![b]));
+
+        let updated = batches.toggle(batch_id, 1).unwrap();
+        let (_, keyboard) = render(batch_id, &updated);
+        assert_eq!(keyboard.inline_keyboard.len(), 2);
+        assert_eq!(batches.selected(batch_id), Some(vec![]));
+        assert!(batches.toggle(batch_id, 5).is_none());
+    }
+
+    #[test]
+    fn resolve_updates_and_forgets_finished_batches() {
+        let batch_id = Uuid::new_v4();
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+        let batches = Batches::default();
+        batches.insert(batch_id, batch(&[(a, "one"), (b, "two")]));
+
+        let updated = batches.resolve(a, "\u{274c} Denied");
+        assert_eq!(updated.len(), 1);
+        assert_eq!(updated[0].0, batch_id);
+        assert_eq!(
+            updated[0].1.items[0].status.as_deref(),
+            Some("\u{274c} Denied")
+        );
+        assert!(batches.resolve(a, "again").is_empty());
+        assert!(batches.toggle(batch_id, 0).is_none());
+        assert_eq!(batches.selected(batch_id), Some(vec![b]));
+
+        assert_eq!(batches.resolve(b, "done").len(), 1);
+        assert!(batches.selected(batch_id).is_none());
+        assert!(batches.resolve(Uuid::new_v4(), "none").is_empty());
+    }
+}
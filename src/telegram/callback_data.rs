@@ -6,10 +6,23 @@ pub enum CallbackAction {
     Deny,
     Reply,
     Always,
+    Extend,
+    Mute,
+    Pause,
+    /// Carries a pause id rather than a request id.
+    Resume,
+    /// Flips the selection of a batch item; carries the batch id.
+    Toggle(usize),
+    /// Approves a batch's selected requests; carries the batch id.
+    ApproveBatch,
+    /// Denies a batch's selected requests; carries the batch id.
+    DenyBatch,
 }
 
 #[derive(Debug)]
 pub struct CallbackData {
+    /// The request the button belongs to (or the pause or batch, for
+    /// actions on those).
     pub request_id: Uuid,
     pub action: CallbackAction,
 }
@@ -23,7 +36,13 @@ impl CallbackData {
             "deny" => CallbackAction::Deny,
             "reply" => CallbackAction::Reply,
             "always" => CallbackAction::Always,
-            _ => return None,
+            "extend" => CallbackAction::Extend,
+            "mute" => CallbackAction::Mute,
+            "pause" => CallbackAction::Pause,
+            "resume" => CallbackAction::Resume,
+            "approveall" => CallbackAction::ApproveBatch,
+            "denyall" => CallbackAction::DenyBatch,
+            other => CallbackAction::Toggle(other.strip_prefix("toggle:")?.parse().ok()?),
         };
         Some(Self { request_id, action })
     }
@@ -66,6 +85,45 @@ mod tests {
         assert_eq!(parsed.action, CallbackAction::Always);
     }
 
+    #[test]
+    fn parse_valid_extend() {
+        let id = Uuid::new_v4();
+        let data = format!("{id}:extend");
+        let parsed = CallbackData::parse(&data).unwrap();
+        assert_eq!(parsed.action, CallbackAction::Extend);
+    }
+
+    #[test]
+    fn parse_valid_mute() {
+        let id = Uuid::new_v4();
+        let data = format!("{id}:mute");
+        let parsed = CallbackData::parse(&data).unwrap();
+        assert_eq!(parsed.action, CallbackAction::Mute);
+    }
+
+    #[test]
+    fn parse_valid_pause_and_resume() {
+        let id = Uuid::new_v4();
+        let parsed = CallbackData::parse(&format!("{id}:pause")).unwrap();
+        assert_eq!(parsed.action, CallbackAction::Pause);
+        let parsed = CallbackData::parse(&format!("{id}:resume")).unwrap();
+        assert_eq!(parsed.action, CallbackAction::Resume);
+        assert_eq!(parsed.request_id, id);
+    }
+
+    #[test]
+    fn parse_batch_actions() {
+        let id = Uuid::new_v4();
+        let parsed = CallbackData::parse(&format!("{id}:toggle:3")).unwrap();
+        assert_eq!(parsed.action, CallbackAction::Toggle(3));
+        let parsed = CallbackData::parse(&format!("{id}:approveall")).unwrap();
+        assert_eq!(parsed.action, CallbackAction::ApproveBatch);
+        let parsed = CallbackData::parse(&format!("{id}:denyall")).unwrap();
+        assert_eq!(parsed.action, CallbackAction::DenyBatch);
+        assert!(CallbackData::parse(&format!("{id}:toggle:x")).is_none());
+        assert!(CallbackData::parse(&format!("{id}:toggle")).is_none());
+    }
+
     #[test]
     fn parse_unknown_action_returns_none() {
         let id = Uuid::new_v4();
@@ -0,0 +1,460 @@
+use crate::channel::{
+    ApprovalChannel, DecisionOutcome, DecisionQueue, DecisionStream, Page, RequestView,
+    StatusUpdate, Verdict,
+};
+use crate::models::{Approver, SentMessage};
+use crate::quiet::{Delivery, QuietState};
+use crate::telegram::batch::{BatchItem, BatchState};
+use crate::telegram::formatter::{describe_session, format_permission_message};
+use crate::telegram::handler::ReplyState;
+use crate::telegram::keyboard::make_keyboard;
+use crate::telegram::topics::{TopicState, is_thread_error};
+use chrono::Utc;
+use dashmap::DashMap;
+use futures::future::BoxFuture;
+use std::sync::Arc;
+use teloxide::prelude::*;
+use teloxide::types::{MessageId, ParseMode, ReplyParameters};
+use tokio::time::Instant;
+use uuid::Uuid;
+
+/// A request's messages in Telegram.
+#[derive(Debug, Default)]
+struct Posted {
+    /// Its own permission messages (not batched lists, see `telegram::batch`).
+    messages: Vec<SentMessage>,
+    /// Pre-timeout reminder replies, deleted once they are stale.
+    reminders: Vec<SentMessage>,
+}
+
+/// Telegram as an approval channel: permission messages with inline
+/// buttons, edited as requests progress and resolve.
+pub struct TelegramChannel {
+    bot: Bot,
+    quiet: QuietState,
+    pub topics: TopicState,
+    pub batches: BatchState,
+    pub reply_state: ReplyState,
+    posted: DashMap<Uuid, Posted>,
+    decisions: DecisionQueue,
+}
+
+pub type TelegramState = Arc<TelegramChannel>;
+
+impl TelegramChannel {
+    pub fn new(bot: Bot, quiet: QuietState, topics: TopicState) -> Self {
+        Self {
+            bot,
+            quiet,
+            topics,
+            batches: Arc::default(),
+            reply_state: Arc::default(),
+            posted: DashMap::new(),
+            decisions: DecisionQueue::default(),
+        }
+    }
+
+    /// Hands a button tap, reaction or reply to the server and waits until
+    /// it is applied.
+    pub async fn decide(
+        &self,
+        request_id: Uuid,
+        verdict: Verdict,
+        approver: Approver,
+    ) -> DecisionOutcome {
+        self.decisions.decide(request_id, verdict, approver).await
+    }
+
+    /// The request's permission messages.
+    pub fn messages(&self, request_id: Uuid) -> Vec<SentMessage> {
+        self.posted
+            .get(&request_id)
+            .map(|posted| posted.messages.clone())
+            .unwrap_or_default()
+    }
+
+    /// The request whose permission message is `message_id` in `chat_id`.
+    pub fn request_for_message(&self, chat_id: ChatId, message_id: MessageId) -> Option<Uuid> {
+        self.posted
+            .iter()
+            .find(|posted| {
+                posted
+                    .messages
+                    .iter()
+                    .any(|msg| msg.chat_id == chat_id && msg.message_id == message_id)
+            })
+            .map(|posted| *posted.key())
+    }
+
+    /// Re-renders an open request's messages (countdown, quorum progress),
+    /// keeping the buttons.
+    async fn refresh(&self, view: &RequestView) {
+        let text = open_message_text(view, Instant::now());
+        let keyboard = make_keyboard(view.request_id(), view.offers_always_allow());
+        for msg in &self.messages(view.request_id()) {
+            if let Err(e) = self
+                .bot
+                .edit_message_text(msg.chat_id, msg.message_id, &text)
+                .parse_mode(ParseMode::Html)
+                .reply_markup(keyboard.clone())
+                .await
+            {
+                tracing::warn!(
+                    chat_id = msg.chat_id.0,
+                    message_id = msg.message_id.0,
+                    "Failed to edit message: {e}"
+                );
+            }
+        }
+    }
+
+    /// Replies to each permission message with a notifying reminder, so a
+    /// silently delivered first message isn't missed. Chats that are quiet
+    /// by now are skipped.
+    async fn remind(&self, view: &RequestView) {
+        let now = Utc::now();
+        let messages: Vec<SentMessage> = self
+            .messages(view.request_id())
+            .into_iter()
+            .filter(|m| !self.quiet.is_quiet(m.chat_id.0, now))
+            .collect();
+        let text = crate::telegram::formatter::reminder_text(
+            view.deadline.saturating_duration_since(Instant::now()),
+        );
+
+        let mut reminders = Vec::new();
+        for msg in &messages {
+            match self
+                .bot
+                .send_message(msg.chat_id, &text)
+                .reply_parameters(
+                    ReplyParameters::new(msg.message_id).allow_sending_without_reply(),
+                )
+                .await
+            {
+                Ok(sent) => reminders.push(SentMessage {
+                    chat_id: msg.chat_id,
+                    message_id: sent.id,
+                }),
+                Err(e) => {
+                    tracing::warn!(chat_id = msg.chat_id.0, "Failed to send reminder: {e}");
+                }
+            }
+        }
+        if let Some(mut posted) = self.posted.get_mut(&view.request_id()) {
+            posted.reminders.extend(reminders);
+        }
+    }
+
+    /// Deletes the request's reminders (best-effort).
+    async fn delete_reminders(&self, request_id: Uuid) {
+        let reminders = self
+            .posted
+            .get_mut(&request_id)
+            .map(|mut posted| std::mem::take(&mut posted.reminders))
+            .unwrap_or_default();
+        delete_messages(&self.bot, &reminders).await;
+    }
+
+    /// Shows `status` under the request on its messages, without buttons,
+    /// and forgets them.
+    async fn close(&self, view: &RequestView, status: &str) {
+        let Some((_, posted)) = self.posted.remove(&view.request_id()) else {
+            return;
+        };
+        delete_messages(&self.bot, &posted.reminders).await;
+        edit_messages_status(
+            &self.bot,
+            &posted.messages,
+            &format_permission_message(&view.request),
+            status,
+        )
+        .await;
+    }
+}
+
+impl ApprovalChannel for TelegramChannel {
+    fn name(&self) -> &'static str {
+        "Telegram"
+    }
+
+    /// Sends the permission message to each chat, inside the session's forum
+    /// topic where the chat supports topics, and silently during quiet hours.
+    fn send_request<'a>(
+        &'a self,
+        view: &'a RequestView,
+        page: Page<'a>,
+    ) -> BoxFuture<'a, anyhow::Result<()>> {
+        Box::pin(async move {
+            let text = open_message_text(view, Instant::now());
+            let keyboard = make_keyboard(view.request_id(), view.offers_always_allow());
+            let mut sent_messages = Vec::new();
+
+            for &Delivery { chat_id, silent } in page.deliveries {
+                let chat = ChatId(chat_id);
+                let send = || {
+                    self.bot
+                        .send_message(chat, &text)
+                        .parse_mode(ParseMode::Html)
+                        .reply_markup(keyboard.clone())
+                        .disable_notification(silent)
+                };
+                let thread_id = self.topics.thread_for(&self.bot, chat, &view.request).await;
+                let mut result = match thread_id {
+                    Some(thread_id) => send().message_thread_id(thread_id).await,
+                    None => send().await,
+                };
+                // A deleted or closed topic: post to the main chat instead
+                if let (Some(thread_id), Err(e)) = (thread_id, &result)
+                    && is_thread_error(e)
+                {
+                    self.topics
+                        .forget(chat, &view.request.session_id, thread_id);
+                    result = send().await;
+                }
+                match result {
+                    Ok(msg) => {
+                        sent_messages.push(SentMessage {
+                            chat_id: chat,
+                            message_id: msg.id,
+                        });
+                    }
+                    Err(e) => {
+                        tracing::warn!(chat_id = chat_id, "Failed to send message: {e}");
+                    }
+                }
+            }
+
+            if sent_messages.is_empty() {
+                anyhow::bail!("Failed to send permission message to any chat");
+            }
+            self.posted
+                .entry(view.request_id())
+                .or_default()
+                .messages
+                .extend(sent_messages);
+            Ok(())
+        })
+    }
+
+    /// Sends the requests as one batched list to each chat.
+    fn send_batch<'a>(
+        &'a self,
+        views: &'a [RequestView],
+        page: Page<'a>,
+    ) -> BoxFuture<'a, anyhow::Result<()>> {
+        Box::pin(async move {
+            let Some(first) = views.first() else {
+                return Ok(());
+            };
+            let title = format!(
+                "<b>\u{1f4e6} {} requests</b> \u{2014} {}",
+                views.len(),
+                describe_session(&first.request.session_id, &first.request.cwd)
+            );
+            let items: Vec<BatchItem> = views
+                .iter()
+                .map(|view| BatchItem::new(view.request_id(), view.summary.clone()))
+                .collect();
+            let mut sent = false;
+            for delivery in page.deliveries {
+                let chat = ChatId(delivery.chat_id);
+                let post = |thread_id| {
+                    crate::telegram::batch::send_batch(
+                        &self.bot,
+                        &self.batches,
+                        (chat, thread_id, delivery.silent),
+                        title.clone(),
+                        items.clone(),
+                    )
+                };
+                let thread_id = self
+                    .topics
+                    .thread_for(&self.bot, chat, &first.request)
+                    .await;
+                let mut result = post(thread_id).await;
+                if let (Some(thread_id), Err(e)) = (thread_id, &result)
+                    && is_thread_error(e)
+                {
+                    self.topics
+                        .forget(chat, &first.request.session_id, thread_id);
+                    result = post(None).await;
+                }
+                match result {
+                    Ok(()) => sent = true,
+                    Err(e) => {
+                        tracing::warn!(chat_id = delivery.chat_id, "Failed to send batch: {e}");
+                    }
+                }
+            }
+            if !sent {
+                anyhow::bail!("Failed to send batched requests to any chat");
+            }
+            Ok(())
+        })
+    }
+
+    fn update_status<'a>(
+        &'a self,
+        view: &'a RequestView,
+        update: &'a StatusUpdate,
+    ) -> BoxFuture<'a, ()> {
+        Box::pin(async move {
+            match update {
+                StatusUpdate::Progress => self.refresh(view).await,
+                StatusUpdate::Extended => {
+                    self.delete_reminders(view.request_id()).await;
+                    self.refresh(view).await;
+                }
+                StatusUpdate::Reminder => self.remind(view).await,
+                StatusUpdate::Withdrawn(status) => self.close(view, status).await,
+                StatusUpdate::Resolved(status) => {
+                    self.close(view, status).await;
+                    // Batched lists have one line per request, so the quorum
+                    // line is left out
+                    let headline = status.lines().next().unwrap_or(status);
+                    crate::telegram::batch::mark_resolved(
+                        &self.bot,
+                        &self.batches,
+                        view.request_id(),
+                        headline,
+                    )
+                    .await;
+                }
+            }
+        })
+    }
+
+    fn decisions(&self) -> DecisionStream {
+        self.decisions.stream()
+    }
+
+    // Each tier has its own chats
+    fn pages_escalation_tiers(&self) -> bool {
+        true
+    }
+}
+
+/// Text of a still-open permission message: the request, quorum progress
+/// and the time left before it falls back to the terminal.
+pub fn open_message_text(view: &RequestView, now: Instant) -> String {
+    let mut status = Vec::new();
+    if view.required_approvals > 1 {
+        status.push(crate::quorum::format_progress(
+            &view.approvals,
+            view.required_approvals,
+        ));
+    }
+    status.push(crate::telegram::formatter::format_time_left(
+        view.deadline.saturating_duration_since(now),
+    ));
+    format!(
+        "{}\n\n{}",
+        format_permission_message(&view.request),
+        status.join("\n")
+    )
+}
+
+/// Deletes messages that only matter while a request is open (best-effort).
+async fn delete_messages(bot: &Bot, messages: &[SentMessage]) {
+    for msg in messages {
+        if let Err(e) = bot.delete_message(msg.chat_id, msg.message_id).await {
+            tracing::debug!(chat_id = msg.chat_id.0, "Failed to delete message: {e}");
+        }
+    }
+}
+
+async fn edit_messages_status(
+    bot: &Bot,
+    sent_messages: &[SentMessage],
+    original_text: &str,
+    status: &str,
+) {
+    let new_text = format!("{original_text}\n\n{status}");
+    for msg in sent_messages {
+        if let Err(e) = bot
+            .edit_message_text(msg.chat_id, msg.message_id, &new_text)
+            .parse_mode(ParseMode::Html)
+            .await
+        {
+            tracing::warn!(
+                chat_id = msg.chat_id.0,
+                message_id = msg.message_id.0,
+                "Failed to edit message: {e}"
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::IpcRequest;
+
+    fn make_view(required_approvals: usize, now: Instant) -> RequestView {
+        RequestView {
+            request: IpcRequest {
+                request_id: Uuid::new_v4(),
+                tool_name: "Bash".to_string(),
+                tool_input: serde_json::json!({"command": "ls"}),
+                cwd: "/tmp/proj".to_string(),
+                session_id: "s".to_string(),
+                permission_suggestions: vec![],
+                assistant_context: None,
+                first_prompt: None,
+            },
+            summary: "Bash: ls".to_string(),
+            deadline: now + std::time::Duration::from_secs(150),
+            required_approvals,
+            approvals: vec![],
+        }
+    }
+
+    #[test]
+    fn open_text_shows_time_left() {
+        let now = Instant::now();
+        let view = make_view(1, now);
+        let text = open_message_text(&view, now);
+        assert!(text.starts_with(&format_permission_message(&view.request)));
+        assert!(text.ends_with("\n\n\u{23f3} 3 min left"));
+        let later = now + std::time::Duration::from_mins(2);
+        assert!(open_message_text(&view, later).ends_with("30s left"));
+    }
+
+    #[test]
+    fn open_text_includes_quorum_progress() {
+        let now = Instant::now();
+        let view = make_view(2, now);
+        assert!(
+            open_message_text(&view, now)
+                .ends_with("\n\n\u{1f5f3} 0/2 approvals\n\u{23f3} 3 min left")
+        );
+    }
+
+    #[tokio::test]
+    async fn messages_are_found_by_chat_and_id() {
+        let tmp = tempfile::tempdir().unwrap();
+        let channel = TelegramChannel::new(
+            Bot::new("fake-token"),
+            Arc::new(crate::quiet::Quiet::new(vec![])),
+            Arc::new(crate::telegram::topics::Topics::load(
+                false,
+                tmp.path().join("topics.json"),
+            )),
+        );
+        let id = Uuid::new_v4();
+        let sent = SentMessage {
+            chat_id: ChatId(1),
+            message_id: MessageId(7),
+        };
+        channel.posted.entry(id).or_default().messages.push(sent);
+
+        assert_eq!(
+            channel.request_for_message(ChatId(1), MessageId(7)),
+            Some(id)
+        );
+        assert_eq!(channel.request_for_message(ChatId(2), MessageId(7)), None);
+        assert_eq!(channel.messages(id).len(), 1);
+        assert!(channel.messages(Uuid::new_v4()).is_empty());
+    }
+}
@@ -0,0 +1,185 @@
+use crate::activity::SessionInfo;
+use crate::telegram::dashboard::format_age;
+use crate::telegram::formatter::{escape_html, project_name};
+use chrono::{DateTime, Utc};
+use std::fmt::Write;
+use std::path::Path;
+use std::time::Duration;
+use teloxide::utils::command::BotCommands;
+
+/// Commands listed in Telegram's command menu.
+#[derive(BotCommands, Clone, Debug, PartialEq, Eq)]
+#[command(rename_rule = "lowercase", description = "vibe-reachout commands:")]
+pub enum Command {
+    #[command(description = "show this list")]
+    Help,
+    /// Sent by Telegram when a chat with the bot is opened.
+    #[command(hide)]
+    Start,
+    #[command(description = "uptime, socket path and pending requests")]
+    Status,
+    #[command(description = "active Claude Code sessions")]
+    Sessions,
+    #[command(description = "review open requests in bulk")]
+    Pending,
+    #[command(description = "show who is on call, or /oncall <name|me> to take over")]
+    OnCall(String),
+    #[command(description = "do not disturb: /dnd 2h [silent|deny], /dnd off")]
+    Dnd(String),
+    #[command(description = "list mutes, or /mute <session> [outcome] [duration]")]
+    Mute(String),
+    #[command(description = "/unmute <session>")]
+    Unmute(String),
+}
+
+pub fn help_text() -> String {
+    Command::descriptions().to_string()
+}
+
+/// Reply to a `/command` the bot doesn't know.
+pub fn unknown_command_text(text: &str) -> String {
+    let name = text
+        .split_whitespace()
+        .next()
+        .unwrap_or(text)
+        .split('@')
+        .next()
+        .unwrap_or_default();
+    format!("Unknown command {name}.\n\n{}", help_text())
+}
+
+/// Text of `/status`.
+pub fn status_text(uptime: Duration, socket_path: &Path, pending: usize) -> String {
+    format!(
+        "<b>\u{1f916} Bot is running</b>\nUptime: {}\nSocket: <code>{}</code>\nPending requests: {pending}",
+        format_age(uptime),
+        escape_html(&socket_path.display().to_string()),
+    )
+}
+
+/// How one session shows up in `/sessions`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionLine {
+    pub info: SessionInfo,
+    pub pending: usize,
+    pub muted: bool,
+    pub paused: bool,
+}
+
+/// Text of `/sessions`, most recently active first.
+pub fn sessions_text(sessions: &[SessionLine], now: DateTime<Utc>) -> String {
+    if sessions.is_empty() {
+        return "No active sessions.".to_string();
+    }
+    let mut text = String::from("<b>Active sessions</b>");
+    for session in sessions {
+        let info = &session.info;
+        let idle = (now - info.last_activity).to_std().unwrap_or_default();
+        let short: String = info.session_id.chars().take(8).collect();
+        let _ = write!(
+            text,
+            "\n\u{2022} {} <code>{}</code> \u{2014} {} ago",
+            escape_html(project_name(&info.cwd)),
+            escape_html(&short),
+            format_age(idle),
+        );
+        if session.pending > 0 {
+            let _ = write!(text, ", {} pending", session.pending);
+        }
+        if session.muted {
+            text.push_str(" \u{1f507}");
+        }
+        if session.paused {
+            text.push_str(" \u{23f8}");
+        }
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(text: &str) -> Option<Command> {
+        Command::parse(text, "my_bot").ok()
+    }
+
+    #[test]
+    fn parse_strips_bot_name_and_keeps_args() {
+        assert_eq!(parse("/oncall"), Some(Command::OnCall(String::new())));
+        assert_eq!(
+            parse("/oncall@my_bot bob"),
+            Some(Command::OnCall("bob".to_string()))
+        );
+        assert_eq!(
+            parse("/mute abc queue 2h"),
+            Some(Command::Mute("abc queue 2h".to_string()))
+        );
+        assert_eq!(parse("/status"), Some(Command::Status));
+        assert_eq!(parse("/status@other_bot"), None);
+        assert_eq!(parse("/nope"), None);
+        assert_eq!(parse("use pytest"), None);
+    }
+
+    #[test]
+    fn menu_lists_visible_commands() {
+        let commands: Vec<String> = Command::bot_commands()
+            .into_iter()
+            .map(|c| c.command)
+            .collect();
+        assert_eq!(
+            commands,
+            [
+                "/help",
+                "/status",
+                "/sessions",
+                "/pending",
+                "/oncall",
+                "/dnd",
+                "/mute",
+                "/unmute"
+            ]
+        );
+        assert!(help_text().contains("/sessions \u{2014} active Claude Code sessions"));
+    }
+
+    #[test]
+    fn unknown_command_suggests_help() {
+        let text = unknown_command_text("/foo@my_bot bar");
+        assert!(text.starts_with("Unknown command /foo.\n\n"));
+        assert!(text.contains("/help"));
+    }
+
+    #[test]
+    fn status_shows_uptime_socket_and_pending() {
+        let text = status_text(
+            Duration::from_mins(125),
+            Path::new("/tmp/vibe-reachout.sock"),
+            3,
+        );
+        assert_eq!(
+            text,
+            "<b>\u{1f916} Bot is running</b>\nUptime: 2h 5m\nSocket: <code>/tmp/vibe-reachout.sock</code>\nPending requests: 3"
+        );
+    }
+
+    #[test]
+    fn sessions_show_pending_and_state() {
+        let now = Utc::now();
+        let line = SessionLine {
+            info: SessionInfo {
+                session_id: "abcdef1234".to_string(),
+                cwd: "/home/me/proj".to_string(),
+                last_activity: now - chrono::Duration::minutes(3),
+            },
+            pending: 2,
+            muted: true,
+            paused: false,
+        };
+        assert_eq!(
+            sessions_text(&[line], now),
+            "<b>Active sessions</b>\n\u{2022} proj <code>abcdef12</code> \u{2014} 3m ago, 2 pending \u{1f507}"
+        );
+        assert_eq!(sessions_text(&[], now), "No active sessions.");
+    }
+}
@@ -0,0 +1,362 @@
+use crate::activity::{ActivityState, DailyStats, SessionInfo};
+use crate::config::Config;
+use crate::ipc::server::PendingMap;
+use crate::telegram::channel::TelegramState;
+use crate::telegram::formatter::{escape_html, project_name};
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::path::Path;
+use std::sync::Arc;
+use teloxide::prelude::*;
+use teloxide::types::{LinkPreviewOptions, MessageId, ParseMode};
+use teloxide::{ApiError, RequestError};
+use tokio::time::Duration;
+use tokio_util::sync::CancellationToken;
+
+/// Changes arriving within this window are folded into a single edit.
+const DEBOUNCE: Duration = Duration::from_secs(3);
+/// Re-render periodically so ages stay current without any state change.
+const REFRESH_INTERVAL: Duration = Duration::from_mins(1);
+
+/// A pending request as seen from one chat.
+#[derive(Debug, Clone)]
+struct PendingEntry {
+    tool_name: String,
+    cwd: String,
+    age: Duration,
+    /// The permission message in this chat, if it was sent here.
+    message_id: Option<MessageId>,
+}
+
+/// Keeps one pinned dashboard message per allowed chat up to date until
+/// `cancel_token` fires. Does nothing unless `dashboard` is enabled.
+pub async fn run_dashboard(
+    bot: Bot,
+    telegram: TelegramState,
+    config: Arc<Config>,
+    pending_map: PendingMap,
+    activity: ActivityState,
+    cancel_token: CancellationToken,
+) {
+    if !config.dashboard {
+        return;
+    }
+    let path = match crate::config::state_file_path("dashboard.json") {
+        Ok(path) => path,
+        Err(e) => {
+            tracing::warn!("Dashboard disabled: {e}");
+            return;
+        }
+    };
+
+    let mut messages = read_messages(&path);
+    let mut last_text: HashMap<i64, String> = HashMap::new();
+    let mut chat_ids: Vec<i64> = config.allowed_chat_ids.iter().copied().collect();
+    chat_ids.sort_unstable();
+
+    loop {
+        let now = Utc::now();
+        let sessions = activity.active_sessions(now);
+        let stats = activity.today();
+        let mut moved = false;
+
+        for &chat_id in &chat_ids {
+            let pending = pending_entries(&pending_map, &telegram, ChatId(chat_id));
+            let text = render(chat_id, &sessions, &pending, &stats, now);
+            if last_text.get(&chat_id) == Some(&text) {
+                continue;
+            }
+            if let Some(message_id) = show(
+                &bot,
+                ChatId(chat_id),
+                &text,
+                messages.get(&chat_id).copied(),
+            )
+            .await
+            {
+                messages.insert(chat_id, message_id);
+                moved = true;
+            }
+            last_text.insert(chat_id, text);
+        }
+
+        if moved && let Err(e) = write_messages(&path, &messages) {
+            tracing::warn!("Failed to persist dashboard to {}: {e}", path.display());
+        }
+
+        tokio::select! {
+            () = cancel_token.cancelled() => return,
+            () = activity.changed() => {
+                tokio::select! {
+                    () = cancel_token.cancelled() => return,
+                    () = tokio::time::sleep(DEBOUNCE) => {}
+                }
+            }
+            () = tokio::time::sleep(REFRESH_INTERVAL) => {}
+        }
+    }
+}
+
+/// Edits the existing dashboard message, or sends and pins a new one when
+/// there is none (or it was deleted). Returns the new message id, if any.
+async fn show(bot: &Bot, chat: ChatId, text: &str, message_id: Option<i32>) -> Option<i32> {
+    let no_preview = LinkPreviewOptions {
+        is_disabled: true,
+        url: None,
+        prefer_small_media: false,
+        prefer_large_media: false,
+        show_above_text: false,
+    };
+
+    if let Some(id) = message_id {
+        match bot
+            .edit_message_text(chat, MessageId(id), text)
+            .parse_mode(ParseMode::Html)
+            .link_preview_options(no_preview.clone())
+            .await
+        {
+            Ok(_) | Err(RequestError::Api(ApiError::MessageNotModified)) => return None,
+            Err(RequestError::Api(
+                ApiError::MessageToEditNotFound | ApiError::MessageIdInvalid,
+            )) => {}
+            Err(e) => {
+                tracing::warn!(chat_id = chat.0, "Failed to edit dashboard: {e}");
+                return None;
+            }
+        }
+    }
+
+    let msg = match bot
+        .send_message(chat, text)
+        .parse_mode(ParseMode::Html)
+        .link_preview_options(no_preview)
+        .await
+    {
+        Ok(msg) => msg,
+        Err(e) => {
+            tracing::warn!(chat_id = chat.0, "Failed to send dashboard: {e}");
+            return None;
+        }
+    };
+    if let Err(e) = bot
+        .pin_chat_message(chat, msg.id)
+        .disable_notification(true)
+        .await
+    {
+        tracing::warn!(chat_id = chat.0, "Failed to pin dashboard: {e}");
+    }
+    Some(msg.id.0)
+}
+
+/// Pending requests routed to or posted in `chat`, oldest first, with the
+/// message sent there. Other chats' requests may be from projects this one
+/// isn't meant to see.
+fn pending_entries(
+    pending_map: &PendingMap,
+    telegram: &TelegramState,
+    chat: ChatId,
+) -> Vec<PendingEntry> {
+    let mut entries: Vec<PendingEntry> = pending_map
+        .iter()
+        .filter_map(|entry| {
+            let message_id = telegram
+                .messages(*entry.key())
+                .iter()
+                .find(|m| m.chat_id == chat)
+                .map(|m| m.message_id);
+            (message_id.is_some() || entry.chat_ids.contains(&chat.0)).then(|| PendingEntry {
+                tool_name: entry.request.tool_name.clone(),
+                cwd: entry.request.cwd.clone(),
+                age: entry.created_at.elapsed(),
+                message_id,
+            })
+        })
+        .collect();
+    entries.sort_by_key(|e| std::cmp::Reverse(e.age));
+    entries
+}
+
+fn render(
+    chat_id: i64,
+    sessions: &[SessionInfo],
+    pending: &[PendingEntry],
+    stats: &DailyStats,
+    now: DateTime<Utc>,
+) -> String {
+    let mut text = String::from("<b>\u{1f4ca} Dashboard</b>\n\n<b>Active sessions</b>\n");
+    if sessions.is_empty() {
+        text.push_str("<i>none</i>\n");
+    }
+    for session in sessions {
+        let idle = (now - session.last_activity).to_std().unwrap_or_default();
+        let short: String = session.session_id.chars().take(8).collect();
+        let _ = writeln!(
+            text,
+            "\u{2022} {} <code>{}</code> \u{2014} {} ago",
+            escape_html(project_name(&session.cwd)),
+            escape_html(&short),
+            format_age(idle),
+        );
+    }
+
+    text.push_str("\n<b>Pending</b>\n");
+    if pending.is_empty() {
+        text.push_str("<i>none</i>\n");
+    }
+    for entry in pending {
+        let label = format!(
+            "{} in {}",
+            escape_html(&entry.tool_name),
+            escape_html(project_name(&entry.cwd))
+        );
+        let label = match entry.message_id.and_then(|id| message_link(chat_id, id)) {
+            Some(link) => format!("<a href=\"{link}\">{label}</a>"),
+            None => label,
+        };
+        let _ = writeln!(text, "\u{2022} {label} \u{2014} {}", format_age(entry.age));
+    }
+
+    let _ = write!(
+        text,
+        "\n<b>Today</b>\n\u{2705} {} approved \u{b7} \u{274c} {} denied \u{b7} \u{23f1}\u{fe0f} {} timed out",
+        stats.approved, stats.denied, stats.timed_out
+    );
+    text
+}
+
+/// `t.me` link to a message. Only supergroups and channels have them.
+fn message_link(chat_id: i64, message_id: MessageId) -> Option<String> {
+    let internal = chat_id.to_string().strip_prefix("-100")?.to_string();
+    Some(format!("https://t.me/c/{internal}/{}", message_id.0))
+}
+
+pub fn format_age(age: Duration) -> String {
+    let secs = age.as_secs();
+    match secs {
+        0..60 => format!("{secs}s"),
+        60..3600 => format!("{}m", secs / 60),
+        _ => format!("{}h {}m", secs / 3600, secs % 3600 / 60),
+    }
+}
+
+fn read_messages(path: &Path) -> HashMap<i64, i32> {
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+fn write_messages(path: &Path, messages: &HashMap<i64, i32>) -> anyhow::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, serde_json::to_string_pretty(messages)?)?;
+    std::fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(tool_name: &str, secs: u64, message_id: Option<i32>) -> PendingEntry {
+        PendingEntry {
+            tool_name: tool_name.to_string(),
+            cwd: "/home/user/my-project".to_string(),
+            age: Duration::from_secs(secs),
+            message_id: message_id.map(MessageId),
+        }
+    }
+
+    #[test]
+    fn format_age_units() {
+        assert_eq!(format_age(Duration::from_secs(42)), "42s");
+        assert_eq!(format_age(Duration::from_secs(185)), "3m");
+        assert_eq!(format_age(Duration::from_mins(65)), "1h 5m");
+    }
+
+    #[test]
+    fn message_link_only_for_supergroups() {
+        assert_eq!(
+            message_link(-1_001_234_567_890, MessageId(42)).as_deref(),
+            Some("https://t.me/c/1234567890/42")
+        );
+        assert!(message_link(12345, MessageId(42)).is_none());
+        assert!(message_link(-4567, MessageId(42)).is_none());
+    }
+
+    #[test]
+    fn render_empty_dashboard() {
+        let text = render(1, &[], &[], &DailyStats::default(), Utc::now());
+        assert_eq!(text.matches("<i>none</i>").count(), 2);
+        assert!(text.contains("0 approved"));
+    }
+
+    #[test]
+    fn render_lists_sessions_pending_and_counts() {
+        let now = Utc::now();
+        let sessions = [SessionInfo {
+            session_id: "abcdef1234567890".to_string(),
+            cwd: "/home/user/my-project".to_string(),
+            last_activity: now - chrono::Duration::minutes(3),
+        }];
+        let pending = [entry("Bash", 90, Some(7)), entry("<Write>", 5, None)];
+        let stats = DailyStats {
+            approved: 4,
+            denied: 1,
+            timed_out: 2,
+        };
+
+        let text = render(-1_001_234_567_890, &sessions, &pending, &stats, now);
+        assert!(text.contains("my-project <code>abcdef12</code> \u{2014} 3m ago"));
+        assert!(text.contains(
+            "<a href=\"https://t.me/c/1234567890/7\">Bash in my-project</a> \u{2014} 1m"
+        ));
+        assert!(text.contains("&lt;Write&gt; in my-project \u{2014} 5s"));
+        assert!(text.contains("4 approved"));
+        assert!(text.contains("1 denied"));
+        assert!(text.contains("2 timed out"));
+    }
+
+    #[test]
+    fn pending_only_lists_each_chats_own_requests() {
+        let tmp = tempfile::tempdir().unwrap();
+        let telegram = Arc::new(crate::telegram::channel::TelegramChannel::new(
+            Bot::new("fake-token"),
+            Arc::new(crate::quiet::Quiet::new(vec![])),
+            Arc::new(crate::telegram::topics::Topics::load(
+                false,
+                tmp.path().join("topics.json"),
+            )),
+        ));
+        let pending_map: PendingMap = Arc::default();
+        for (tool_name, chat_id) in [("Bash", 1), ("Write", 2)] {
+            let mut pending = crate::channel::memory::pending_request("ls");
+            pending.request.tool_name = tool_name.to_string();
+            pending.chat_ids = vec![chat_id];
+            pending_map.insert(pending.request.request_id, pending);
+        }
+
+        let tools = |chat_id| {
+            pending_entries(&pending_map, &telegram, ChatId(chat_id))
+                .into_iter()
+                .map(|entry| entry.tool_name)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(tools(1), ["Bash"]);
+        assert_eq!(tools(2), ["Write"]);
+        assert!(tools(3).is_empty());
+    }
+
+    #[test]
+    fn messages_roundtrip_through_file() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("state").join("dashboard.json");
+        let messages = HashMap::from([(-1001, 5), (42, 9)]);
+        write_messages(&path, &messages).unwrap();
+        assert_eq!(read_messages(&path), messages);
+        assert!(read_messages(&tmp.path().join("missing.json")).is_empty());
+    }
+}
@@ -1,20 +1,22 @@
 use crate::models::IpcRequest;
+use std::fmt::Write;
 use std::path::Path;
 
 const MAX_FIELD_CHARS: usize = 500;
 const MAX_TOTAL_CHARS: usize = 4000;
 
-pub fn format_permission_message(request: &IpcRequest) -> String {
-    let project_name = Path::new(&request.cwd)
+/// Last path component of `cwd`, used as the project's display name.
+pub fn project_name(cwd: &str) -> &str {
+    Path::new(cwd)
         .file_name()
         .and_then(|n| n.to_str())
-        .unwrap_or("unknown");
+        .unwrap_or("unknown")
+}
 
-    let session_short = if request.session_id.len() > 8 {
-        &request.session_id[..8]
-    } else {
-        &request.session_id
-    };
+pub fn format_permission_message(request: &IpcRequest) -> String {
+    let project_name = project_name(&request.cwd);
+
+    let session_short = short_session_id(&request.session_id);
 
     let tool_details = format_tool_details(&request.tool_name, &request.tool_input);
 
@@ -37,70 +39,167 @@ pub fn format_permission_message(request: &IpcRequest) -> String {
     truncate(&message, MAX_TOTAL_CHARS)
 }
 
-fn format_tool_details(tool_name: &str, tool_input: &serde_json::Value) -> String {
+/// First 8 bytes of a session ID, as shown in permission messages.
+pub fn short_session_id(session_id: &str) -> &str {
+    if session_id.len() > 8 {
+        &session_id[..8]
+    } else {
+        session_id
+    }
+}
+
+/// One-line plain-text summary of a request for batched lists,
+/// e.g. `Bash: cargo test`.
+pub fn format_summary(request: &IpcRequest) -> String {
+    const MAX_SUMMARY_CHARS: usize = 80;
+    let detail = match request.tool_name.as_str() {
+        "Bash" => request.tool_input.get("command"),
+        "Write" | "Edit" | "Read" => request.tool_input.get("file_path"),
+        _ => None,
+    }
+    .and_then(|v| v.as_str())
+    .and_then(|d| d.lines().next());
+
+    let summary = match detail {
+        Some(detail) => format!("{}: {detail}", request.tool_name),
+        None => request.tool_name.clone(),
+    };
+    if summary.len() <= MAX_SUMMARY_CHARS {
+        summary
+    } else {
+        let boundary = summary.floor_char_boundary(MAX_SUMMARY_CHARS);
+        format!("{}\u{2026}", &summary[..boundary])
+    }
+}
+
+/// `Session <code>abcdef12</code> in <b>project</b>`, for messages about a
+/// whole session.
+pub fn describe_session(session_id: &str, cwd: &str) -> String {
+    let short: String = session_id.chars().take(8).collect();
+    let mut text = format!("Session <code>{}</code>", escape_html(&short));
+    if !cwd.is_empty() {
+        let _ = write!(text, " in <b>{}</b>", escape_html(project_name(cwd)));
+    }
+    text
+}
+
+/// What a permission message shows about the tool call, before markup.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ToolDetails {
+    /// A command or JSON excerpt, shown as a code block.
+    Code(String),
+    /// A file about to be written, with the content size.
+    Write { file_path: String, size: String },
+    /// A file edit with its old and new text.
+    Edit {
+        file_path: String,
+        old: String,
+        new: String,
+    },
+}
+
+/// Picks the fields worth showing for `tool_name`, truncated.
+pub fn tool_details(tool_name: &str, tool_input: &serde_json::Value) -> ToolDetails {
+    let field = |name: &str| tool_input.get(name).and_then(|v| v.as_str());
     match tool_name {
         "Bash" => {
-            let command = tool_input
-                .get("command")
-                .and_then(|v| v.as_str())
-                .unwrap_or("<no command>");
-            let truncated = truncate(command, MAX_FIELD_CHARS);
-            format!("<pre>{}</pre>", escape_html(&truncated))
-        }
-        "Write" => {
-            let file_path = tool_input
-                .get("file_path")
-                .and_then(|v| v.as_str())
-                .unwrap_or("<unknown file>");
-            let content_len = tool_input
-                .get("content")
-                .and_then(|v| v.as_str())
-                .map_or(0, str::len);
-            let size = format_size(content_len);
-            format!(
-                "\u{1f4c4} <code>{}</code> ({})",
-                escape_html(file_path),
-                escape_html(&size)
-            )
-        }
-        "Edit" => {
-            let file_path = tool_input
-                .get("file_path")
-                .and_then(|v| v.as_str())
-                .unwrap_or("<unknown file>");
-            let old = tool_input
-                .get("old_string")
-                .and_then(|v| v.as_str())
-                .unwrap_or("");
-            let new = tool_input
-                .get("new_string")
-                .and_then(|v| v.as_str())
-                .unwrap_or("");
-            let old_truncated = truncate(old, MAX_FIELD_CHARS / 2);
-            let new_truncated = truncate(new, MAX_FIELD_CHARS / 2);
-            format!(
-                "\u{1f4c4} <code>{}</code>\n<pre>- {}\n+ {}</pre>",
-                escape_html(file_path),
-                escape_html(&old_truncated),
-                escape_html(&new_truncated),
-            )
+            let command = field("command").unwrap_or("<no command>");
+            ToolDetails::Code(truncate(command, MAX_FIELD_CHARS))
         }
+        "Write" => ToolDetails::Write {
+            file_path: field("file_path").unwrap_or("<unknown file>").to_string(),
+            size: format_size(field("content").map_or(0, str::len)),
+        },
+        "Edit" => ToolDetails::Edit {
+            file_path: field("file_path").unwrap_or("<unknown file>").to_string(),
+            old: truncate(field("old_string").unwrap_or(""), MAX_FIELD_CHARS / 2),
+            new: truncate(field("new_string").unwrap_or(""), MAX_FIELD_CHARS / 2),
+        },
         _ => {
             // Generic: show JSON excerpt
             let json_str = serde_json::to_string_pretty(tool_input).unwrap_or_default();
-            let truncated = truncate(&json_str, MAX_FIELD_CHARS);
-            format!("<pre>{}</pre>", escape_html(&truncated))
+            ToolDetails::Code(truncate(&json_str, MAX_FIELD_CHARS))
+        }
+    }
+}
+
+fn format_tool_details(tool_name: &str, tool_input: &serde_json::Value) -> String {
+    match tool_details(tool_name, tool_input) {
+        ToolDetails::Code(code) => format!("<pre>{}</pre>", escape_html(&code)),
+        ToolDetails::Write { file_path, size } => format!(
+            "\u{1f4c4} <code>{}</code> ({})",
+            escape_html(&file_path),
+            escape_html(&size)
+        ),
+        ToolDetails::Edit {
+            file_path,
+            old,
+            new,
+        } => format!(
+            "\u{1f4c4} <code>{}</code>\n<pre>- {}\n+ {}</pre>",
+            escape_html(&file_path),
+            escape_html(&old),
+            escape_html(&new),
+        ),
+    }
+}
+
+/// The tool input in full as plain-text lines, untruncated: the command,
+/// the whole file, or an edit as removed then added lines.
+pub fn plain_tool_input(request: &IpcRequest) -> Vec<String> {
+    let input = &request.tool_input;
+    let field = |name: &str| input.get(name).and_then(serde_json::Value::as_str);
+    let text_lines = |text: &str| text.lines().map(String::from).collect::<Vec<_>>();
+    match (request.tool_name.as_str(), field("file_path")) {
+        ("Bash", _) if field("command").is_some() => {
+            let mut lines = text_lines(field("command").unwrap_or_default());
+            if let Some(description) = field("description") {
+                lines.push(format!("({description})"));
+            }
+            lines
+        }
+        ("Write", Some(file_path)) => {
+            let mut lines = vec![file_path.to_string()];
+            lines.extend(text_lines(field("content").unwrap_or_default()));
+            lines
         }
+        ("Edit", Some(file_path)) => {
+            let old = field("old_string").unwrap_or_default().lines();
+            let new = field("new_string").unwrap_or_default().lines();
+            let mut lines = vec![file_path.to_string()];
+            lines.extend(old.map(|l| format!("- {l}")));
+            lines.extend(new.map(|l| format!("+ {l}")));
+            lines
+        }
+        _ => text_lines(&serde_json::to_string_pretty(input).unwrap_or_default()),
     }
 }
 
-fn escape_html(s: &str) -> String {
+pub fn escape_html(s: &str) -> String {
     s.replace('&', "&amp;")
         .replace('<', "&lt;")
         .replace('>', "&gt;")
 }
 
-fn truncate(s: &str, max: usize) -> String {
+/// Countdown line for an open request, in whole minutes until the last one.
+pub fn format_time_left(remaining: std::time::Duration) -> String {
+    let secs = remaining.as_secs();
+    if secs >= 60 {
+        format!("\u{23f3} {} min left", secs.div_ceil(60))
+    } else {
+        format!("\u{23f3} {secs}s left")
+    }
+}
+
+/// The pre-timeout reminder, with `remaining` as the countdown.
+pub fn reminder_text(remaining: std::time::Duration) -> String {
+    format!(
+        "\u{23f0} Still waiting for your decision ({})",
+        format_time_left(remaining)
+    )
+}
+
+pub fn truncate(s: &str, max: usize) -> String {
     if s.len() <= max {
         s.to_string()
     } else {
@@ -137,9 +236,50 @@ mod tests {
             session_id: "abcdef1234567890".to_string(),
             permission_suggestions: vec![],
             assistant_context: None,
+            first_prompt: None,
         }
     }
 
+    #[test]
+    fn summary_uses_first_line_of_command() {
+        let req = make_request(
+            "Bash",
+            serde_json::json!({"command": "cargo test\ncargo build"}),
+        );
+        assert_eq!(format_summary(&req), "Bash: cargo test");
+    }
+
+    #[test]
+    fn summary_of_file_tools_and_others() {
+        let req = make_request(
+            "Write",
+            serde_json::json!({"file_path": "src/main.rs", "content": "x"}),
+        );
+        assert_eq!(format_summary(&req), "Write: src/main.rs");
+        let req = make_request(
+            "WebFetch",
+            serde_json::json!({"url": "https://example.com"}),
+        );
+        assert_eq!(format_summary(&req), "WebFetch");
+    }
+
+    #[test]
+    fn summary_is_truncated() {
+        let req = make_request("Bash", serde_json::json!({"command": "x".repeat(200)}));
+        let summary = format_summary(&req);
+        assert!(summary.ends_with('\u{2026}'));
+        assert!(summary.len() < 90);
+    }
+
+    #[test]
+    fn describe_session_shortens_id() {
+        assert_eq!(
+            describe_session("abcdef1234567890", "/home/user/my-project"),
+            "Session <code>abcdef12</code> in <b>my-project</b>"
+        );
+        assert_eq!(describe_session("abc", ""), "Session <code>abc</code>");
+    }
+
     #[test]
     fn format_bash_tool() {
         let req = make_request("Bash", serde_json::json!({"command": "ls -la"}));
@@ -209,6 +349,22 @@ mod tests {
         assert!(msg.len() <= 4000 + "... (truncated)".len());
     }
 
+    #[test]
+    fn time_left_rounds_up_to_minutes() {
+        assert_eq!(
+            format_time_left(std::time::Duration::from_mins(5)),
+            "\u{23f3} 5 min left"
+        );
+        assert_eq!(
+            format_time_left(std::time::Duration::from_secs(61)),
+            "\u{23f3} 2 min left"
+        );
+        assert_eq!(
+            format_time_left(std::time::Duration::from_secs(45)),
+            "\u{23f3} 45s left"
+        );
+    }
+
     #[test]
     fn format_size_bytes() {
         assert_eq!(format_size(0), "0 B");
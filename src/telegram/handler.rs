@@ -1,28 +1,50 @@
-use crate::config::Config;
-use crate::ipc::server::PendingMap;
-use crate::models::IpcResponse;
+use crate::channel::{DecisionOutcome, Page, Verdict};
+use crate::config::MAX_TIMEOUT_SECONDS;
+use crate::ipc::server::ServerContext;
+use crate::models::{Approver, IpcResponse};
+use crate::mute::{MUTED_MESSAGE, Mute, MuteOutcome, MuteState};
+use crate::quiet::{Delivery, QuietMode, QuietState};
+use crate::rota::OnCallState;
+use crate::telegram::batch::BatchItem;
 use crate::telegram::callback_data::{CallbackAction, CallbackData};
+use crate::telegram::channel::TelegramState;
+use crate::telegram::command::{self, Command, SessionLine};
+use crate::telegram::formatter::describe_session;
+use crate::telegram::reaction::{self, ReactionAction};
 use dashmap::DashMap;
+use std::fmt::Write;
 use std::sync::Arc;
 use teloxide::prelude::*;
-use teloxide::types::{ChatId, MessageId};
+use teloxide::types::{
+    CallbackQueryId, ChatId, InlineKeyboardButton, InlineKeyboardMarkup, Me, MessageId,
+    MessageReactionUpdated, ParseMode, User,
+};
+use teloxide::utils::command::{BotCommands, ParseError};
+use tokio::time::Duration;
 use uuid::Uuid;
 
+/// How much time one tap on "+5 min" adds.
+const EXTEND_BY: Duration = Duration::from_mins(5);
+
 pub type ReplyState = Arc<DashMap<ChatId, (Uuid, MessageId)>>;
 
 pub async fn handle_callback(
     bot: Bot,
     query: CallbackQuery,
-    config: Arc<Config>,
-    pending_map: PendingMap,
-    reply_state: ReplyState,
+    ctx: ServerContext,
+    telegram: TelegramState,
 ) -> Result<(), teloxide::RequestError> {
+    let ServerContext {
+        config,
+        pending_map,
+        ..
+    } = &ctx;
     let Some(msg) = query.message.as_ref() else {
         tracing::warn!("Callback query with no associated message");
         return Ok(());
     };
     let chat_id = msg.chat().id;
-    let query_id = query.id;
+    let query_id = query.id.clone();
 
     // Authorization check
     if !config.allowed_chat_ids.contains(&chat_id.0) {
@@ -52,13 +74,16 @@ pub async fn handle_callback(
         bot.answer_callback_query(query_id.clone()).await?;
 
         if pending_map.contains_key(&request_id) {
-            // Send ForceReply prompt
-            let msg = bot
+            // Send ForceReply prompt (in the request's forum topic, if any)
+            let mut prompt = bot
                 .send_message(chat_id, "Type your reply:")
-                .reply_markup(teloxide::types::ForceReply::new())
-                .await?;
+                .reply_markup(teloxide::types::ForceReply::new());
+            if let Some(thread_id) = msg.regular_message().and_then(|m| m.thread_id) {
+                prompt = prompt.message_thread_id(thread_id);
+            }
+            let msg = prompt.await?;
 
-            reply_state.insert(chat_id, (request_id, msg.id));
+            telegram.reply_state.insert(chat_id, (request_id, msg.id));
         } else {
             bot.send_message(chat_id, "This request has already been handled.")
                 .await?;
@@ -67,67 +92,342 @@ pub async fn handle_callback(
         return Ok(());
     }
 
-    // For allow/deny/always — resolve the pending request
-    let Some((_, pending)) = pending_map.remove(&request_id) else {
-        // Already handled
-        bot.answer_callback_query(query_id.clone())
+    if callback.action == CallbackAction::Extend {
+        let extended = pending_map.get(&request_id).map(|pending| {
+            let before = pending.deadline();
+            pending
+                .extend_deadline(EXTEND_BY, Duration::from_secs(MAX_TIMEOUT_SECONDS))
+                .map(|after| after - before)
+        });
+        let text = match extended {
+            None => "This request has already been handled".to_string(),
+            Some(None) => "This request can't be extended any further".to_string(),
+            Some(Some(added)) => extended_text(added),
+        };
+        // The server refreshes the messages once it sees the new deadline
+        bot.answer_callback_query(query_id).text(text).await?;
+        return Ok(());
+    }
+
+    if callback.action == CallbackAction::Mute {
+        return mute_from_button(&bot, query_id, &ctx, request_id).await;
+    }
+    if callback.action == CallbackAction::Pause {
+        return pause_from_button(&bot, &query, &ctx, request_id).await;
+    }
+    if callback.action == CallbackAction::Resume {
+        return resume_from_button(&bot, &query, &ctx, request_id).await;
+    }
+    if matches!(
+        callback.action,
+        CallbackAction::Toggle(_) | CallbackAction::ApproveBatch | CallbackAction::DenyBatch
+    ) {
+        return handle_batch_action(&bot, &query, &telegram, request_id, callback.action).await;
+    }
+
+    let verdict = match callback.action {
+        CallbackAction::Always => Verdict::AlwaysAllow,
+        CallbackAction::Deny => Verdict::Deny,
+        _ => Verdict::Allow,
+    };
+    let outcome = telegram
+        .decide(request_id, verdict, approver(&query.from))
+        .await;
+    // Quorum requests collect approvals from distinct users before resolving
+    let mut answer = bot.answer_callback_query(query_id);
+    if let Some(text) = outcome.feedback() {
+        answer = answer.text(text);
+    }
+    answer
+        .show_alert(outcome == DecisionOutcome::AlreadyHandled)
+        .await?;
+    Ok(())
+}
+
+/// Who made a decision in Telegram.
+fn approver(user: &User) -> Approver {
+    Approver {
+        id: format!("telegram:{}", user.id),
+        name: user.full_name(),
+    }
+}
+
+/// Handles a reaction on a permission message: a mapped emoji (👍/👎 by
+/// default) works like tapping Allow or Deny.
+pub async fn handle_reaction(
+    update: MessageReactionUpdated,
+    ctx: ServerContext,
+    telegram: TelegramState,
+) -> Result<(), teloxide::RequestError> {
+    let chat_id = update.chat.id;
+
+    // Authorization check
+    if !ctx.config.allowed_chat_ids.contains(&chat_id.0) {
+        tracing::warn!(chat_id = chat_id.0, "Unauthorized reaction attempt");
+        return Ok(());
+    }
+    // Anonymous group admins can't be told apart for quorum approvals
+    let Some(user) = update.user() else {
+        return Ok(());
+    };
+    let Some(action) = reaction::added_action(
+        &ctx.config.reactions,
+        &update.old_reaction,
+        &update.new_reaction,
+    ) else {
+        return Ok(());
+    };
+    let Some(request_id) = telegram.request_for_message(chat_id, update.message_id) else {
+        return Ok(());
+    };
+
+    let verdict = match action {
+        ReactionAction::Allow => Verdict::Allow,
+        ReactionAction::Deny => Verdict::Deny,
+    };
+    telegram.decide(request_id, verdict, approver(user)).await;
+    Ok(())
+}
+
+/// Handles the selection toggles and "Approve/Deny all" of a batched list.
+async fn handle_batch_action(
+    bot: &Bot,
+    query: &CallbackQuery,
+    telegram: &TelegramState,
+    batch_id: Uuid,
+    action: CallbackAction,
+) -> Result<(), teloxide::RequestError> {
+    let gone = "This list has already been handled";
+    if let CallbackAction::Toggle(index) = action {
+        let Some(batch) = telegram.batches.toggle(batch_id, index) else {
+            bot.answer_callback_query(query.id.clone())
+                .text(gone)
+                .await?;
+            return Ok(());
+        };
+        bot.answer_callback_query(query.id.clone()).await?;
+        crate::telegram::batch::show(bot, batch_id, &batch).await;
+        return Ok(());
+    }
+
+    let Some(selected) = telegram.batches.selected(batch_id) else {
+        bot.answer_callback_query(query.id.clone())
+            .text(gone)
+            .show_alert(true)
+            .await?;
+        return Ok(());
+    };
+    let approve = action == CallbackAction::ApproveBatch;
+    let verdict = if approve {
+        Verdict::Allow
+    } else {
+        Verdict::Deny
+    };
+    let (mut resolved, mut counted) = (0, 0);
+    for request_id in selected {
+        let outcome = telegram
+            .decide(request_id, verdict.clone(), approver(&query.from))
+            .await;
+        match outcome {
+            DecisionOutcome::Resolved => resolved += 1,
+            // Quorum requests only count this approval until enough are in
+            DecisionOutcome::ApprovalRecorded | DecisionOutcome::DuplicateApproval => {
+                counted += 1;
+            }
+            DecisionOutcome::AlreadyHandled | DecisionOutcome::UnidentifiedApprover => {}
+        }
+    }
+
+    let verb = if approve { "Approved" } else { "Denied" };
+    let mut text = format!(
+        "{verb} {resolved} request{}",
+        if resolved == 1 { "" } else { "s" }
+    );
+    if counted > 0 {
+        let _ = write!(text, "; approval recorded on {counted} needing a quorum");
+    }
+    bot.answer_callback_query(query.id.clone())
+        .text(text)
+        .await?;
+    Ok(())
+}
+
+/// Mutes the session behind a request with the configured `mute_outcome`
+/// until `/unmute`, and applies that outcome to the request itself.
+async fn mute_from_button(
+    bot: &Bot,
+    query_id: CallbackQueryId,
+    ctx: &ServerContext,
+    request_id: Uuid,
+) -> Result<(), teloxide::RequestError> {
+    let ServerContext {
+        config,
+        pending_map,
+        mutes,
+        ..
+    } = ctx;
+    let Some(session_id) = pending_map
+        .get(&request_id)
+        .map(|p| p.request.session_id.clone())
+    else {
+        bot.answer_callback_query(query_id)
             .text("This request has already been handled")
             .show_alert(true)
             .await?;
         return Ok(());
     };
 
-    bot.answer_callback_query(query_id.clone()).await?;
+    let outcome = config.mute_outcome;
+    mutes.mute(
+        &session_id,
+        Mute {
+            outcome,
+            until: None,
+        },
+    );
+    tracing::info!(session_id = %session_id, "Session muted via Telegram");
+    bot.answer_callback_query(query_id)
+        .text(format!("Session muted: {}", outcome.describe()))
+        .await?;
+
+    if outcome == MuteOutcome::Queue {
+        // Keep it open, but without messages it is neither refreshed nor reminded
+        let status = "\u{1f507} Session muted \u{2014} queued for /pending";
+        crate::channel::withdraw(ctx, request_id, status).await;
+        return Ok(());
+    }
 
-    let (response, status_text) =
-        build_callback_response(callback.action, request_id, &pending.permission_suggestions);
+    let response = if outcome == MuteOutcome::Deny {
+        IpcResponse::deny(request_id, MUTED_MESSAGE.to_string())
+    } else {
+        IpcResponse::timeout(request_id)
+    };
+    crate::channel::resolve(ctx, request_id, response, "\u{1f507} Session muted").await;
+    Ok(())
+}
 
-    // Edit ALL sent messages to show status
-    crate::bot::edit_messages_status(
-        &bot,
-        &pending.sent_messages,
-        &pending.original_text,
-        status_text,
-    )
-    .await;
+/// Pauses the session behind a request: its later requests are held without
+/// notifying anyone until "Resume" is tapped. The request itself stays open.
+async fn pause_from_button(
+    bot: &Bot,
+    query: &CallbackQuery,
+    ctx: &ServerContext,
+    request_id: Uuid,
+) -> Result<(), teloxide::RequestError> {
+    let Some((session_id, cwd)) = ctx
+        .pending_map
+        .get(&request_id)
+        .map(|p| (p.request.session_id.clone(), p.request.cwd.clone()))
+    else {
+        bot.answer_callback_query(query.id.clone())
+            .text("This request has already been handled")
+            .show_alert(true)
+            .await?;
+        return Ok(());
+    };
 
-    // Send response via oneshot channel
-    let _ = pending.sender.send(response);
+    let pause_id = ctx.pauses.pause(&session_id);
+    tracing::info!(session_id = %session_id, "Session paused via Telegram");
+    bot.answer_callback_query(query.id.clone())
+        .text("Session paused")
+        .await?;
 
+    let Some(msg) = query.message.as_ref() else {
+        return Ok(());
+    };
+    let text = format!(
+        "\u{23f8} {} is paused. New requests are held until you resume, \
+         for up to an hour each before they fall back to the terminal.",
+        describe_session(&session_id, &cwd)
+    );
+    let keyboard = InlineKeyboardMarkup::new([[InlineKeyboardButton::callback(
+        "\u{25b6} Resume",
+        format!("{pause_id}:resume"),
+    )]]);
+    let mut send = bot
+        .send_message(msg.chat().id, text)
+        .parse_mode(ParseMode::Html)
+        .reply_markup(keyboard);
+    if let Some(thread_id) = msg.regular_message().and_then(|m| m.thread_id) {
+        send = send.message_thread_id(thread_id);
+    }
+    send.await?;
     Ok(())
 }
 
-/// Build the IPC response and status text for a callback action.
-/// Extracted as a pure function for testability.
-fn build_callback_response(
-    action: CallbackAction,
-    request_id: Uuid,
-    permission_suggestions: &[serde_json::Value],
-) -> (IpcResponse, &'static str) {
-    match action {
-        CallbackAction::Allow => (IpcResponse::allow(request_id), "\u{2705} Approved"),
-        CallbackAction::Deny => (
-            IpcResponse::deny(request_id, "Denied by user via Telegram".to_string()),
-            "\u{274c} Denied",
-        ),
-        CallbackAction::Always => {
-            let suggestion = permission_suggestions.first().cloned();
-            (
-                IpcResponse::always_allow(request_id, suggestion),
-                "\u{1f513} Always Allowed",
-            )
-        }
-        CallbackAction::Reply => unreachable!("Reply should be handled before calling this"),
+/// Ends a pause and posts the requests held meanwhile to every channel, as
+/// one batched list, here in Telegram.
+async fn resume_from_button(
+    bot: &Bot,
+    query: &CallbackQuery,
+    ctx: &ServerContext,
+    pause_id: Uuid,
+) -> Result<(), teloxide::RequestError> {
+    let Some(session_id) = ctx.pauses.resume(pause_id) else {
+        bot.answer_callback_query(query.id.clone())
+            .text("This session is not paused")
+            .await?;
+        return Ok(());
+    };
+    tracing::info!(session_id = %session_id, "Session resumed via Telegram");
+    bot.answer_callback_query(query.id.clone())
+        .text("Session resumed")
+        .await?;
+
+    let mut held: Vec<_> = ctx
+        .pending_map
+        .iter()
+        .filter(|p| p.request.session_id == session_id && !p.posted)
+        .map(|p| (p.created_at, p.request.cwd.clone(), *p.key()))
+        .collect();
+    held.sort_by_key(|(created_at, ..)| *created_at);
+
+    let Some(msg) = query.message.as_ref() else {
+        return Ok(());
+    };
+    let chat_id = msg.chat().id;
+    let session = match held.first() {
+        Some((_, cwd, _)) => describe_session(&session_id, cwd),
+        None => describe_session(&session_id, ""),
+    };
+    let status = if held.is_empty() {
+        format!("\u{25b6} {session} resumed. Nothing was held.")
+    } else {
+        format!("\u{25b6} {session} resumed.")
+    };
+    if let Err(e) = bot
+        .edit_message_text(chat_id, msg.id(), status)
+        .parse_mode(ParseMode::Html)
+        .await
+    {
+        tracing::warn!(chat_id = chat_id.0, "Failed to edit pause message: {e}");
+    }
+    if held.is_empty() {
+        return Ok(());
     }
+
+    let request_ids: Vec<Uuid> = held.into_iter().map(|(.., id)| id).collect();
+    let deliveries = [Delivery {
+        chat_id: chat_id.0,
+        silent: false,
+    }];
+    let page = Page {
+        tier: 0,
+        deliveries: &deliveries,
+    };
+    crate::ipc::server::post_together(ctx, &request_ids, page).await;
+    Ok(())
 }
 
 pub async fn handle_message(
     bot: Bot,
     msg: Message,
-    config: Arc<Config>,
-    pending_map: PendingMap,
-    reply_state: ReplyState,
+    me: Me,
+    ctx: ServerContext,
+    telegram: TelegramState,
 ) -> Result<(), teloxide::RequestError> {
+    let config = &ctx.config;
+    let reply_state = &telegram.reply_state;
     let chat_id = msg.chat.id;
 
     // Authorization check
@@ -136,6 +436,25 @@ pub async fn handle_message(
         return Ok(());
     }
 
+    if let Some(text) = msg.text().filter(|text| text.starts_with('/')) {
+        match Command::parse(text, me.username()) {
+            Ok(command) => return run_command(&bot, &msg, &ctx, &telegram, command).await,
+            // Meant for another bot in the same group
+            Err(ParseError::WrongBotName(_)) => return Ok(()),
+            // Otherwise a reply to "Type your reply:" may well start with '/'
+            Err(e) if !reply_state.contains_key(&chat_id) => {
+                let reply = if matches!(e, ParseError::UnknownCommand(_)) {
+                    command::unknown_command_text(text)
+                } else {
+                    command::help_text()
+                };
+                bot.send_message(chat_id, reply).await?;
+                return Ok(());
+            }
+            Err(_) => {}
+        }
+    }
+
     // Check if this is a reply to a ForceReply prompt
     let Some((_, (request_id, prompt_message_id))) = reply_state.remove(&chat_id) else {
         return Ok(()); // Not a reply we're tracking
@@ -145,89 +464,501 @@ pub async fn handle_message(
 
     if text.is_empty() {
         // Re-prompt
-        let new_msg = bot
+        let mut prompt = bot
             .send_message(chat_id, "Reply cannot be empty. Type your reply:")
-            .reply_markup(teloxide::types::ForceReply::new())
-            .await?;
+            .reply_markup(teloxide::types::ForceReply::new());
+        if let Some(thread_id) = msg.thread_id {
+            prompt = prompt.message_thread_id(thread_id);
+        }
+        let new_msg = prompt.await?;
         reply_state.insert(chat_id, (request_id, new_msg.id));
         return Ok(());
     }
 
     // Resolve the pending request
-    let Some((_, pending)) = pending_map.remove(&request_id) else {
+    let Some(user) = msg.from.as_ref() else {
+        return Ok(());
+    };
+    let outcome = telegram
+        .decide(request_id, Verdict::Reply(text), approver(user))
+        .await;
+    if outcome == DecisionOutcome::AlreadyHandled {
         bot.send_message(chat_id, "This request has already been handled.")
             .await?;
         return Ok(());
-    };
-
-    let response = IpcResponse::reply(request_id, text);
-
-    // Edit ALL sent messages
-    crate::bot::edit_messages_status(
-        &bot,
-        &pending.sent_messages,
-        &pending.original_text,
-        "\u{270f}\u{fe0f} Amended",
-    )
-    .await;
+    }
 
     // Delete the ForceReply prompt message (best-effort)
     let _ = bot.delete_message(chat_id, prompt_message_id).await;
 
-    // Send response via oneshot channel
-    let _ = pending.sender.send(response);
+    Ok(())
+}
 
+/// Runs a command from the bot's command menu.
+async fn run_command(
+    bot: &Bot,
+    msg: &Message,
+    ctx: &ServerContext,
+    telegram: &TelegramState,
+    command: Command,
+) -> Result<(), teloxide::RequestError> {
+    let chat_id = msg.chat.id;
+    let now = chrono::Utc::now();
+    let (text, parse_mode) = match command {
+        Command::Help | Command::Start => (command::help_text(), None),
+        Command::Status => (
+            command::status_text(
+                ctx.started_at.elapsed(),
+                &ctx.config.effective_socket_path(),
+                ctx.pending_map.len(),
+            ),
+            Some(ParseMode::Html),
+        ),
+        Command::Sessions => (
+            command::sessions_text(&session_lines(ctx, now), now),
+            Some(ParseMode::Html),
+        ),
+        Command::Pending => return pending_command(bot, msg, ctx, telegram).await,
+        Command::OnCall(args) => (
+            on_call_command(&ctx.on_call, chat_id, args.trim(), now),
+            None,
+        ),
+        Command::Dnd(args) => (dnd_command(&ctx.quiet, chat_id, args.trim(), now), None),
+        Command::Mute(args) => (
+            mute_command(&ctx.mutes, ctx.config.mute_outcome, args.trim(), now),
+            None,
+        ),
+        Command::Unmute(args) => (unmute_command(&ctx.mutes, args.trim()), None),
+    };
+    let mut send = bot.send_message(chat_id, text);
+    if let Some(parse_mode) = parse_mode {
+        send = send.parse_mode(parse_mode);
+    }
+    send.await?;
     Ok(())
 }
 
+/// Active sessions with their open request count and mute/pause state.
+fn session_lines(ctx: &ServerContext, now: chrono::DateTime<chrono::Utc>) -> Vec<SessionLine> {
+    ctx.activity
+        .active_sessions(now)
+        .into_iter()
+        .map(|info| SessionLine {
+            pending: ctx
+                .pending_map
+                .iter()
+                .filter(|p| p.request.session_id == info.session_id)
+                .count(),
+            muted: ctx.mutes.get(&info.session_id, now).is_some(),
+            paused: ctx.pauses.is_paused(&info.session_id),
+            info,
+        })
+        .collect()
+}
+
+/// Handles `/pending`: posts every open request as batched lists, one per
+/// session, oldest first.
+async fn pending_command(
+    bot: &Bot,
+    msg: &Message,
+    ctx: &ServerContext,
+    telegram: &TelegramState,
+) -> Result<(), teloxide::RequestError> {
+    let entries = ctx
+        .pending_map
+        .iter()
+        .map(|p| PendingSummary {
+            session_id: p.request.session_id.clone(),
+            cwd: p.request.cwd.clone(),
+            created_at: p.created_at,
+            item: BatchItem::new(*p.key(), p.summary.clone()),
+        })
+        .collect();
+    let sessions = group_by_session(entries);
+
+    if sessions.is_empty() {
+        let mut reply = bot.send_message(msg.chat.id, "No pending requests.");
+        if let Some(thread_id) = msg.thread_id {
+            reply = reply.message_thread_id(thread_id);
+        }
+        reply.await?;
+        return Ok(());
+    }
+
+    for (session_id, cwd, items) in sessions {
+        let title = format!(
+            "<b>\u{1f4cb} {} pending</b> \u{2014} {}",
+            items.len(),
+            describe_session(&session_id, &cwd)
+        );
+        crate::telegram::batch::send_batch(
+            bot,
+            &telegram.batches,
+            (msg.chat.id, msg.thread_id, false),
+            title,
+            items,
+        )
+        .await?;
+    }
+    Ok(())
+}
+
+/// An open request as listed by `/pending`.
+struct PendingSummary {
+    session_id: String,
+    cwd: String,
+    created_at: tokio::time::Instant,
+    item: BatchItem,
+}
+
+/// Groups requests into `(session_id, cwd, items)`, each session's items
+/// oldest first and sessions by their oldest request.
+fn group_by_session(mut entries: Vec<PendingSummary>) -> Vec<(String, String, Vec<BatchItem>)> {
+    entries.sort_by_key(|e| e.created_at);
+    let mut sessions: Vec<(String, String, Vec<BatchItem>)> = Vec::new();
+    for entry in entries {
+        match sessions.iter_mut().find(|(id, ..)| *id == entry.session_id) {
+            Some((.., items)) => items.push(entry.item),
+            None => sessions.push((entry.session_id, entry.cwd, vec![entry.item])),
+        }
+    }
+    sessions
+}
+
+/// Handles `/oncall` (show the current on-call member) and
+/// `/oncall <name|me>` (take over until the next handover).
+fn on_call_command(
+    on_call: &OnCallState,
+    chat_id: ChatId,
+    args: &str,
+    now: chrono::DateTime<chrono::Utc>,
+) -> String {
+    let Some(rota) = on_call.rota() else {
+        return "No on-call rota is configured.".to_string();
+    };
+    let format_time = |t: chrono::DateTime<chrono::Utc>| {
+        t.with_timezone(&rota.timezone)
+            .format("%a %Y-%m-%d %H:%M %Z")
+            .to_string()
+    };
+
+    if args.is_empty() {
+        let member = on_call.current(now).expect("rota is configured");
+        let names: Vec<&str> = rota.members.iter().map(|m| m.name.as_str()).collect();
+        return format!(
+            "\u{1f4df} On call: {} until {}\nRotation: {}\nUse /oncall <name> or /oncall me to swap.",
+            member.name,
+            format_time(rota.next_handover(now)),
+            names.join(", "),
+        );
+    }
+
+    let name = if args.eq_ignore_ascii_case("me") {
+        match rota.members.iter().find(|m| m.chat_id == chat_id.0) {
+            Some(member) => member.name.as_str(),
+            None => return "You are not a member of the on-call rota.".to_string(),
+        }
+    } else {
+        args
+    };
+
+    match on_call.swap_to(name, now) {
+        Some((member, until)) => {
+            tracing::info!(member = %member.name, "On-call swapped via Telegram");
+            format!(
+                "\u{1f4df} {} is now on call until {}.",
+                member.name,
+                format_time(until)
+            )
+        }
+        None => format!("Unknown rota member: {name}"),
+    }
+}
+
+/// Handles `/dnd` (show state), `/dnd off` and `/dnd <duration> [silent|deny]`.
+fn dnd_command(
+    quiet: &QuietState,
+    chat_id: ChatId,
+    args: &str,
+    now: chrono::DateTime<chrono::Utc>,
+) -> String {
+    const USAGE: &str = "Usage: /dnd 2h [silent|deny] (at most 30d), /dnd off";
+    let format_until = |until: chrono::DateTime<chrono::Utc>| {
+        until
+            .with_timezone(&chrono::Local)
+            .format("%a %H:%M")
+            .to_string()
+    };
+    let describe = |mode: QuietMode| match mode {
+        QuietMode::Silent => "requests arrive silently",
+        QuietMode::Deny => "requests are denied",
+    };
+
+    let mut words = args.split_whitespace();
+    match words.next() {
+        None => match quiet.dnd_window(chat_id.0, now) {
+            Some(window) => format!(
+                "\u{1f515} Do not disturb until {} ({}).",
+                format_until(window.until),
+                describe(window.mode)
+            ),
+            None => format!("Do not disturb is off.\n{USAGE}"),
+        },
+        Some("off") => {
+            if quiet.end_dnd(chat_id.0, now) {
+                "\u{1f514} Do not disturb is off.".to_string()
+            } else {
+                "Do not disturb was not on.".to_string()
+            }
+        }
+        Some(duration) => {
+            let Some(duration) = crate::quiet::parse_duration(duration) else {
+                return USAGE.to_string();
+            };
+            let mode = match words.next() {
+                None | Some("silent") => QuietMode::Silent,
+                Some("deny") => QuietMode::Deny,
+                Some(_) => return USAGE.to_string(),
+            };
+            let Some(window) = quiet.start_dnd(chat_id.0, duration, mode, now) else {
+                return USAGE.to_string();
+            };
+            tracing::info!(chat_id = chat_id.0, until = %window.until, "Do not disturb started");
+            format!(
+                "\u{1f515} Do not disturb until {} ({}).",
+                format_until(window.until),
+                describe(mode)
+            )
+        }
+    }
+}
+
+/// Handles `/mute` (list muted sessions) and
+/// `/mute <session> [terminal|deny|queue] [duration]`.
+fn mute_command(
+    mutes: &MuteState,
+    default_outcome: MuteOutcome,
+    args: &str,
+    now: chrono::DateTime<chrono::Utc>,
+) -> String {
+    const USAGE: &str = "Usage: /mute <session> [terminal|deny|queue] [duration, at most 30d]";
+    let describe = |mute: &Mute| match mute.until {
+        Some(until) => format!(
+            "{} until {}",
+            mute.outcome.describe(),
+            until.with_timezone(&chrono::Local).format("%a %H:%M")
+        ),
+        None => mute.outcome.describe().to_string(),
+    };
+
+    let mut words = args.split_whitespace();
+    let Some(session) = words.next() else {
+        let list = mutes.list(now);
+        if list.is_empty() {
+            return format!("No sessions are muted.\n{USAGE}");
+        }
+        let lines: Vec<String> = list
+            .iter()
+            .map(|(session, mute)| format!("\u{2022} {session}: {}", describe(mute)))
+            .collect();
+        return format!("\u{1f507} Muted sessions:\n{}", lines.join("\n"));
+    };
+
+    let mut mute = Mute {
+        outcome: default_outcome,
+        until: None,
+    };
+    for word in words {
+        if let Some(outcome) = MuteOutcome::parse(word) {
+            mute.outcome = outcome;
+        } else if let Some(until) =
+            crate::quiet::parse_duration(word).and_then(|duration| now.checked_add_signed(duration))
+        {
+            mute.until = Some(until);
+        } else {
+            return USAGE.to_string();
+        }
+    }
+    mutes.mute(session, mute);
+    tracing::info!(session = %session, "Session muted via /mute");
+    format!("\u{1f507} Muted session {session}: {}.", describe(&mute))
+}
+
+/// Handles `/unmute <session>`.
+fn unmute_command(mutes: &MuteState, args: &str) -> String {
+    if args.is_empty() {
+        return "Usage: /unmute <session>".to_string();
+    }
+    if mutes.unmute(args) {
+        format!("\u{1f50a} Unmuted session {args}.")
+    } else {
+        format!("Session {args} was not muted.")
+    }
+}
+
+/// Feedback for a "+5 min" tap that added `added`, which is less than
+/// [`EXTEND_BY`] when it ran into the overall limit.
+fn extended_text(added: Duration) -> String {
+    if added >= EXTEND_BY {
+        return "Extended by 5 minutes".to_string();
+    }
+    let secs = added.as_secs();
+    let amount = if secs >= 60 {
+        format!("{} min {} s", secs / 60, secs % 60)
+    } else {
+        format!("{secs} s")
+    };
+    format!("Extended by {amount}, up to the one-hour limit")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::models::Decision;
 
     #[test]
-    fn build_response_allow() {
-        let id = Uuid::new_v4();
-        let (resp, status) = build_callback_response(CallbackAction::Allow, id, &[]);
-        assert_eq!(resp.decision, Decision::Allow);
-        assert_eq!(resp.request_id, id);
-        assert!(status.contains("Approved"));
+    fn extension_reports_what_was_added() {
+        assert_eq!(extended_text(EXTEND_BY), "Extended by 5 minutes");
+        assert_eq!(
+            extended_text(Duration::from_secs(130)),
+            "Extended by 2 min 10 s, up to the one-hour limit"
+        );
+        assert_eq!(
+            extended_text(Duration::from_secs(42)),
+            "Extended by 42 s, up to the one-hour limit"
+        );
+    }
+
+    #[test]
+    fn pending_groups_by_session_oldest_first() {
+        let now = tokio::time::Instant::now();
+        let entry = |session: &str, age_secs: u64, summary: &str| PendingSummary {
+            session_id: session.to_string(),
+            cwd: format!("/work/{session}"),
+            created_at: now - Duration::from_secs(age_secs),
+            item: BatchItem::new(Uuid::new_v4(), summary.to_string()),
+        };
+        let sessions = group_by_session(vec![
+            entry("b", 5, "b-new"),
+            entry("a", 10, "a-new"),
+            entry("b", 30, "b-old"),
+            entry("a", 20, "a-old"),
+        ]);
+
+        let summaries: Vec<(&str, Vec<&str>)> = sessions
+            .iter()
+            .map(|(id, _, items)| {
+                (
+                    id.as_str(),
+                    items.iter().map(|i| i.summary.as_str()).collect(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summaries,
+            vec![("b", vec!["b-old", "b-new"]), ("a", vec!["a-old", "a-new"])]
+        );
+        assert_eq!(sessions[0].1, "/work/b");
+    }
+
+    #[test]
+    fn dnd_command_starts_and_ends_window() {
+        let quiet: QuietState = Arc::new(crate::quiet::Quiet::new(vec![]));
+        let now = chrono::Utc::now();
+
+        let text = dnd_command(&quiet, ChatId(1), "2h deny", now);
+        assert!(text.contains("requests are denied"));
+        let window = quiet.dnd_window(1, now).unwrap();
+        assert_eq!(window.until, now + chrono::Duration::hours(2));
+        assert_eq!(window.mode, QuietMode::Deny);
+
+        assert!(dnd_command(&quiet, ChatId(1), "", now).contains("Do not disturb until"));
+        assert!(dnd_command(&quiet, ChatId(1), "off", now).contains("is off"));
+        assert!(quiet.dnd_window(1, now).is_none());
     }
 
     #[test]
-    fn build_response_deny() {
-        let id = Uuid::new_v4();
-        let (resp, status) = build_callback_response(CallbackAction::Deny, id, &[]);
-        assert_eq!(resp.decision, Decision::Deny);
-        assert_eq!(resp.message.as_deref(), Some("Denied by user via Telegram"));
-        assert!(status.contains("Denied"));
+    fn dnd_command_rejects_bad_arguments() {
+        let quiet: QuietState = Arc::new(crate::quiet::Quiet::new(vec![]));
+        let now = chrono::Utc::now();
+        assert!(dnd_command(&quiet, ChatId(1), "soon", now).starts_with("Usage"));
+        assert!(dnd_command(&quiet, ChatId(1), "2h loud", now).starts_with("Usage"));
+        assert!(dnd_command(&quiet, ChatId(1), "99999999999d", now).starts_with("Usage"));
+        assert!(dnd_command(&quiet, ChatId(1), "31d", now).starts_with("Usage"));
+        assert!(quiet.dnd_window(1, now).is_none());
+        assert!(quiet.dnd_window(1, now).is_none());
     }
 
     #[test]
-    fn build_response_always_with_suggestion() {
-        let id = Uuid::new_v4();
-        let suggestions = vec![serde_json::json!({"tool": "Bash", "command": "ls"})];
-        let (resp, status) = build_callback_response(CallbackAction::Always, id, &suggestions);
-        assert_eq!(resp.decision, Decision::AlwaysAllow);
+    fn mute_command_uses_default_or_given_outcome() {
+        let mutes: MuteState = Arc::default();
+        let now = chrono::Utc::now();
+
+        let text = mute_command(&mutes, MuteOutcome::Terminal, "abcdef12", now);
+        assert!(text.contains("fall back to the terminal"));
         assert_eq!(
-            resp.always_allow_suggestion,
-            Some(serde_json::json!({"tool": "Bash", "command": "ls"}))
+            mutes.get("abcdef1234", now).unwrap().outcome,
+            MuteOutcome::Terminal
         );
-        assert!(status.contains("Always Allowed"));
+
+        mute_command(&mutes, MuteOutcome::Terminal, "12345678 2h queue", now);
+        let mute = mutes.get("1234567890", now).unwrap();
+        assert_eq!(mute.outcome, MuteOutcome::Queue);
+        assert_eq!(mute.until, Some(now + chrono::Duration::hours(2)));
+
+        let list = mute_command(&mutes, MuteOutcome::Terminal, "", now);
+        assert!(list.contains("abcdef12"));
+        assert!(list.contains("12345678"));
+    }
+
+    #[test]
+    fn mute_command_rejects_bad_arguments() {
+        let mutes: MuteState = Arc::default();
+        let now = chrono::Utc::now();
+        let text = mute_command(&mutes, MuteOutcome::Deny, "abcdef12 loudly", now);
+        assert!(text.starts_with("Usage"));
+        let text = mute_command(&mutes, MuteOutcome::Deny, "abcdef12 deny 99999999999d", now);
+        assert!(text.starts_with("Usage"));
+        assert!(mutes.list(now).is_empty());
+    }
+
+    #[test]
+    fn unmute_command_reports_result() {
+        let mutes: MuteState = Arc::default();
+        mute_command(&mutes, MuteOutcome::Deny, "abcdef12", chrono::Utc::now());
+        assert!(unmute_command(&mutes, "abcdef12").contains("Unmuted"));
+        assert!(unmute_command(&mutes, "abcdef12").contains("was not muted"));
+        assert!(unmute_command(&mutes, "").starts_with("Usage"));
     }
 
     #[test]
-    fn build_response_always_without_suggestion() {
-        let id = Uuid::new_v4();
-        let (resp, _status) = build_callback_response(CallbackAction::Always, id, &[]);
-        assert_eq!(resp.decision, Decision::AlwaysAllow);
-        assert!(resp.always_allow_suggestion.is_none());
+    fn on_call_command_without_rota() {
+        let on_call: OnCallState = Arc::new(crate::rota::OnCall::new(None));
+        let text = on_call_command(&on_call, ChatId(1), "", chrono::Utc::now());
+        assert!(text.contains("No on-call rota"));
     }
 
     #[test]
-    #[should_panic(expected = "Reply should be handled")]
-    fn build_response_reply_panics() {
-        let id = Uuid::new_v4();
-        build_callback_response(CallbackAction::Reply, id, &[]);
+    fn on_call_command_swaps_to_sender() {
+        let rota: crate::rota::RotaConfig = toml::from_str(
+            r#"
+            timezone = "UTC"
+            schedule = "daily"
+            start_date = "2026-01-05"
+            members = [{ name = "alice", chat_id = 1 }, { name = "bob", chat_id = 2 }]
+            "#,
+        )
+        .unwrap();
+        let on_call: OnCallState = Arc::new(crate::rota::OnCall::new(Some(rota)));
+        let now = chrono::Utc::now();
+
+        let text = on_call_command(&on_call, ChatId(2), "me", now);
+        assert!(text.contains("bob is now on call"));
+        assert_eq!(on_call.current(now).unwrap().name, "bob");
+
+        let text = on_call_command(&on_call, ChatId(3), "me", now);
+        assert!(text.contains("not a member"));
+
+        let text = on_call_command(&on_call, ChatId(1), "", now);
+        assert!(text.contains("On call: bob"));
     }
 }
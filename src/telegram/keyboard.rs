@@ -22,7 +22,13 @@ pub fn make_keyboard(request_id: Uuid, has_permission_suggestions: bool) -> Inli
         ));
     }
 
-    InlineKeyboardMarkup::new(vec![buttons])
+    let extra = vec![
+        InlineKeyboardButton::callback("\u{23f3} +5 min", format!("{id}:extend")),
+        InlineKeyboardButton::callback("\u{1f507} Mute session", format!("{id}:mute")),
+        InlineKeyboardButton::callback("\u{23f8} Pause session", format!("{id}:pause")),
+    ];
+
+    InlineKeyboardMarkup::new(vec![buttons, extra])
 }
 
 #[cfg(test)]
@@ -45,6 +51,25 @@ mod tests {
         assert_eq!(buttons.len(), 4);
     }
 
+    #[test]
+    fn keyboard_has_session_row() {
+        let id = Uuid::new_v4();
+        let kb = make_keyboard(id, false);
+        assert_eq!(kb.inline_keyboard.len(), 2);
+        assert_eq!(kb.inline_keyboard[1].len(), 3);
+        for (button, action) in kb.inline_keyboard[1]
+            .iter()
+            .zip(["extend", "mute", "pause"])
+        {
+            match &button.kind {
+                teloxide::types::InlineKeyboardButtonKind::CallbackData(data) => {
+                    assert_eq!(data, &format!("{id}:{action}"));
+                }
+                _ => panic!("Expected CallbackData button kind"),
+            }
+        }
+    }
+
     #[test]
     fn button_callback_data_format() {
         let id = Uuid::new_v4();
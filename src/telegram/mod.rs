@@ -1,4 +1,10 @@
+pub mod batch;
 pub mod callback_data;
+pub mod channel;
+pub mod command;
+pub mod dashboard;
 pub mod formatter;
 pub mod handler;
 pub mod keyboard;
+pub mod reaction;
+pub mod topics;
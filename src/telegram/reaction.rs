@@ -0,0 +1,92 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+use teloxide::types::ReactionType;
+
+/// What reacting to a permission message with an emoji does.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ReactionAction {
+    Allow,
+    Deny,
+}
+
+/// 👍 allows, 👎 denies.
+pub fn default_reactions() -> HashMap<String, ReactionAction> {
+    HashMap::from([
+        ("\u{1f44d}".to_string(), ReactionAction::Allow),
+        ("\u{1f44e}".to_string(), ReactionAction::Deny),
+    ])
+}
+
+/// The action of the first mapped emoji in `new` that wasn't already in
+/// `old`. Removing a reaction does nothing.
+pub fn added_action(
+    mapping: &HashMap<String, ReactionAction>,
+    old: &[ReactionType],
+    new: &[ReactionType],
+) -> Option<ReactionAction> {
+    new.iter()
+        .filter(|reaction| !old.contains(reaction))
+        .find_map(|reaction| match reaction {
+            ReactionType::Emoji { emoji } => mapping.get(emoji).copied(),
+            ReactionType::CustomEmoji { .. } | ReactionType::Paid => None,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn emoji(emoji: &str) -> ReactionType {
+        ReactionType::Emoji {
+            emoji: emoji.to_string(),
+        }
+    }
+
+    #[test]
+    fn thumbs_map_to_allow_and_deny() {
+        let mapping = default_reactions();
+        assert_eq!(
+            added_action(&mapping, &[], &[emoji("\u{1f44d}")]),
+            Some(ReactionAction::Allow)
+        );
+        assert_eq!(
+            added_action(&mapping, &[], &[emoji("\u{1f44e}")]),
+            Some(ReactionAction::Deny)
+        );
+        assert_eq!(added_action(&mapping, &[], &[emoji("\u{1f525}")]), None);
+    }
+
+    #[test]
+    fn only_newly_added_reactions_count() {
+        let mapping = default_reactions();
+        let thumbs_up = emoji("\u{1f44d}");
+        // Removing 👍 (or keeping it while adding something else) is ignored
+        assert_eq!(
+            added_action(&mapping, std::slice::from_ref(&thumbs_up), &[]),
+            None
+        );
+        assert_eq!(
+            added_action(
+                &mapping,
+                std::slice::from_ref(&thumbs_up),
+                &[thumbs_up.clone(), emoji("\u{1f525}")]
+            ),
+            None
+        );
+        assert_eq!(
+            added_action(&mapping, &[thumbs_up], &[emoji("\u{1f44e}")]),
+            Some(ReactionAction::Deny)
+        );
+    }
+
+    #[test]
+    fn custom_mapping() {
+        let mapping = HashMap::from([("\u{1f44c}".to_string(), ReactionAction::Allow)]);
+        assert_eq!(
+            added_action(&mapping, &[], &[emoji("\u{1f44c}")]),
+            Some(ReactionAction::Allow)
+        );
+        assert_eq!(added_action(&mapping, &[], &[emoji("\u{1f44d}")]), None);
+    }
+}
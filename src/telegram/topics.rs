@@ -0,0 +1,351 @@
+use crate::models::IpcRequest;
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use teloxide::prelude::*;
+use teloxide::types::{
+    ChatFullInfo, ChatFullInfoKind, ChatFullInfoPublicKind, MessageId, ThreadId,
+};
+use teloxide::{ApiError, RequestError};
+
+/// Telegram limits forum topic names to 128 characters.
+const MAX_TOPIC_NAME_CHARS: usize = 128;
+
+/// One persisted session → topic mapping.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[allow(clippy::struct_field_names)]
+struct TopicEntry {
+    chat_id: i64,
+    session_id: String,
+    thread_id: i32,
+}
+
+#[derive(Default)]
+struct TopicsInner {
+    /// `(chat_id, session_id)` → forum thread.
+    threads: HashMap<TopicKey, i32>,
+    /// Forum capability per chat, as reported by `getChat`.
+    forum_chats: HashMap<i64, bool>,
+}
+
+type TopicKey = (i64, String);
+
+/// Posts each Claude session's requests into its own forum topic when the
+/// target chat is a forum-enabled supergroup.
+pub struct Topics {
+    enabled: bool,
+    path: PathBuf,
+    /// Only held while reading or updating the maps, never across API calls.
+    inner: Mutex<TopicsInner>,
+    /// Held while a session's topic is being created in a chat, so
+    /// concurrent requests from a new session don't create duplicates.
+    creating: DashMap<TopicKey, Arc<tokio::sync::Mutex<()>>>,
+}
+
+pub type TopicState = Arc<Topics>;
+
+impl Topics {
+    /// Loads persisted mappings from `path` (missing or unreadable files start empty).
+    pub fn load(enabled: bool, path: PathBuf) -> Self {
+        let threads = read_entries(&path)
+            .into_iter()
+            .map(|e| ((e.chat_id, e.session_id), e.thread_id))
+            .collect();
+        Self {
+            enabled,
+            path,
+            inner: Mutex::new(TopicsInner {
+                threads,
+                forum_chats: HashMap::new(),
+            }),
+            creating: DashMap::new(),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, TopicsInner> {
+        self.inner.lock().expect("topics lock poisoned")
+    }
+
+    fn thread(&self, key: &TopicKey) -> Option<ThreadId> {
+        self.lock()
+            .threads
+            .get(key)
+            .map(|&thread_id| ThreadId(MessageId(thread_id)))
+    }
+
+    /// Returns the forum thread for `request`'s session in `chat`, creating
+    /// the topic on first use. Returns `None` for chats without topics.
+    pub async fn thread_for(
+        &self,
+        bot: &Bot,
+        chat: ChatId,
+        request: &IpcRequest,
+    ) -> Option<ThreadId> {
+        if !self.enabled {
+            return None;
+        }
+        let key = (chat.0, request.session_id.clone());
+        if let Some(thread_id) = self.thread(&key) {
+            return Some(thread_id);
+        }
+
+        let creating = self.creating.entry(key.clone()).or_default().clone();
+        let thread_id = {
+            let _creating = creating.lock().await;
+            match self.thread(&key) {
+                Some(thread_id) => Some(thread_id),
+                None => self.create(bot, chat, request, key.clone()).await,
+            }
+        };
+        self.creating.remove(&key);
+        thread_id
+    }
+
+    /// Creates the session's topic if `chat` is a forum, and remembers it.
+    #[allow(clippy::significant_drop_tightening)]
+    async fn create(
+        &self,
+        bot: &Bot,
+        chat: ChatId,
+        request: &IpcRequest,
+        key: TopicKey,
+    ) -> Option<ThreadId> {
+        let known = self.lock().forum_chats.get(&chat.0).copied();
+        let is_forum = if let Some(known) = known {
+            known
+        } else {
+            let known = match bot.get_chat(chat).await {
+                Ok(info) => is_forum(&info),
+                Err(e) => {
+                    tracing::warn!(chat_id = chat.0, "Failed to look up chat: {e}");
+                    return None;
+                }
+            };
+            self.lock().forum_chats.insert(chat.0, known);
+            known
+        };
+        if !is_forum {
+            return None;
+        }
+
+        let topic = match bot.create_forum_topic(chat, topic_name(request)).await {
+            Ok(topic) => topic,
+            Err(e) => {
+                tracing::warn!(chat_id = chat.0, "Failed to create forum topic: {e}");
+                return None;
+            }
+        };
+
+        let ThreadId(MessageId(thread_id)) = topic.thread_id;
+        tracing::info!(
+            chat_id = chat.0,
+            session_id = %request.session_id,
+            thread_id,
+            "Created forum topic for session"
+        );
+        let mut inner = self.lock();
+        inner.threads.insert(key, thread_id);
+        self.persist(&inner.threads);
+        Some(topic.thread_id)
+    }
+
+    /// Drops the session's topic in `chat` after Telegram rejected it (e.g.
+    /// it was deleted or closed), so the next request creates a new one.
+    #[allow(clippy::significant_drop_tightening)]
+    pub fn forget(&self, chat: ChatId, session_id: &str, thread_id: ThreadId) {
+        let ThreadId(MessageId(thread_id)) = thread_id;
+        let key = (chat.0, session_id.to_string());
+        let mut inner = self.lock();
+        if inner.threads.get(&key) == Some(&thread_id) {
+            inner.threads.remove(&key);
+            self.persist(&inner.threads);
+            tracing::info!(
+                chat_id = chat.0,
+                session_id,
+                thread_id,
+                "Forgot forum topic"
+            );
+        }
+    }
+
+    fn persist(&self, threads: &HashMap<TopicKey, i32>) {
+        if let Err(e) = write_entries(&self.path, threads) {
+            tracing::warn!(
+                "Failed to persist forum topics to {}: {e}",
+                self.path.display()
+            );
+        }
+    }
+}
+
+/// Whether Telegram rejected a message because its forum topic is gone or
+/// closed.
+pub fn is_thread_error(error: &RequestError) -> bool {
+    matches!(
+        error,
+        RequestError::Api(ApiError::Unknown(text))
+            if text.contains("message thread not found")
+                || text.contains("TOPIC_CLOSED")
+                || text.contains("TOPIC_DELETED")
+    )
+}
+
+fn is_forum(info: &ChatFullInfo) -> bool {
+    match &info.kind {
+        ChatFullInfoKind::Public(public) => {
+            matches!(&public.kind, ChatFullInfoPublicKind::Supergroup(sg) if sg.is_forum)
+        }
+        ChatFullInfoKind::Private(_) => false,
+    }
+}
+
+/// "project: first prompt", falling back to the short session id.
+fn topic_name(request: &IpcRequest) -> String {
+    let project = Path::new(&request.cwd)
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("unknown");
+    let summary = request.first_prompt.as_deref().map_or_else(
+        || request.session_id.chars().take(8).collect(),
+        |prompt| prompt.split_whitespace().collect::<Vec<_>>().join(" "),
+    );
+    let name = format!("{project}: {summary}");
+    if name.chars().count() <= MAX_TOPIC_NAME_CHARS {
+        name
+    } else {
+        let mut truncated: String = name.chars().take(MAX_TOPIC_NAME_CHARS - 1).collect();
+        truncated.push('\u{2026}');
+        truncated
+    }
+}
+
+fn read_entries(path: &Path) -> Vec<TopicEntry> {
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+fn write_entries(path: &Path, threads: &HashMap<TopicKey, i32>) -> anyhow::Result<()> {
+    let mut entries: Vec<TopicEntry> = threads
+        .iter()
+        .map(|((chat_id, session_id), &thread_id)| TopicEntry {
+            chat_id: *chat_id,
+            session_id: session_id.clone(),
+            thread_id,
+        })
+        .collect();
+    entries.sort_by(|a, b| (a.chat_id, &a.session_id).cmp(&(b.chat_id, &b.session_id)));
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    // Write-then-rename so a crash never leaves a half-written file
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, serde_json::to_string_pretty(&entries)?)?;
+    std::fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    fn make_request(first_prompt: Option<&str>) -> IpcRequest {
+        IpcRequest {
+            request_id: Uuid::new_v4(),
+            tool_name: "Bash".to_string(),
+            tool_input: serde_json::json!({"command": "ls"}),
+            cwd: "/home/user/my-project".to_string(),
+            session_id: "abcdef1234567890".to_string(),
+            permission_suggestions: vec![],
+            assistant_context: None,
+            first_prompt: first_prompt.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn topic_name_uses_project_and_first_prompt() {
+        let req = make_request(Some("Fix the\nflaky   test"));
+        assert_eq!(topic_name(&req), "my-project: Fix the flaky test");
+    }
+
+    #[test]
+    fn topic_name_falls_back_to_session() {
+        let req = make_request(None);
+        assert_eq!(topic_name(&req), "my-project: abcdef12");
+    }
+
+    #[test]
+    fn topic_name_truncated_to_telegram_limit() {
+        let long = "\u{e9}".repeat(300);
+        let req = make_request(Some(&long));
+        let name = topic_name(&req);
+        assert_eq!(name.chars().count(), MAX_TOPIC_NAME_CHARS);
+        assert!(name.ends_with('\u{2026}'));
+    }
+
+    #[test]
+    fn entries_roundtrip_through_file() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("state").join("topics.json");
+        let threads = HashMap::from([
+            ((-1001, "s1".to_string()), 7),
+            ((-1001, "s2".to_string()), 9),
+        ]);
+
+        write_entries(&path, &threads).unwrap();
+
+        let topics = Topics::load(true, path);
+        assert_eq!(topics.lock().threads, threads);
+    }
+
+    #[test]
+    fn load_from_missing_file_starts_empty() {
+        let tmp = tempfile::tempdir().unwrap();
+        let topics = Topics::load(true, tmp.path().join("missing.json"));
+        assert!(topics.lock().threads.is_empty());
+    }
+
+    #[test]
+    fn forgotten_topics_are_dropped_from_the_file() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("topics.json");
+        let threads = HashMap::from([((-1001, "s1".to_string()), 7)]);
+        write_entries(&path, &threads).unwrap();
+        let topics = Topics::load(true, path.clone());
+
+        // A stale thread id leaves a newer mapping alone
+        topics.forget(ChatId(-1001), "s1", ThreadId(MessageId(6)));
+        assert_eq!(topics.lock().threads, threads);
+
+        topics.forget(ChatId(-1001), "s1", ThreadId(MessageId(7)));
+        assert!(topics.lock().threads.is_empty());
+        assert!(read_entries(&path).is_empty());
+    }
+
+    #[test]
+    fn thread_errors_are_recognised() {
+        let gone = RequestError::Api(ApiError::Unknown(
+            "Bad Request: message thread not found".to_string(),
+        ));
+        assert!(is_thread_error(&gone));
+        let closed = RequestError::Api(ApiError::Unknown("Bad Request: TOPIC_CLOSED".to_string()));
+        assert!(is_thread_error(&closed));
+        assert!(!is_thread_error(&RequestError::Api(ApiError::BotBlocked)));
+    }
+
+    #[tokio::test]
+    async fn disabled_topics_never_call_telegram() {
+        let tmp = tempfile::tempdir().unwrap();
+        let topics = Topics::load(false, tmp.path().join("topics.json"));
+        let bot = Bot::new("fake-token");
+        let thread = topics
+            .thread_for(&bot, ChatId(-100), &make_request(None))
+            .await;
+        assert!(thread.is_none());
+    }
+}
@@ -0,0 +1,549 @@
+use crate::channel::Verdict;
+use crate::config::Config;
+use crate::control::{ControlCommand, ControlEvent, OpenRequest};
+use crate::telegram::formatter::{format_time_left, plain_tool_input, project_name};
+use crossterm::event::{Event, EventStream, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::{cursor, execute, queue, style, terminal};
+use futures::StreamExt;
+use std::io::Write;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::UnixStream;
+use tokio::time::{Duration, Instant};
+use uuid::Uuid;
+
+/// Follows the bot's open requests over its control socket and answers
+/// them from the keyboard until `q`.
+pub async fn run_tui(config: &Config) -> anyhow::Result<()> {
+    let path = config.effective_control_socket_path();
+    let stream = UnixStream::connect(&path).await.map_err(|e| {
+        anyhow::anyhow!(
+            "Cannot connect to {} ({e}); is `vibe-reachout bot` running with `tui = true`?",
+            path.display()
+        )
+    })?;
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+
+    let _terminal = RawTerminal::enter()?;
+    let mut keys = EventStream::new();
+    let mut tick = tokio::time::interval(Duration::from_secs(1));
+    let mut state = TuiState::default();
+    loop {
+        draw(&state)?;
+        tokio::select! {
+            line = lines.next_line() => {
+                let Some(line) = line? else {
+                    anyhow::bail!("The bot closed the control socket");
+                };
+                match serde_json::from_str::<ControlEvent>(&line) {
+                    Ok(event) => state.apply(event, Instant::now()),
+                    Err(e) => state.status = format!("Bad message from the bot: {e}"),
+                }
+            }
+            event = keys.next() => {
+                let Some(event) = event else {
+                    return Ok(());
+                };
+                let Event::Key(key) = event? else {
+                    continue;
+                };
+                match state.handle_key(key) {
+                    Action::None => {}
+                    Action::Quit => return Ok(()),
+                    Action::Send(command) => {
+                        let mut line = serde_json::to_string(&command)?;
+                        line.push('\n');
+                        writer.write_all(line.as_bytes()).await?;
+                    }
+                }
+            }
+            _ = tick.tick() => {}
+        }
+    }
+}
+
+/// Raw mode on the alternate screen, restored on drop, even on error.
+struct RawTerminal;
+
+impl RawTerminal {
+    fn enter() -> anyhow::Result<Self> {
+        terminal::enable_raw_mode()?;
+        execute!(
+            std::io::stdout(),
+            terminal::EnterAlternateScreen,
+            cursor::Hide
+        )?;
+        Ok(Self)
+    }
+}
+
+impl Drop for RawTerminal {
+    fn drop(&mut self) {
+        let _ = execute!(
+            std::io::stdout(),
+            cursor::Show,
+            terminal::LeaveAlternateScreen
+        );
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+fn draw(state: &TuiState) -> anyhow::Result<()> {
+    let (width, height) = terminal::size()?;
+    let mut stdout = std::io::stdout();
+    queue!(
+        stdout,
+        cursor::MoveTo(0, 0),
+        terminal::Clear(terminal::ClearType::All)
+    )?;
+    let lines = state.render(usize::from(width), usize::from(height), Instant::now());
+    for (row, line) in (0..height).zip(&lines) {
+        queue!(stdout, cursor::MoveTo(0, row), style::Print(line))?;
+    }
+    stdout.flush()?;
+    Ok(())
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+enum Mode {
+    #[default]
+    Browse,
+    /// Typing a reply to the selected request.
+    Reply(String),
+}
+
+#[derive(Debug)]
+enum Action {
+    None,
+    Quit,
+    Send(ControlCommand),
+}
+
+#[derive(Debug)]
+struct TuiState {
+    requests: Vec<OpenRequest>,
+    /// When the list arrived, to count `seconds_left` down from.
+    received_at: Instant,
+    selected: usize,
+    /// Lines of the details pane scrolled past.
+    scroll: usize,
+    mode: Mode,
+    status: String,
+}
+
+impl Default for TuiState {
+    fn default() -> Self {
+        Self {
+            requests: Vec::new(),
+            received_at: Instant::now(),
+            selected: 0,
+            scroll: 0,
+            mode: Mode::Browse,
+            status: "Connected".to_string(),
+        }
+    }
+}
+
+impl TuiState {
+    fn apply(&mut self, event: ControlEvent, now: Instant) {
+        match event {
+            ControlEvent::Requests { requests } => {
+                // Stay on the same request while others come and go
+                let selected_id = self.selected_id();
+                self.requests = requests;
+                self.received_at = now;
+                let index = selected_id.and_then(|id| {
+                    self.requests
+                        .iter()
+                        .position(|r| r.request.request_id == id)
+                });
+                if let Some(index) = index {
+                    self.selected = index;
+                } else {
+                    self.selected = self.selected.min(self.requests.len().saturating_sub(1));
+                    self.scroll = 0;
+                    // The reply was for a request that's gone
+                    self.mode = Mode::Browse;
+                }
+            }
+            ControlEvent::Feedback { text, .. } => self.status = text,
+        }
+    }
+
+    fn selected_id(&self) -> Option<Uuid> {
+        self.requests
+            .get(self.selected)
+            .map(|r| r.request.request_id)
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) -> Action {
+        if key.kind == KeyEventKind::Release {
+            return Action::None;
+        }
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            return Action::Quit;
+        }
+        if let Mode::Reply(text) = &mut self.mode {
+            match key.code {
+                KeyCode::Esc => self.mode = Mode::Browse,
+                KeyCode::Enter if !text.trim().is_empty() => {
+                    let verdict = Verdict::Reply(text.trim().to_string());
+                    self.mode = Mode::Browse;
+                    return self.decide(verdict);
+                }
+                KeyCode::Backspace => {
+                    text.pop();
+                }
+                KeyCode::Char(c) => text.push(c),
+                _ => {}
+            }
+            return Action::None;
+        }
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return Action::Quit,
+            KeyCode::Char('j') | KeyCode::Down => self.select(self.selected + 1),
+            KeyCode::Char('k') | KeyCode::Up => self.select(self.selected.saturating_sub(1)),
+            KeyCode::PageDown | KeyCode::Char(' ') => self.scroll += 10,
+            KeyCode::PageUp => self.scroll = self.scroll.saturating_sub(10),
+            KeyCode::Char('a' | 'y') => return self.decide(Verdict::Allow),
+            KeyCode::Char('d' | 'n') => return self.decide(Verdict::Deny),
+            KeyCode::Char('A') => match self.requests.get(self.selected) {
+                Some(request) if request.offers_always_allow() => {
+                    return self.decide(Verdict::AlwaysAllow);
+                }
+                Some(_) => self.status = "Always Allow isn't offered here".to_string(),
+                None => {}
+            },
+            KeyCode::Char('r') if self.selected_id().is_some() => {
+                self.mode = Mode::Reply(String::new());
+            }
+            _ => {}
+        }
+        Action::None
+    }
+
+    fn select(&mut self, index: usize) {
+        let index = index.min(self.requests.len().saturating_sub(1));
+        if index != self.selected {
+            self.selected = index;
+            self.scroll = 0;
+        }
+    }
+
+    fn decide(&mut self, verdict: Verdict) -> Action {
+        let Some(request_id) = self.selected_id() else {
+            return Action::None;
+        };
+        self.status = "Sending…".to_string();
+        Action::Send(ControlCommand::Decide {
+            request_id,
+            verdict,
+        })
+    }
+
+    /// The screen as lines no wider than `width`: the list, the selected
+    /// request in full, then the keys and the last feedback at the bottom.
+    fn render(&self, width: usize, height: usize, now: Instant) -> Vec<String> {
+        let elapsed = now.saturating_duration_since(self.received_at);
+        let mut top = vec![format!("vibe-reachout · {} pending", self.requests.len())];
+        for (index, request) in self.requests.iter().enumerate() {
+            let marker = if index == self.selected { '>' } else { ' ' };
+            let left = Duration::from_secs(request.seconds_left).saturating_sub(elapsed);
+            top.push(format!(
+                "{marker} {} · {} · {}",
+                format_time_left(left),
+                project_name(&request.request.cwd),
+                request.summary
+            ));
+        }
+        if self.requests.is_empty() {
+            top.push("  Nothing waiting for you.".to_string());
+        }
+        top.push(String::new());
+
+        let bottom = [
+            match &self.mode {
+                Mode::Browse => {
+                    "j/k move · a allow · d deny · A always · r reply · q quit".to_string()
+                }
+                Mode::Reply(text) => format!("Reply (Enter sends, Esc cancels): {text}_"),
+            },
+            self.status.clone(),
+        ];
+
+        let mut lines: Vec<String> = top.iter().flat_map(|l| wrap(l, width)).collect();
+        let room = height.saturating_sub(lines.len() + bottom.len());
+        let details: Vec<String> = self
+            .requests
+            .get(self.selected)
+            .map(details)
+            .unwrap_or_default()
+            .iter()
+            .flat_map(|l| wrap(l, width))
+            .collect();
+        let scroll = self.scroll.min(details.len().saturating_sub(1));
+        let mut shown: Vec<String> = details.iter().skip(scroll).take(room).cloned().collect();
+        if details.len() > scroll + room && room > 0 {
+            shown.pop();
+            shown.push(format!(
+                "… {} more lines (PgDn)",
+                details.len() - scroll - room + 1
+            ));
+        }
+        let shown_len = shown.len();
+        lines.extend(shown);
+        lines.extend(std::iter::repeat_n(String::new(), room - shown_len));
+        lines.extend(bottom.iter().map(|l| truncate(l, width)));
+        lines
+    }
+}
+
+/// Everything about one request: where it's from and the tool input in
+/// full.
+fn details(request: &OpenRequest) -> Vec<String> {
+    let ipc = &request.request;
+    let mut lines = vec![
+        format!("{} in {}", ipc.tool_name, ipc.cwd),
+        format!("session {}", ipc.session_id),
+    ];
+    if request.required_approvals > 1 {
+        lines.push(format!(
+            "{}/{} approvals{}",
+            request.approvals.len(),
+            request.required_approvals,
+            if request.approvals.is_empty() {
+                String::new()
+            } else {
+                format!(": {}", request.approvals.join(", "))
+            }
+        ));
+    }
+    if let Some(context) = &ipc.assistant_context {
+        lines.push(String::new());
+        lines.extend(context.lines().map(|l| format!("│ {l}")));
+    }
+    lines.push(String::new());
+
+    lines.extend(plain_tool_input(ipc));
+    lines
+}
+
+/// `line` safe to print: tabs become spaces, and other control characters,
+/// which could move the cursor or restyle the terminal, become U+FFFD.
+fn printable(line: &str) -> String {
+    let mut out = String::with_capacity(line.len());
+    for c in line.chars() {
+        match c {
+            '\t' => out.push_str("    "),
+            c if c.is_control() => out.push('\u{fffd}'),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Splits a line into rows of at most `width` printable characters.
+fn wrap(line: &str, width: usize) -> Vec<String> {
+    let chars: Vec<char> = printable(line).chars().collect();
+    if chars.is_empty() || width == 0 {
+        return vec![String::new()];
+    }
+    chars
+        .chunks(width)
+        .map(|row| row.iter().collect())
+        .collect()
+}
+
+fn truncate(line: &str, width: usize) -> String {
+    printable(line).chars().take(width).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::IpcRequest;
+    use serde_json::{Value, json};
+
+    fn make_request(tool_name: &str, tool_input: Value) -> OpenRequest {
+        OpenRequest {
+            request: IpcRequest {
+                request_id: Uuid::new_v4(),
+                tool_name: tool_name.to_string(),
+                tool_input,
+                cwd: "/home/me/proj".to_string(),
+                session_id: "abcdef1234".to_string(),
+                permission_suggestions: vec![],
+                assistant_context: None,
+                first_prompt: None,
+            },
+            summary: format!("{tool_name}: something"),
+            seconds_left: 150,
+            required_approvals: 1,
+            approvals: vec![],
+        }
+    }
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    fn state_with(requests: Vec<OpenRequest>) -> TuiState {
+        let mut state = TuiState::default();
+        state.apply(ControlEvent::Requests { requests }, Instant::now());
+        state
+    }
+
+    fn sent(action: Action) -> (Uuid, Verdict) {
+        let Action::Send(ControlCommand::Decide {
+            request_id,
+            verdict,
+        }) = action
+        else {
+            panic!("expected a decision, got {action:?}");
+        };
+        (request_id, verdict)
+    }
+
+    #[test]
+    fn keys_decide_the_selected_request() {
+        let first = make_request("Bash", json!({"command": "ls"}));
+        let second = make_request("Bash", json!({"command": "pwd"}));
+        let second_id = second.request.request_id;
+        let mut state = state_with(vec![first, second]);
+
+        assert!(matches!(
+            state.handle_key(key(KeyCode::Char('j'))),
+            Action::None
+        ));
+        assert_eq!(
+            sent(state.handle_key(key(KeyCode::Char('a')))),
+            (second_id, Verdict::Allow)
+        );
+        assert_eq!(
+            sent(state.handle_key(key(KeyCode::Char('n')))),
+            (second_id, Verdict::Deny)
+        );
+        // Always Allow needs a suggestion to apply
+        assert!(matches!(
+            state.handle_key(key(KeyCode::Char('A'))),
+            Action::None
+        ));
+        assert!(matches!(
+            state.handle_key(key(KeyCode::Char('q'))),
+            Action::Quit
+        ));
+    }
+
+    #[test]
+    fn reply_is_typed_then_sent() {
+        let request = make_request("Bash", json!({"command": "ls"}));
+        let id = request.request.request_id;
+        let mut state = state_with(vec![request]);
+
+        state.handle_key(key(KeyCode::Char('r')));
+        for c in "use qx".chars() {
+            assert!(matches!(
+                state.handle_key(key(KeyCode::Char(c))),
+                Action::None
+            ));
+        }
+        state.handle_key(key(KeyCode::Backspace));
+        state.handle_key(key(KeyCode::Char('a')));
+        assert_eq!(
+            sent(state.handle_key(key(KeyCode::Enter))),
+            (id, Verdict::Reply("use qa".to_string()))
+        );
+        assert_eq!(state.mode, Mode::Browse);
+
+        state.handle_key(key(KeyCode::Char('r')));
+        state.handle_key(key(KeyCode::Char('x')));
+        state.handle_key(key(KeyCode::Esc));
+        assert_eq!(state.mode, Mode::Browse);
+    }
+
+    #[test]
+    fn selection_follows_its_request() {
+        let first = make_request("Bash", json!({"command": "ls"}));
+        let second = make_request("Bash", json!({"command": "pwd"}));
+        let second_id = second.request.request_id;
+        let mut state = state_with(vec![first, second.clone()]);
+        state.handle_key(key(KeyCode::Down));
+
+        let newer = make_request("Bash", json!({"command": "whoami"}));
+        state.apply(
+            ControlEvent::Requests {
+                requests: vec![newer, second],
+            },
+            Instant::now(),
+        );
+        assert_eq!(state.selected_id(), Some(second_id));
+
+        state.apply(ControlEvent::Requests { requests: vec![] }, Instant::now());
+        assert_eq!(state.selected_id(), None);
+        assert!(matches!(
+            state.handle_key(key(KeyCode::Char('a'))),
+            Action::None
+        ));
+    }
+
+    #[test]
+    fn renders_the_selected_request_in_full() {
+        let command = format!("echo {}", "x".repeat(200));
+        let state = state_with(vec![
+            make_request("Bash", json!({"command": command})),
+            make_request(
+                "Edit",
+                json!({"file_path": "/src/a.rs", "old_string": "a", "new_string": "b"}),
+            ),
+        ]);
+        let lines = state.render(80, 24, state.received_at);
+        assert_eq!(lines.len(), 24);
+        assert!(lines.iter().all(|l| l.chars().count() <= 80));
+        assert_eq!(lines[1], "> ⏳ 3 min left · proj · Bash: something");
+        assert_eq!(lines.concat().matches('x').count(), 200);
+        assert!(lines[22].starts_with("j/k move"));
+
+        let mut state = state;
+        state.handle_key(key(KeyCode::Down));
+        let lines = state.render(80, 24, state.received_at + Duration::from_mins(2));
+        assert_eq!(lines[2], "> ⏳ 30s left · proj · Edit: something");
+        assert!(lines.contains(&"- a".to_string()));
+        assert!(lines.contains(&"+ b".to_string()));
+    }
+
+    #[test]
+    fn control_characters_are_not_printed() {
+        let mut state = state_with(vec![make_request(
+            "Bash",
+            json!({"command": "echo ok\r\u{1b}[2J\u{1b}]0;pwned\u{7}\tdone"}),
+        )]);
+        state.status = "bad \u{1b}[31mstatus".to_string();
+        let lines = state.render(80, 24, state.received_at);
+        assert!(lines.iter().all(|l| !l.chars().any(char::is_control)));
+        assert!(
+            lines.contains(
+                &"echo ok\u{fffd}\u{fffd}[2J\u{fffd}]0;pwned\u{fffd}    done".to_string()
+            )
+        );
+        assert_eq!(lines[23], "bad \u{fffd}[31mstatus");
+    }
+
+    #[test]
+    fn long_details_scroll() {
+        let content = (0..100)
+            .map(|i| format!("line {i}"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let mut state = state_with(vec![make_request(
+            "Write",
+            json!({"file_path": "/a.txt", "content": content}),
+        )]);
+        let lines = state.render(80, 20, state.received_at);
+        assert_eq!(lines.len(), 20);
+        assert_eq!(lines[3], "Write in /home/me/proj");
+        assert_eq!(lines[17], "… 90 more lines (PgDn)");
+        state.handle_key(key(KeyCode::PageDown));
+        let lines = state.render(80, 20, state.received_at);
+        assert_eq!(lines[3], "line 6");
+    }
+}
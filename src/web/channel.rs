@@ -0,0 +1,377 @@
+use crate::channel::open::OpenRequests;
+use crate::channel::{
+    ApprovalChannel, DecisionOutcome, DecisionQueue, DecisionStream, Page, RequestView,
+    StatusUpdate, Verdict,
+};
+use crate::http::{Body, Handler, bearer_token, html_response, text_response, tokens_match};
+use crate::ipc::server::PendingMap;
+use crate::models::Approver;
+use crate::web::WebConfig;
+use crate::web::page;
+use bytes::Bytes;
+use futures::future::BoxFuture;
+use hyper::header::{CACHE_CONTROL, CONTENT_TYPE, COOKIE, LOCATION, SET_COOKIE};
+use hyper::{Method, Request, Response, StatusCode};
+use serde::Deserialize;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tokio::time::Instant;
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
+
+/// Cookie holding the token once the UI has been opened with it.
+const COOKIE_NAME: &str = "reachout_token";
+
+/// A comment line sent this often keeps idle event streams open.
+const KEEPALIVE_INTERVAL: Duration = Duration::from_secs(15);
+
+/// The browser UI as an approval channel. It tells browsers to re-fetch the
+/// list of open requests whenever it changes.
+pub struct WebChannel {
+    config: WebConfig,
+    open: OpenRequests,
+    decisions: DecisionQueue,
+}
+
+pub type WebState = Arc<WebChannel>;
+
+impl WebChannel {
+    pub fn new(config: WebConfig, pending_map: PendingMap) -> Self {
+        Self {
+            config,
+            open: OpenRequests::new(pending_map),
+            decisions: DecisionQueue::default(),
+        }
+    }
+
+    /// Serves the UI until `cancel_token` fires.
+    pub async fn serve(self: Arc<Self>, cancel_token: CancellationToken) -> anyhow::Result<()> {
+        let addr = self.config.listen;
+        let handler: Handler = Arc::new(move |request| {
+            let channel = self.clone();
+            Box::pin(async move { channel.handle_request(&request).await })
+        });
+        crate::http::serve(addr, handler, cancel_token).await
+    }
+
+    async fn handle_request(&self, request: &Request<Bytes>) -> Response<Body> {
+        let path = request.uri().path();
+        if request.method() == Method::GET
+            && path == "/"
+            && let Some(token) = query_token(request)
+        {
+            return self.sign_in(token);
+        }
+        if !self.authorized(request) {
+            return text_response(StatusCode::UNAUTHORIZED, "Missing or bad token");
+        }
+        match (request.method(), path) {
+            (&Method::GET, "/") => html_response(page::index(&self.open.views(), Instant::now())),
+            (&Method::GET, "/requests") => {
+                html_response(page::request_list(&self.open.views(), Instant::now()))
+            }
+            (&Method::GET, "/events") => self.events(),
+            (&Method::POST, _) => match parse_action(path) {
+                Some((request_id, action)) => self.answer(request_id, action, request.body()).await,
+                None => text_response(StatusCode::NOT_FOUND, "Not found"),
+            },
+            _ => text_response(StatusCode::NOT_FOUND, "Not found"),
+        }
+    }
+
+    /// Swaps a `?token=` link for a cookie, so the token leaves the URL.
+    fn sign_in(&self, token: &str) -> Response<Body> {
+        if !tokens_match(token, &self.config.token) {
+            return text_response(StatusCode::UNAUTHORIZED, "Missing or bad token");
+        }
+        let mut response = text_response(StatusCode::SEE_OTHER, "");
+        let headers = response.headers_mut();
+        headers.insert(LOCATION, "/".parse().expect("valid header"));
+        let cookie = format!("{COOKIE_NAME}={token}; Path=/; HttpOnly; SameSite=Strict");
+        if let Ok(cookie) = cookie.parse() {
+            headers.insert(SET_COOKIE, cookie);
+        }
+        response
+    }
+
+    /// A bearer token (for scripts) or the sign-in cookie (for browsers).
+    /// `SameSite=Strict` keeps other sites from posting forms with it.
+    fn authorized(&self, request: &Request<Bytes>) -> bool {
+        let presented = bearer_token(request).or_else(|| cookie_token(request));
+        presented.is_some_and(|token| tokens_match(token, &self.config.token))
+    }
+
+    /// Server-sent events: `changed` whenever the list should be re-fetched,
+    /// including once on (re)connecting in case something was missed.
+    fn events(&self) -> Response<Body> {
+        let changes = self.open.subscribe();
+        let stream = futures::stream::unfold((changes, true), |(mut changes, first)| async move {
+            if first {
+                return Some((
+                    Bytes::from("retry: 3000\nevent: changed\ndata:\n\n"),
+                    (changes, false),
+                ));
+            }
+            let chunk = match tokio::time::timeout(KEEPALIVE_INTERVAL, changes.recv()).await {
+                Err(_) => Bytes::from(": keepalive\n\n"),
+                Ok(Ok(()) | Err(broadcast::error::RecvError::Lagged(_))) => {
+                    Bytes::from("event: changed\ndata:\n\n")
+                }
+                Ok(Err(broadcast::error::RecvError::Closed)) => return None,
+            };
+            Some((chunk, (changes, false)))
+        });
+        let mut response = Response::new(crate::http::streamed(stream));
+        let headers = response.headers_mut();
+        headers.insert(
+            CONTENT_TYPE,
+            "text/event-stream".parse().expect("valid header"),
+        );
+        headers.insert(CACHE_CONTROL, "no-cache".parse().expect("valid header"));
+        response
+    }
+
+    async fn answer(&self, request_id: Uuid, action: &str, body: &[u8]) -> Response<Body> {
+        let verdict = match action {
+            "allow" => Verdict::Allow,
+            "deny" => Verdict::Deny,
+            "always" => Verdict::AlwaysAllow,
+            "reply" => {
+                #[derive(Deserialize)]
+                struct ReplyForm {
+                    #[serde(default)]
+                    text: String,
+                }
+                let text = serde_urlencoded::from_bytes::<ReplyForm>(body)
+                    .map(|form| form.text.trim().to_string())
+                    .unwrap_or_default();
+                if text.is_empty() {
+                    return text_response(StatusCode::BAD_REQUEST, "The reply is empty");
+                }
+                Verdict::Reply(text)
+            }
+            _ => return text_response(StatusCode::NOT_FOUND, "Not found"),
+        };
+        // Whoever holds the token; there are no accounts to tell apart
+        let approver = Approver {
+            id: "web".to_string(),
+            name: "web UI".to_string(),
+        };
+        match self.decisions.decide(request_id, verdict, approver).await {
+            DecisionOutcome::AlreadyHandled => text_response(
+                StatusCode::CONFLICT,
+                DecisionOutcome::AlreadyHandled
+                    .feedback()
+                    .unwrap_or_default(),
+            ),
+            outcome => text_response(StatusCode::OK, outcome.feedback().unwrap_or("Done")),
+        }
+    }
+}
+
+impl ApprovalChannel for WebChannel {
+    fn name(&self) -> &'static str {
+        "Web UI"
+    }
+
+    fn send_request<'a>(
+        &'a self,
+        view: &'a RequestView,
+        _page: Page<'a>,
+    ) -> BoxFuture<'a, anyhow::Result<()>> {
+        Box::pin(async move {
+            self.open.show(view.request_id());
+            Ok(())
+        })
+    }
+
+    fn update_status<'a>(
+        &'a self,
+        view: &'a RequestView,
+        update: &'a StatusUpdate,
+    ) -> BoxFuture<'a, ()> {
+        Box::pin(async move { self.open.update(view, update) })
+    }
+
+    fn decisions(&self) -> DecisionStream {
+        self.decisions.stream()
+    }
+
+    // Anyone who can reach the web UI decides alike
+    fn identifies_approvers(&self) -> bool {
+        false
+    }
+}
+
+fn query_token<B>(request: &Request<B>) -> Option<&str> {
+    request
+        .uri()
+        .query()?
+        .split('&')
+        .find_map(|pair| pair.strip_prefix("token="))
+}
+
+fn cookie_token<B>(request: &Request<B>) -> Option<&str> {
+    request
+        .headers()
+        .get_all(COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|cookies| cookies.split(';'))
+        .find_map(|cookie| cookie.trim().strip_prefix(COOKIE_NAME)?.strip_prefix('='))
+}
+
+/// `/requests/{id}/{action}`.
+fn parse_action(path: &str) -> Option<(Uuid, &str)> {
+    let (id, action) = path.strip_prefix("/requests/")?.split_once('/')?;
+    Some((id.parse().ok()?, action))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::channel::memory::pending_request;
+    use dashmap::DashMap;
+    use futures::StreamExt;
+    use http_body_util::BodyExt;
+
+    const TOKEN: &str = "0123456789abcdef";
+
+    fn make_channel() -> (Arc<WebChannel>, PendingMap) {
+        let config = WebConfig {
+            listen: "127.0.0.1:0".parse().unwrap(),
+            token: TOKEN.to_string(),
+        };
+        let pending_map: PendingMap = Arc::new(DashMap::new());
+        let channel = Arc::new(WebChannel::new(config, pending_map.clone()));
+        (channel, pending_map)
+    }
+
+    /// Adds a pending request and shows it, as `process_request` would.
+    async fn add_request(channel: &WebChannel, pending_map: &PendingMap, command: &str) -> Uuid {
+        let pending = pending_request(command);
+        let id = pending.request.request_id;
+        let view = pending.view();
+        pending_map.insert(id, pending);
+        let page = Page {
+            tier: 0,
+            deliveries: &[],
+        };
+        channel.send_request(&view, page).await.unwrap();
+        id
+    }
+
+    fn get(path: &str) -> Request<Bytes> {
+        Request::get(path)
+            .header("cookie", format!("theme=dark; {COOKIE_NAME}={TOKEN}"))
+            .body(Bytes::new())
+            .unwrap()
+    }
+
+    async fn text_of(response: Response<Body>) -> String {
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        String::from_utf8(body.to_vec()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn token_link_sets_cookie_and_guards_pages() {
+        let (channel, _) = make_channel();
+        let signed_in = channel
+            .handle_request(
+                &Request::get(format!("/?token={TOKEN}"))
+                    .body(Bytes::new())
+                    .unwrap(),
+            )
+            .await;
+        assert_eq!(signed_in.status(), StatusCode::SEE_OTHER);
+        let cookie = signed_in.headers()[SET_COOKIE].to_str().unwrap();
+        assert!(cookie.starts_with(&format!("{COOKIE_NAME}={TOKEN};")));
+        assert!(cookie.contains("SameSite=Strict"));
+
+        let bad = channel
+            .handle_request(&Request::get("/?token=guess").body(Bytes::new()).unwrap())
+            .await;
+        assert_eq!(bad.status(), StatusCode::UNAUTHORIZED);
+        let anonymous = channel
+            .handle_request(&Request::get("/requests").body(Bytes::new()).unwrap())
+            .await;
+        assert_eq!(anonymous.status(), StatusCode::UNAUTHORIZED);
+        let scripted = Request::get("/requests")
+            .header("authorization", format!("Bearer {TOKEN}"))
+            .body(Bytes::new())
+            .unwrap();
+        assert_eq!(
+            channel.handle_request(&scripted).await.status(),
+            StatusCode::OK
+        );
+    }
+
+    #[tokio::test]
+    async fn lists_open_requests_until_closed() {
+        let (channel, pending_map) = make_channel();
+        let id = add_request(&channel, &pending_map, "cargo test --workspace").await;
+        let list = text_of(channel.handle_request(&get("/requests")).await).await;
+        assert!(list.contains("cargo test --workspace"));
+
+        let (_, pending) = pending_map.remove(&id).unwrap();
+        let status = StatusUpdate::Resolved("\u{2705} Approved".to_string());
+        channel.update_status(&pending.view(), &status).await;
+        let list = text_of(channel.handle_request(&get("/requests")).await).await;
+        assert!(list.contains("Nothing waiting"));
+    }
+
+    #[tokio::test]
+    async fn forms_become_decisions() {
+        let (channel, pending_map) = make_channel();
+        let mut decisions = channel.decisions();
+        let id = add_request(&channel, &pending_map, "ls").await;
+
+        let reply = Request::post(format!("/requests/{id}/reply"))
+            .header("cookie", format!("{COOKIE_NAME}={TOKEN}"))
+            .body(Bytes::from("text=+use+pytest+"))
+            .unwrap();
+        let answered = tokio::spawn({
+            let channel = channel.clone();
+            async move { channel.handle_request(&reply).await }
+        });
+        let decision = decisions.next().await.unwrap();
+        assert_eq!(decision.request_id, id);
+        assert_eq!(decision.verdict, Verdict::Reply("use pytest".to_string()));
+        assert_eq!(decision.approver.id, "web");
+        decision
+            .ack
+            .unwrap()
+            .send(DecisionOutcome::AlreadyHandled)
+            .unwrap();
+        let response = answered.await.unwrap();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+        assert_eq!(
+            text_of(response).await,
+            "This request has already been handled"
+        );
+
+        let empty = Request::post(format!("/requests/{id}/reply"))
+            .header("cookie", format!("{COOKIE_NAME}={TOKEN}"))
+            .body(Bytes::from("text=++"))
+            .unwrap();
+        assert_eq!(
+            channel.handle_request(&empty).await.status(),
+            StatusCode::BAD_REQUEST
+        );
+    }
+
+    #[tokio::test]
+    async fn event_stream_announces_changes() {
+        let (channel, pending_map) = make_channel();
+        let response = channel.handle_request(&get("/events")).await;
+        assert_eq!(response.headers()[CONTENT_TYPE], "text/event-stream");
+        let mut body = response.into_body();
+        let first = body.frame().await.unwrap().unwrap().into_data().unwrap();
+        assert!(first.ends_with(b"event: changed\ndata:\n\n"));
+
+        add_request(&channel, &pending_map, "ls").await;
+        let next = body.frame().await.unwrap().unwrap().into_data().unwrap();
+        assert_eq!(&next[..], b"event: changed\ndata:\n\n");
+    }
+}
@@ -0,0 +1,36 @@
+pub mod channel;
+pub mod page;
+
+use serde::Deserialize;
+use std::net::SocketAddr;
+
+/// Shortest token accepted, so it can't be guessed.
+const MIN_TOKEN_CHARS: usize = 16;
+
+/// A browser UI listing pending requests, with forms to answer them.
+#[derive(Debug, Deserialize, Clone)]
+pub struct WebConfig {
+    /// Address to serve on: localhost, or e.g. a tailnet IP.
+    #[serde(default = "default_listen")]
+    pub listen: SocketAddr,
+    /// Secret for opening the UI (`http://<listen>/?token=...`).
+    pub token: String,
+}
+
+fn default_listen() -> SocketAddr {
+    SocketAddr::from(([127, 0, 0, 1], 8793))
+}
+
+impl WebConfig {
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if self.token.len() < MIN_TOKEN_CHARS {
+            anyhow::bail!("web token must be at least {MIN_TOKEN_CHARS} characters");
+        }
+        // It ends up in a URL and a cookie
+        let url_safe = |c: char| c.is_ascii_alphanumeric() || c == '-' || c == '_';
+        if !self.token.chars().all(url_safe) {
+            anyhow::bail!("web token must be letters, digits, '-' and '_'");
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,221 @@
+use crate::channel::RequestView;
+use crate::models::IpcRequest;
+use crate::telegram::formatter::{escape_html, format_time_left, project_name};
+use serde_json::Value;
+use std::fmt::Write;
+use tokio::time::Instant;
+
+const STYLE: &str = r"
+body { font-family: system-ui, sans-serif; margin: 0 auto; max-width: 60rem; padding: 1rem; }
+article { border: 1px solid #ccc; border-radius: 6px; margin: 1rem 0; padding: 0 1rem 1rem; }
+pre { background: #f6f6f6; overflow-x: auto; padding: 0.5rem; white-space: pre-wrap; }
+ins { background: #e6ffec; text-decoration: none; display: block; }
+del { background: #ffebe9; text-decoration: none; display: block; }
+.meta { color: #666; font-size: 0.9rem; }
+form { display: inline-block; margin: 0.25rem 0.25rem 0 0; }
+textarea { display: block; width: 40rem; max-width: 100%; }
+#notice:empty { display: none; }
+#notice { background: #fff8c5; padding: 0.5rem; }
+";
+
+/// Re-fetches the list on every change the server announces, but not while
+/// a half-written reply would be thrown away. Forms are sent in the
+/// background and their answer shown at the top.
+const SCRIPT: &str = r"
+const list = document.getElementById('requests');
+const notice = document.getElementById('notice');
+let stale = false;
+async function refresh() {
+  const active = document.activeElement;
+  if (active && active.tagName === 'TEXTAREA' && active.value) { stale = true; return; }
+  stale = false;
+  const response = await fetch('requests');
+  if (response.ok) list.innerHTML = await response.text();
+}
+document.addEventListener('focusout', () => { if (stale) refresh(); });
+new EventSource('events').addEventListener('changed', refresh);
+setInterval(refresh, 30000);
+document.addEventListener('submit', async (event) => {
+  event.preventDefault();
+  const form = event.target;
+  const response = await fetch(form.action, {
+    method: 'POST',
+    body: new URLSearchParams(new FormData(form)),
+  });
+  notice.textContent = await response.text();
+  form.reset();
+  stale = false;
+  refresh();
+});
+";
+
+/// The whole page.
+pub fn index(views: &[RequestView], now: Instant) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"en\"><head><meta charset=\"utf-8\">\
+         <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\
+         <title>Pending permission requests</title><style>{STYLE}</style></head>\
+         <body><h1>Pending permission requests</h1><p id=\"notice\"></p>\
+         <main id=\"requests\">{}</main><script>{SCRIPT}</script></body></html>",
+        request_list(views, now)
+    )
+}
+
+/// The list of open requests, as swapped in on every change.
+pub fn request_list(views: &[RequestView], now: Instant) -> String {
+    if views.is_empty() {
+        return "<p>Nothing waiting for you.</p>".to_string();
+    }
+    views.iter().map(|view| request_card(view, now)).collect()
+}
+
+fn request_card(view: &RequestView, now: Instant) -> String {
+    let request = &view.request;
+    let mut card = format!(
+        "<article><h2>{} &middot; {}</h2>",
+        escape_html(&request.tool_name),
+        escape_html(project_name(&request.cwd))
+    );
+    let mut status = vec![format_time_left(
+        view.deadline.saturating_duration_since(now),
+    )];
+    if view.required_approvals > 1 {
+        status.push(crate::quorum::format_progress(
+            &view.approvals,
+            view.required_approvals,
+        ));
+    }
+    let _ = write!(
+        card,
+        "<p class=\"meta\">{} &middot; {} &middot; session <code>{}</code></p>",
+        status.join(" &middot; "),
+        escape_html(&request.cwd),
+        escape_html(&request.session_id)
+    );
+    if let Some(context) = &request.assistant_context {
+        let _ = write!(card, "<blockquote>{}</blockquote>", escape_html(context));
+    }
+    card.push_str(&tool_body(request));
+
+    let action = |verdict: &str, label: &str| {
+        format!(
+            "<form method=\"post\" action=\"requests/{}/{verdict}\"><button>{label}</button></form>",
+            view.request_id()
+        )
+    };
+    card.push_str(&action("allow", "\u{2705} Allow"));
+    card.push_str(&action("deny", "\u{274c} Deny"));
+    if view.offers_always_allow() {
+        card.push_str(&action("always", "\u{1f513} Always Allow"));
+    }
+    let _ = write!(
+        card,
+        "<form method=\"post\" action=\"requests/{}/reply\">\
+         <textarea name=\"text\" rows=\"2\" required placeholder=\"Reply with instructions instead\"></textarea>\
+         <button>\u{1f4ac} Reply</button></form></article>",
+        view.request_id()
+    );
+    card
+}
+
+/// The tool input in full: the command, the whole file, or the edit as a
+/// diff.
+fn tool_body(request: &IpcRequest) -> String {
+    let input = &request.tool_input;
+    let field = |name: &str| input.get(name).and_then(Value::as_str);
+    match (request.tool_name.as_str(), field("file_path")) {
+        ("Bash", _) if field("command").is_some() => {
+            let mut body = pre(field("command").unwrap_or_default());
+            if let Some(description) = field("description") {
+                let _ = write!(body, "<p class=\"meta\">{}</p>", escape_html(description));
+            }
+            body
+        }
+        ("Write", Some(file_path)) => format!(
+            "<p><code>{}</code></p>{}",
+            escape_html(file_path),
+            pre(field("content").unwrap_or_default())
+        ),
+        ("Edit", Some(file_path)) => format!(
+            "<p><code>{}</code></p>{}",
+            escape_html(file_path),
+            diff(
+                field("old_string").unwrap_or_default(),
+                field("new_string").unwrap_or_default()
+            )
+        ),
+        _ => pre(&serde_json::to_string_pretty(input).unwrap_or_default()),
+    }
+}
+
+fn pre(text: &str) -> String {
+    format!("<pre>{}</pre>", escape_html(text))
+}
+
+/// The removed lines, then the added ones.
+fn diff(old: &str, new: &str) -> String {
+    let mut diff = String::from("<pre>");
+    for line in old.lines() {
+        let _ = write!(diff, "<del>- {}</del>", escape_html(line));
+    }
+    for line in new.lines() {
+        let _ = write!(diff, "<ins>+ {}</ins>", escape_html(line));
+    }
+    diff.push_str("</pre>");
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use uuid::Uuid;
+
+    fn make_view(tool_name: &str, tool_input: Value) -> RequestView {
+        RequestView {
+            request: IpcRequest {
+                request_id: Uuid::new_v4(),
+                tool_name: tool_name.to_string(),
+                tool_input,
+                cwd: "/home/me/proj".to_string(),
+                session_id: "abcdef1234".to_string(),
+                permission_suggestions: vec![],
+                assistant_context: Some("Fixing <the> bug".to_string()),
+                first_prompt: None,
+            },
+            summary: "Edit".to_string(),
+            deadline: Instant::now() + std::time::Duration::from_secs(150),
+            required_approvals: 1,
+            approvals: vec![],
+        }
+    }
+
+    #[test]
+    fn shows_content_in_full() {
+        let command = format!("echo {}", "x".repeat(2000));
+        let view = make_view("Bash", json!({"command": command}));
+        let html = request_list(std::slice::from_ref(&view), Instant::now());
+        assert!(html.contains(&command));
+        assert!(html.contains("Fixing &lt;the&gt; bug"));
+        assert!(html.contains(&format!("action=\"requests/{}/allow\"", view.request_id())));
+        assert!(!html.contains("/always\""));
+    }
+
+    #[test]
+    fn edits_render_as_diff() {
+        let view = make_view(
+            "Edit",
+            json!({"file_path": "/src/a.rs", "old_string": "a\nb", "new_string": "a\n<c>"}),
+        );
+        let html = tool_body(&view.request);
+        assert_eq!(
+            html,
+            "<p><code>/src/a.rs</code></p><pre><del>- a</del><del>- b</del><ins>+ a</ins><ins>+ &lt;c&gt;</ins></pre>"
+        );
+    }
+
+    #[test]
+    fn empty_list_says_so() {
+        assert!(index(&[], Instant::now()).contains("Nothing waiting for you."));
+    }
+}
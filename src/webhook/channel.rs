@@ -0,0 +1,436 @@
+use crate::channel::{
+    ApprovalChannel, DecisionOutcome, DecisionQueue, DecisionStream, Page, RequestView,
+    StatusUpdate, Verdict,
+};
+use crate::http::{Body, Handler, json_response, text_response};
+use crate::models::{Approver, Decision, IpcRequest};
+use crate::telegram::formatter::project_name;
+use crate::webhook::WebhookConfig;
+use crate::webhook::signature::{self, SIGNATURE_HEADER, TIMESTAMP_HEADER};
+use bytes::Bytes;
+use chrono::{DateTime, Utc};
+use futures::future::BoxFuture;
+use hyper::{Method, Request, Response, StatusCode};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::sync::Arc;
+use tokio::time::Instant;
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
+
+/// Header naming the event, as in the payload's `event` field.
+const EVENT_HEADER: &str = "x-reachout-event";
+
+/// What the webhook receives.
+#[derive(Debug, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+enum WebhookEvent<'a> {
+    /// A new request, answered at `POST /requests/{request_id}/decision`.
+    Request {
+        request: &'a IpcRequest,
+        summary: &'a str,
+        project: &'a str,
+        /// When the request falls back to the terminal, unless extended.
+        expires_at: DateTime<Utc>,
+        required_approvals: usize,
+        approvals: Vec<&'a str>,
+        offers_always_allow: bool,
+    },
+    /// The request was answered (anywhere) or withdrawn.
+    Closed { request_id: Uuid, status: &'a str },
+}
+
+impl WebhookEvent<'_> {
+    const fn name(&self) -> &'static str {
+        match self {
+            Self::Request { .. } => "request",
+            Self::Closed { .. } => "closed",
+        }
+    }
+}
+
+/// Body of `POST /requests/{id}/decision`, in `IpcResponse`'s terms.
+#[derive(Debug, Deserialize)]
+struct DecisionBody {
+    /// Must match the URL, so a signed decision can't be replayed against
+    /// another request.
+    request_id: Uuid,
+    decision: Decision,
+    /// The instructions, for a `Reply`.
+    message: Option<String>,
+    /// Who decided, shown in the other channels. Anyone with the secret can
+    /// claim any name, so all webhook decisions count as one approver.
+    approver: Option<String>,
+}
+
+/// A webhook as an approval channel: requests go out as signed JSON and
+/// decisions come back, signed the same way, at the decision endpoint.
+pub struct WebhookChannel {
+    client: reqwest::Client,
+    config: WebhookConfig,
+    decisions: DecisionQueue,
+}
+
+pub type WebhookState = Arc<WebhookChannel>;
+
+impl WebhookChannel {
+    pub fn new(config: WebhookConfig) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            config,
+            decisions: DecisionQueue::default(),
+        }
+    }
+
+    /// Serves the decision endpoint until `cancel_token` fires.
+    pub async fn serve(self: Arc<Self>, cancel_token: CancellationToken) -> anyhow::Result<()> {
+        let addr = self.config.listen;
+        let handler: Handler = Arc::new(move |request| {
+            let channel = self.clone();
+            Box::pin(async move { channel.handle_request(&request).await })
+        });
+        crate::http::serve(addr, handler, cancel_token).await
+    }
+
+    async fn handle_request(&self, request: &Request<Bytes>) -> Response<Body> {
+        let request_id = request
+            .uri()
+            .path()
+            .strip_prefix("/requests/")
+            .and_then(|rest| rest.strip_suffix("/decision"))
+            .and_then(|id| id.parse::<Uuid>().ok());
+        let Some(request_id) = request_id.filter(|_| request.method() == Method::POST) else {
+            return text_response(StatusCode::NOT_FOUND, "Not found");
+        };
+        let header = |name: &str| {
+            request
+                .headers()
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .unwrap_or_default()
+        };
+        let signed = signature::verify(
+            &self.config.secret,
+            header(TIMESTAMP_HEADER),
+            request.body(),
+            header(SIGNATURE_HEADER),
+            Utc::now().timestamp(),
+        );
+        if !signed {
+            tracing::warn!(request_id = %request_id, "Webhook decision with a bad signature");
+            return text_response(StatusCode::UNAUTHORIZED, "Bad signature");
+        }
+        let body: DecisionBody = match serde_json::from_slice(request.body()) {
+            Ok(body) => body,
+            Err(e) => return text_response(StatusCode::BAD_REQUEST, &e.to_string()),
+        };
+        if body.request_id != request_id {
+            tracing::warn!(request_id = %request_id, "Webhook decision signed for another request");
+            return text_response(StatusCode::BAD_REQUEST, "request_id does not match the URL");
+        }
+        let verdict = match (body.decision, body.message) {
+            (Decision::Allow, _) => Verdict::Allow,
+            (Decision::Deny, _) => Verdict::Deny,
+            (Decision::AlwaysAllow, _) => Verdict::AlwaysAllow,
+            (Decision::Reply, Some(message)) if !message.trim().is_empty() => {
+                Verdict::Reply(message)
+            }
+            (Decision::Reply, _) => {
+                return text_response(StatusCode::BAD_REQUEST, "A Reply needs a message");
+            }
+            (Decision::Timeout, _) => {
+                return text_response(StatusCode::BAD_REQUEST, "Timeout is not a decision");
+            }
+        };
+        let name = body.approver.unwrap_or_else(|| "webhook".to_string());
+        let approver = Approver {
+            id: "webhook".to_string(),
+            name,
+        };
+
+        let outcome = match self.decisions.decide(request_id, verdict, approver).await {
+            DecisionOutcome::AlreadyHandled => {
+                let mut response = json_response(&json!({"outcome": "already_handled"}));
+                *response.status_mut() = StatusCode::CONFLICT;
+                return response;
+            }
+            DecisionOutcome::UnidentifiedApprover => {
+                let mut response = json_response(&json!({"outcome": "unidentified_approver"}));
+                *response.status_mut() = StatusCode::FORBIDDEN;
+                return response;
+            }
+            DecisionOutcome::Resolved => "resolved",
+            DecisionOutcome::ApprovalRecorded => "approval_recorded",
+            DecisionOutcome::DuplicateApproval => "duplicate_approval",
+        };
+        json_response(&json!({"outcome": outcome}))
+    }
+
+    /// Posts `event` to the webhook, signed.
+    async fn post(&self, event: &WebhookEvent<'_>) -> anyhow::Result<()> {
+        let body = serde_json::to_vec(event)?;
+        let timestamp = Utc::now().timestamp();
+        self.client
+            .post(&self.config.url)
+            .header(hyper::header::CONTENT_TYPE.as_str(), "application/json")
+            .header(EVENT_HEADER, event.name())
+            .header(TIMESTAMP_HEADER, timestamp.to_string())
+            .header(
+                SIGNATURE_HEADER,
+                signature::sign(&self.config.secret, timestamp, &body),
+            )
+            .body(body)
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+}
+
+impl ApprovalChannel for WebhookChannel {
+    fn name(&self) -> &'static str {
+        "Webhook"
+    }
+
+    fn send_request<'a>(
+        &'a self,
+        view: &'a RequestView,
+        _page: Page<'a>,
+    ) -> BoxFuture<'a, anyhow::Result<()>> {
+        Box::pin(async move {
+            let left = view.deadline.saturating_duration_since(Instant::now());
+            let event = WebhookEvent::Request {
+                request: &view.request,
+                summary: &view.summary,
+                project: project_name(&view.request.cwd),
+                expires_at: Utc::now() + left,
+                required_approvals: view.required_approvals,
+                approvals: view.approvals.iter().map(|a| a.name.as_str()).collect(),
+                offers_always_allow: view.offers_always_allow(),
+            };
+            self.post(&event).await
+        })
+    }
+
+    fn update_status<'a>(
+        &'a self,
+        view: &'a RequestView,
+        update: &'a StatusUpdate,
+    ) -> BoxFuture<'a, ()> {
+        Box::pin(async move {
+            let status = match update {
+                StatusUpdate::Progress | StatusUpdate::Extended | StatusUpdate::Reminder => return,
+                StatusUpdate::Withdrawn(status) | StatusUpdate::Resolved(status) => status,
+            };
+            let event = WebhookEvent::Closed {
+                request_id: view.request_id(),
+                status,
+            };
+            if let Err(e) = self.post(&event).await {
+                tracing::warn!("Failed to post webhook event: {e}");
+            }
+        })
+    }
+
+    fn decisions(&self) -> DecisionStream {
+        self.decisions.stream()
+    }
+
+    // Every webhook caller shares one identity
+    fn identifies_approvers(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::test_support::{json_body, recording_server};
+    use futures::StreamExt;
+    use serde_json::Value;
+    use tokio::sync::mpsc;
+
+    const SECRET: &str = "webhook-secret-0123456789";
+
+    /// Stands in for the webhook, reporting each call as
+    /// `(event header, signature verified, body)`.
+    async fn fake_webhook() -> (String, mpsc::UnboundedReceiver<(String, bool, Value)>) {
+        let record = |request: &Request<Bytes>| {
+            let header = |name: &str| {
+                request.headers()[name]
+                    .to_str()
+                    .unwrap_or_default()
+                    .to_string()
+            };
+            let verified = signature::verify(
+                SECRET,
+                &header(TIMESTAMP_HEADER),
+                request.body(),
+                &header(SIGNATURE_HEADER),
+                Utc::now().timestamp(),
+            );
+            Some((header(EVENT_HEADER), verified, json_body(request)))
+        };
+        let respond =
+            |_: &Request<Bytes>| std::future::ready(text_response(StatusCode::NO_CONTENT, ""));
+        let (url, calls) = recording_server(record, respond).await;
+        (format!("{url}/hooks/reachout"), calls)
+    }
+
+    fn make_channel(url: &str) -> Arc<WebhookChannel> {
+        Arc::new(WebhookChannel::new(WebhookConfig {
+            url: url.to_string(),
+            secret: SECRET.to_string(),
+            listen: "127.0.0.1:0".parse().unwrap(),
+        }))
+    }
+
+    fn make_view() -> RequestView {
+        RequestView {
+            request: IpcRequest {
+                request_id: Uuid::new_v4(),
+                tool_name: "Bash".to_string(),
+                tool_input: json!({"command": "ls"}),
+                cwd: "/tmp/proj".to_string(),
+                session_id: "s".to_string(),
+                permission_suggestions: vec![json!({"type": "addRules"})],
+                assistant_context: None,
+                first_prompt: None,
+            },
+            summary: "Bash: ls".to_string(),
+            deadline: Instant::now() + std::time::Duration::from_secs(150),
+            required_approvals: 1,
+            approvals: vec![],
+        }
+    }
+
+    /// A decision for `request_id`, with `body` plus its `request_id`.
+    fn decision_request(request_id: Uuid, body: &Value, secret: &str) -> Request<Bytes> {
+        let mut body = body.clone();
+        body["request_id"] = json!(request_id);
+        signed_request(
+            &format!("/requests/{request_id}/decision"),
+            body.to_string(),
+            secret,
+        )
+    }
+
+    fn signed_request(path: &str, body: String, secret: &str) -> Request<Bytes> {
+        let timestamp = Utc::now().timestamp();
+        Request::post(path)
+            .header(TIMESTAMP_HEADER, timestamp.to_string())
+            .header(
+                SIGNATURE_HEADER,
+                signature::sign(secret, timestamp, body.as_bytes()),
+            )
+            .body(Bytes::from(body))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn posts_signed_requests_and_closings() {
+        let (url, mut calls) = fake_webhook().await;
+        let channel = make_channel(&url);
+        let view = make_view();
+
+        let page = Page {
+            tier: 0,
+            deliveries: &[],
+        };
+        channel.send_request(&view, page).await.unwrap();
+        let (event, verified, body) = calls.recv().await.unwrap();
+        assert_eq!(event, "request");
+        assert!(verified);
+        assert_eq!(body["event"], "request");
+        assert_eq!(body["request"]["request_id"], view.request_id().to_string());
+        assert_eq!(body["request"]["tool_input"]["command"], "ls");
+        assert_eq!(body["project"], "proj");
+        assert_eq!(body["offers_always_allow"], true);
+        let expires_at: DateTime<Utc> = body["expires_at"].as_str().unwrap().parse().unwrap();
+        assert!(expires_at > Utc::now() + chrono::Duration::seconds(100));
+
+        channel
+            .update_status(
+                &view,
+                &StatusUpdate::Resolved("\u{2705} Approved".to_string()),
+            )
+            .await;
+        let (event, verified, body) = calls.recv().await.unwrap();
+        assert_eq!(event, "closed");
+        assert!(verified);
+        assert_eq!(
+            body,
+            json!({
+                "event": "closed",
+                "request_id": view.request_id(),
+                "status": "\u{2705} Approved",
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn signed_decisions_are_applied() {
+        let channel = make_channel("http://127.0.0.1:9/");
+        let mut decisions = channel.decisions();
+        let id = Uuid::new_v4();
+
+        let body = json!({"decision": "Reply", "message": "use nextest", "approver": "ci-bot"});
+        let response = channel
+            .handle_request(&decision_request(id, &body, "some-other-secret-000"))
+            .await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        let response = channel
+            .handle_request(&decision_request(id, &json!({"decision": "Reply"}), SECRET))
+            .await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let response = channel
+            .handle_request(&decision_request(
+                id,
+                &json!({"decision": "Timeout"}),
+                SECRET,
+            ))
+            .await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let decided = tokio::spawn({
+            let channel = channel.clone();
+            let request = decision_request(id, &body, SECRET);
+            async move { channel.handle_request(&request).await }
+        });
+        let decision = decisions.next().await.unwrap();
+        assert_eq!(decision.request_id, id);
+        assert_eq!(decision.verdict, Verdict::Reply("use nextest".to_string()));
+        assert_eq!(decision.approver.id, "webhook");
+        assert_eq!(decision.approver.name, "ci-bot");
+        decision
+            .ack
+            .unwrap()
+            .send(DecisionOutcome::AlreadyHandled)
+            .unwrap();
+        assert_eq!(decided.await.unwrap().status(), StatusCode::CONFLICT);
+    }
+
+    #[tokio::test]
+    async fn signed_decisions_only_answer_their_request() {
+        let channel = make_channel("http://127.0.0.1:9/");
+        let signed_for = Uuid::new_v4();
+        let other = Uuid::new_v4();
+
+        let captured = decision_request(signed_for, &json!({"decision": "Allow"}), SECRET);
+        let replayed = Request::post(format!("/requests/{other}/decision"))
+            .header(TIMESTAMP_HEADER, &captured.headers()[TIMESTAMP_HEADER])
+            .header(SIGNATURE_HEADER, &captured.headers()[SIGNATURE_HEADER])
+            .body(captured.body().clone())
+            .unwrap();
+        let response = channel.handle_request(&replayed).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let unnamed = signed_request(
+            &format!("/requests/{other}/decision"),
+            json!({"decision": "Allow"}).to_string(),
+            SECRET,
+        );
+        let response = channel.handle_request(&unnamed).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+}
@@ -0,0 +1,38 @@
+pub mod channel;
+pub mod signature;
+
+use serde::Deserialize;
+use std::net::SocketAddr;
+
+/// Shortest secret accepted, so signatures can't be forged by guessing it.
+const MIN_SECRET_CHARS: usize = 16;
+
+/// Posting requests to a webhook of your own, and a local REST endpoint
+/// where it (or anything else holding the secret) sends decisions back.
+#[derive(Debug, Deserialize, Clone)]
+pub struct WebhookConfig {
+    /// Receives every request and its closing as signed JSON.
+    pub url: String,
+    /// Shared HMAC secret: signs what the bot sends, and must sign the
+    /// decisions it receives.
+    pub secret: String,
+    /// Address of the decision endpoint (`POST /requests/{id}/decision`).
+    #[serde(default = "default_listen")]
+    pub listen: SocketAddr,
+}
+
+fn default_listen() -> SocketAddr {
+    SocketAddr::from(([127, 0, 0, 1], 8795))
+}
+
+impl WebhookConfig {
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if !(self.url.starts_with("https://") || self.url.starts_with("http://")) {
+            anyhow::bail!("webhook url must be an http(s) URL");
+        }
+        if self.secret.len() < MIN_SECRET_CHARS {
+            anyhow::bail!("webhook secret must be at least {MIN_SECRET_CHARS} characters");
+        }
+        Ok(())
+    }
+}
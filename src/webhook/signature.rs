@@ -0,0 +1,100 @@
+use ring::hmac;
+
+/// Header carrying the Unix time the payload was signed at.
+pub const TIMESTAMP_HEADER: &str = "x-reachout-timestamp";
+
+/// Header carrying `sha256=<hex HMAC-SHA256>` of `{timestamp}.{body}`.
+pub const SIGNATURE_HEADER: &str = "x-reachout-signature";
+
+/// Payloads signed longer ago than this are rejected as possible replays.
+const MAX_AGE_SECS: i64 = 5 * 60;
+
+/// The signature header value for `body` sent at `timestamp`.
+pub fn sign(secret: &str, timestamp: i64, body: &[u8]) -> String {
+    let key = hmac::Key::new(hmac::HMAC_SHA256, secret.as_bytes());
+    let tag = hmac::sign(&key, &signed_payload(&timestamp.to_string(), body));
+    format!("sha256={}", hex::encode(tag.as_ref()))
+}
+
+/// Checks a signature made by [`sign`] and that it is recent.
+pub fn verify(secret: &str, timestamp: &str, body: &[u8], signature: &str, now: i64) -> bool {
+    let Ok(signed_at) = timestamp.parse::<i64>() else {
+        return false;
+    };
+    if (now - signed_at).abs() > MAX_AGE_SECS {
+        return false;
+    }
+    let Some(tag) = signature
+        .strip_prefix("sha256=")
+        .and_then(|hex_tag| hex::decode(hex_tag).ok())
+    else {
+        return false;
+    };
+    let key = hmac::Key::new(hmac::HMAC_SHA256, secret.as_bytes());
+    hmac::verify(&key, &signed_payload(timestamp, body), &tag).is_ok()
+}
+
+fn signed_payload(timestamp: &str, body: &[u8]) -> Vec<u8> {
+    let mut payload = format!("{timestamp}.").into_bytes();
+    payload.extend_from_slice(body);
+    payload
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECRET: &str = "0123456789abcdef";
+
+    #[test]
+    fn known_signature() {
+        // printf '1700000000.{}' | openssl dgst -sha256 -hmac 0123456789abcdef
+        assert_eq!(
+            sign(SECRET, 1_700_000_000, b"{}"),
+            "sha256=e4f8e2ecae2295b2ddb2f0b5584c8275e226c0ebe9b3b819e70156bb67122e3e"
+        );
+    }
+
+    #[test]
+    fn verifies_recent_untampered_payloads() {
+        let now = 1_700_000_000;
+        let signature = sign(SECRET, now, b"{\"decision\":\"Allow\"}");
+        let ts = now.to_string();
+        assert!(verify(
+            SECRET,
+            &ts,
+            b"{\"decision\":\"Allow\"}",
+            &signature,
+            now + 60
+        ));
+        assert!(!verify(
+            SECRET,
+            &ts,
+            b"{\"decision\":\"Deny\"}",
+            &signature,
+            now
+        ));
+        assert!(!verify(
+            "fedcba9876543210",
+            &ts,
+            b"{\"decision\":\"Allow\"}",
+            &signature,
+            now
+        ));
+        assert!(!verify(
+            SECRET,
+            &ts,
+            b"{\"decision\":\"Allow\"}",
+            "sha256=zz",
+            now
+        ));
+        let later = now + MAX_AGE_SECS + 1;
+        assert!(!verify(
+            SECRET,
+            &ts,
+            b"{\"decision\":\"Allow\"}",
+            &signature,
+            later
+        ));
+    }
+}
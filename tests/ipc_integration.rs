@@ -3,7 +3,7 @@ use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
 use tokio::net::UnixListener;
 use uuid::Uuid;
 use vibe_reachout::ipc::client::send_request;
-use vibe_reachout::models::{Decision, IpcRequest, IpcResponse};
+use vibe_reachout::models::{Decision, IpcKeepalive, IpcRequest, IpcResponse};
 
 fn make_request() -> IpcRequest {
     IpcRequest {
@@ -14,6 +14,7 @@ fn make_request() -> IpcRequest {
         session_id: "test-session".to_string(),
         permission_suggestions: vec![],
         assistant_context: None,
+        first_prompt: None,
     }
 }
 
@@ -152,3 +153,40 @@ async fn client_times_out_when_server_does_not_respond() {
     let err_msg = result.unwrap_err().to_string();
     assert!(err_msg.contains("timed out"));
 }
+
+#[tokio::test]
+async fn client_keepalive_extends_timeout() {
+    let tmp = tempfile::tempdir().unwrap();
+    let socket_path = tmp.path().join("keepalive.sock");
+
+    // Server that extends the request, then answers after the original timeout
+    let listener = UnixListener::bind(&socket_path).unwrap();
+    let _server = tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let (reader, mut writer) = stream.into_split();
+
+        let mut buf_reader = BufReader::new(reader);
+        let mut line = String::new();
+        buf_reader.read_line(&mut line).await.unwrap();
+        let request: IpcRequest = serde_json::from_str(line.trim()).unwrap();
+
+        let keepalive = IpcKeepalive {
+            request_id: request.request_id,
+            extend_seconds: 5,
+        };
+        let mut json = serde_json::to_string(&keepalive).unwrap();
+        json.push('\n');
+        writer.write_all(json.as_bytes()).await.unwrap();
+
+        tokio::time::sleep(std::time::Duration::from_millis(1500)).await;
+
+        let mut json = serde_json::to_string(&IpcResponse::allow(request.request_id)).unwrap();
+        json.push('\n');
+        writer.write_all(json.as_bytes()).await.unwrap();
+    });
+
+    let request = make_request();
+    let response = send_request(&socket_path, &request, 1).await.unwrap();
+
+    assert_eq!(response.decision, Decision::Allow);
+}